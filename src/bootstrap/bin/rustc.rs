@@ -135,9 +135,10 @@ fn main() {
         }
 
         // Pass down extra flags, commonly used to configure `-Clinker` when
-        // cross compiling.
+        // cross compiling. They're separated by the unit separator character
+        // rather than spaces, as they may contain paths with spaces.
         if let Ok(s) = env::var("RUSTC_FLAGS") {
-            cmd.args(&s.split(" ").filter(|s| !s.is_empty()).collect::<Vec<_>>());
+            cmd.args(&s.split('\x1f').filter(|s| !s.is_empty()).collect::<Vec<_>>());
         }
 
         // Pass down incremental directory, if any.
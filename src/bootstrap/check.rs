@@ -20,13 +20,15 @@ use std::env;
 use std::fmt;
 use std::fs::{self, File};
 use std::path::{PathBuf, Path};
-use std::process::Command;
+use std::process::{self, Command};
 use std::io::Read;
 
 use build_helper::output;
 
 use {Build, Compiler, Mode};
+use compile;
 use dist;
+use reproducible;
 use util::{self, dylib_path, dylib_path_var, exe};
 
 const ADB_TEST_DIR: &'static str = "/data/tmp/work";
@@ -161,6 +163,7 @@ pub fn tidy(build: &Build, host: &str) {
     if build.config.quiet_tests {
         cmd.arg("--quiet");
     }
+    cmd.args(&build.flags.cmd.test_args());
     try_run(build, &mut cmd);
 }
 
@@ -198,7 +201,13 @@ pub fn compiletest(build: &Build,
     cmd.arg("--mode").arg(mode);
     cmd.arg("--target").arg(target);
     cmd.arg("--host").arg(compiler.host);
-    cmd.arg("--llvm-filecheck").arg(build.llvm_filecheck(&build.config.build));
+
+    // Codegen tests are checked by compiletest's own FileCheck, unless LLVM's
+    // is around.
+    let filecheck = build.llvm_filecheck(&build.config.build);
+    if filecheck.exists() {
+        cmd.arg("--llvm-filecheck").arg(filecheck);
+    }
 
     if let Some(nodejs) = build.config.nodejs.as_ref() {
         cmd.arg("--nodejs").arg(nodejs);
@@ -344,11 +353,11 @@ pub fn docs(build: &Build, compiler: &Compiler) {
 }
 
 /// Run the error index generator tool to execute the tests located in the error
-/// index.
+/// index and the lint index.
 ///
 /// The `error_index_generator` tool lives in `src/tools` and is used to
-/// generate a markdown file from the error indexes of the code base which is
-/// then passed to `rustdoc --test`.
+/// generate markdown files from the error indexes of the code base and the
+/// explanations of the lints, which are then passed to `rustdoc --test`.
 pub fn error_index(build: &Build, compiler: &Compiler) {
     let _folder = build.fold_output(|| "test_error_index");
     println!("Testing error-index stage{}", compiler.stage);
@@ -356,15 +365,18 @@ pub fn error_index(build: &Build, compiler: &Compiler) {
     let dir = testdir(build, compiler.host);
     t!(fs::create_dir_all(&dir));
     let output = dir.join("error-index.md");
+    let lint_output = dir.join("lint-index.md");
 
     let _time = util::timeit();
     build.run(build.tool_cmd(&Compiler::new(0, compiler.host),
                              "error_index_generator")
                    .arg("markdown")
                    .arg(&output)
+                   .arg(&lint_output)
                    .env("CFG_BUILD", &build.config.build));
 
     markdown_test(build, compiler, &output);
+    markdown_test(build, compiler, &lint_output);
 }
 
 fn markdown_test(build: &Build, compiler: &Compiler, markdown: &Path) {
@@ -657,6 +669,84 @@ pub fn distcheck(build: &Build) {
                      .current_dir(&dir));
 }
 
+/// Checks that the standard library builds reproducibly.
+///
+/// The standard library for `target` is built twice by `compiler`, in two
+/// different directories, remapping the paths of the sources and of the build
+/// directory so that neither leaks into the artifacts. The rlibs, object files
+/// and metadata of both builds are then compared, reporting the first section
+/// and symbol each differing artifact differs in.
+pub fn reproducible(build: &Build, compiler: &Compiler, target: &str) {
+    let _folder = build.fold_output(|| "reproducible");
+    println!("Checking that stage{} std is reproducible ({} -> {})",
+             compiler.stage, compiler.host, target);
+
+    let root = build.out.join("tmp").join("reproducible");
+    let _ = fs::remove_dir_all(&root);
+    let mut deps = Vec::new();
+    for name in ["a", "b"].iter() {
+        let dir = root.join(name);
+        t!(fs::create_dir_all(&dir));
+        let mut cargo = build.cargo(compiler, Mode::Libstd, target, "build");
+        compile::std_cargo(build, target, compiler, &mut cargo);
+
+        // Later mappings take precedence, and the build directory is usually
+        // within the sources.
+        let mut flags = build.rustc_flags(target);
+        for &(from, to) in [(&build.src, "/rustc/src"), (&dir, "/rustc/build")].iter() {
+            flags.push(format!("-Zremap-path-prefix-from={}", from.display()));
+            flags.push(format!("-Zremap-path-prefix-to={}", to));
+        }
+        cargo.env("CARGO_TARGET_DIR", &dir)
+             .env("RUSTC_FLAGS", flags.join("\x1f"));
+        build.run(&mut cargo);
+        deps.push(dir.join(target).join(build.cargo_dir()).join("deps"));
+    }
+
+    // Dep-info files list the absolute paths of the sources, so they're
+    // expected to differ.
+    let artifacts = |dir: &Path| {
+        let mut names = t!(fs::read_dir(dir)).map(|e| t!(e).file_name())
+                                             .filter(|n| !n.to_string_lossy().ends_with(".d"))
+                                             .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    let (names_a, names_b) = (artifacts(&deps[0]), artifacts(&deps[1]));
+    let mut differences = Vec::new();
+    for name in names_a.iter().filter(|n| !names_b.contains(n)) {
+        differences.push(format!("{}: only in the first build", name.to_string_lossy()));
+    }
+    for name in names_b.iter().filter(|n| !names_a.contains(n)) {
+        differences.push(format!("{}: only in the second build", name.to_string_lossy()));
+    }
+    for name in names_a.iter().filter(|n| names_b.contains(n)) {
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        t!(t!(File::open(deps[0].join(name))).read_to_end(&mut a));
+        t!(t!(File::open(deps[1].join(name))).read_to_end(&mut b));
+        if let Some(diff) = reproducible::diff(&name.to_string_lossy(), &a, &b) {
+            differences.push(diff);
+        }
+    }
+
+    if differences.is_empty() {
+        println!("all {} artifacts are identical", names_a.len());
+        return
+    }
+    println!("\nthe two builds of std differ:\n");
+    for difference in differences.iter() {
+        println!("    {}", difference);
+    }
+    println!("\nthe artifacts of both builds are left in {}\n", root.display());
+    if build.flags.cmd.no_fail_fast() {
+        let failures = build.delayed_failures.get();
+        build.delayed_failures.set(failures + 1);
+    } else {
+        process::exit(1);
+    }
+}
+
 /// Test the build system itself
 pub fn bootstrap(build: &Build) {
     let mut cmd = Command::new(&build.cargo);
@@ -16,6 +16,7 @@
 //! compiler. This module is also responsible for assembling the sysroot as it
 //! goes along from the output of the previous stage.
 
+use std::collections::HashSet;
 use std::env;
 use std::fs::{self, File};
 use std::io::BufReader;
@@ -48,6 +49,15 @@ pub fn std(build: &Build, target: &str, compiler: &Compiler) {
     let out_dir = build.cargo_out(compiler, Mode::Libstd, target);
     build.clear_if_dirty(&out_dir, &build.compiler_path(compiler));
     let mut cargo = build.cargo(compiler, Mode::Libstd, target, "build");
+    std_cargo(build, target, compiler, &mut cargo);
+    run_cargo(build,
+              &mut cargo,
+              &libstd_stamp(build, &compiler, target));
+}
+
+/// Configures `cargo`, a `cargo build` for the standard library, with the
+/// features and environment it needs for `target`.
+pub fn std_cargo(build: &Build, target: &str, compiler: &Compiler, cargo: &mut Command) {
     let mut features = build.std_features();
 
     if let Ok(target) = env::var("MACOSX_STD_DEPLOYMENT_TARGET") {
@@ -87,10 +97,6 @@ pub fn std(build: &Build, target: &str, compiler: &Compiler) {
             cargo.env("MUSL_ROOT", p);
         }
     }
-
-    run_cargo(build,
-              &mut cargo,
-              &libstd_stamp(build, &compiler, target));
 }
 
 /// Link all libstd rlibs/dylibs into the sysroot location.
@@ -336,6 +342,39 @@ fn librustc_stamp(build: &Build, compiler: &Compiler, target: &str) -> PathBuf {
     build.cargo_out(compiler, Mode::Librustc, target).join(".librustc.stamp")
 }
 
+/// Returns whether the libraries for `mode` built by `compiler` for `target`
+/// are newer than that compiler and than the sources of `krate` and all the
+/// crates it depends on, in which case building them again would do nothing.
+pub fn libs_up_to_date(build: &Build,
+                       compiler: &Compiler,
+                       mode: Mode,
+                       target: &str,
+                       krate: &str) -> bool {
+    let stamp = match mode {
+        Mode::Libstd => libstd_stamp(build, compiler, target),
+        Mode::Libtest => libtest_stamp(build, compiler, target),
+        Mode::Librustc => librustc_stamp(build, compiler, target),
+        _ => panic!(),
+    };
+    if !stamp.exists() || mtime(&stamp) < mtime(&build.compiler_path(compiler)) {
+        return false
+    }
+
+    let mut list = vec![krate];
+    let mut visited = HashSet::new();
+    while let Some(krate) = list.pop() {
+        if !visited.insert(krate) {
+            continue
+        }
+        let krate = &build.crates[krate];
+        if !up_to_date(&krate.path, &stamp) {
+            return false
+        }
+        list.extend(krate.deps.iter().map(|dep| &dep[..]));
+    }
+    true
+}
+
 fn compiler_file(compiler: &Path, file: &str) -> PathBuf {
     let out = output(Command::new(compiler)
                             .arg(format!("-print-file-name={}", file)));
@@ -473,6 +512,14 @@ pub fn tool(build: &Build, stage: u32, target: &str, tool: &str) {
     build.run(&mut cargo);
 }
 
+/// Returns whether the tool built by `tool` with the same arguments is newer
+/// than its sources.
+pub fn tool_up_to_date(build: &Build, stage: u32, target: &str, tool: &str) -> bool {
+    let compiler = Compiler::new(stage, &build.config.build);
+    let exe = build.cargo_out(&compiler, Mode::Tool, target).join(exe(tool, target));
+    exe.exists() && up_to_date(&build.src.join("src/tools").join(tool), &exe)
+}
+
 fn run_cargo(build: &Build, cargo: &mut Command, stamp: &Path) {
     // Instruct Cargo to give us json messages on stdout, critically leaving
     // stderr as piped so we can get those pretty colors.
@@ -337,7 +337,7 @@ pub fn rustc(build: &Build, stage: u32, target: &str) {
     cp_r(&my_out, &out);
 }
 
-/// Generates the HTML rendered error-index by running the
+/// Generates the HTML rendered error-index and lint-index by running the
 /// `error_index_generator` tool.
 pub fn error_index(build: &Build, target: &str) {
     println!("Documenting error index ({})", target);
@@ -347,6 +347,7 @@ pub fn error_index(build: &Build, target: &str) {
     let mut index = build.tool_cmd(&compiler, "error_index_generator");
     index.arg("html");
     index.arg(out.join("error-index.html"));
+    index.arg(out.join("lint-index.html"));
 
     // FIXME: shouldn't have to pass this env var
     index.env("CFG_BUILD", &build.config.build);
@@ -39,6 +39,8 @@ pub struct Flags {
     pub jobs: Option<u32>,
     pub cmd: Subcommand,
     pub incremental: bool,
+    pub dry_run: bool,
+    pub graph: Option<PathBuf>,
 }
 
 impl Flags {
@@ -62,6 +64,7 @@ pub enum Subcommand {
         paths: Vec<PathBuf>,
         test_args: Vec<String>,
         no_fail_fast: bool,
+        changed: Option<Changes>,
     },
     Bench {
         paths: Vec<PathBuf>,
@@ -76,6 +79,14 @@ pub enum Subcommand {
     },
 }
 
+/// The files from which `./x.py test --changed` selects the tests to run.
+pub enum Changes {
+    /// The files which differ from a git revision, along with untracked files.
+    Since(String),
+    /// The files listed in a file, one per line.
+    List(PathBuf),
+}
+
 impl Flags {
     pub fn parse(args: &[String]) -> Flags {
         let mut extra_help = String::new();
@@ -106,6 +117,10 @@ To learn more about a subcommand, run `./x.py <subcommand> -h`");
         opts.optopt("", "keep-stage", "stage to keep without recompiling", "N");
         opts.optopt("", "src", "path to the root of the rust checkout", "DIR");
         opts.optopt("j", "jobs", "number of jobs to run in parallel", "JOBS");
+        opts.optflag("", "dry-run", "print the steps which would be executed, without executing \
+                                     them");
+        opts.optopt("", "graph", "write the graph of the steps to execute to FILE in dot format",
+                    "FILE");
         opts.optflag("h", "help", "print this help message");
 
         // fn usage()
@@ -145,6 +160,10 @@ To learn more about a subcommand, run `./x.py <subcommand> -h`");
             "test"  => {
                 opts.optflag("", "no-fail-fast", "Run all tests regardless of failure");
                 opts.optmulti("", "test-args", "extra arguments", "ARGS");
+                opts.optopt("", "changed", "only run the tests affected by the files changed \
+                                            since a git revision", "REV");
+                opts.optopt("", "changed-files", "only run the tests affected by the files \
+                                                  listed in FILE", "FILE");
             },
             "bench" => { opts.optmulti("", "test-args", "extra arguments", "ARGS"); },
             _ => { },
@@ -214,7 +233,15 @@ Arguments:
     compiled and tested.
 
         ./x.py test
-        ./x.py test --stage 1");
+        ./x.py test --stage 1
+
+    Instead of paths, the tests affected by a set of changes can be selected
+    with `--changed`, which looks at the files that differ from a git revision,
+    or `--changed-files`, which reads a list of files relative to the root of
+    the source tree. The reason each test was picked is printed before it runs.
+
+        ./x.py test --changed origin/master
+        ./x.py test --changed-files changed.txt");
             }
             "doc" => {
                 subcommand_help.push_str("\n
@@ -264,10 +291,24 @@ Arguments:
                 Subcommand::Build { paths: paths }
             }
             "test" => {
+                let changed = match (matches.opt_str("changed"), matches.opt_str("changed-files")) {
+                    (Some(_), Some(_)) => {
+                        println!("\n--changed and --changed-files can't be used together\n");
+                        usage(1, &opts, &subcommand_help, &extra_help);
+                    }
+                    (Some(rev), None) => Some(Changes::Since(rev)),
+                    (None, Some(file)) => Some(Changes::List(cwd.join(file))),
+                    (None, None) => None,
+                };
+                if changed.is_some() && paths.len() > 0 {
+                    println!("\n--changed and --changed-files can't be used with paths\n");
+                    usage(1, &opts, &subcommand_help, &extra_help);
+                }
                 Subcommand::Test {
                     paths: paths,
                     test_args: matches.opt_strs("test-args"),
                     no_fail_fast: matches.opt_present("no-fail-fast"),
+                    changed: changed,
                 }
             }
             "bench" => {
@@ -333,6 +374,8 @@ Arguments:
             jobs: matches.opt_str("jobs").map(|j| j.parse().unwrap()),
             cmd: cmd,
             incremental: matches.opt_present("incremental"),
+            dry_run: matches.opt_present("dry-run"),
+            graph: matches.opt_str("graph").map(|p| cwd.join(p)),
         }
     }
 }
@@ -354,6 +397,13 @@ impl Subcommand {
             _ => false,
         }
     }
+
+    pub fn changed(&self) -> Option<&Changes> {
+        match *self {
+            Subcommand::Test { ref changed, .. } => changed.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 fn split(s: Vec<String>) -> Vec<String> {
@@ -105,6 +105,7 @@ mod doc;
 mod flags;
 mod install;
 mod native;
+mod reproducible;
 mod sanity;
 mod step;
 pub mod util;
@@ -376,7 +377,7 @@ impl Build {
              .env("RUSTC_RPATH", self.config.rust_rpath.to_string())
              .env("RUSTDOC", self.out.join("bootstrap/debug/rustdoc"))
              .env("RUSTDOC_REAL", self.rustdoc(compiler))
-             .env("RUSTC_FLAGS", self.rustc_flags(target).join(" "));
+             .env("RUSTC_FLAGS", self.rustc_flags(target).join("\x1f"));
 
         if mode != Mode::Tool {
             // Tools don't get debuginfo right now, e.g. cargo and rls don't
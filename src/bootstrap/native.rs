@@ -43,22 +43,15 @@ pub fn llvm(build: &Build, target: &str) {
         }
     }
 
-    let rebuild_trigger = build.src.join("src/rustllvm/llvm-rebuild-trigger");
-    let mut rebuild_trigger_contents = String::new();
-    t!(t!(File::open(&rebuild_trigger)).read_to_string(&mut rebuild_trigger_contents));
+    // If LLVM was already built previously and contents of the rebuild-trigger file
+    // didn't change from the previous build, then no action is required.
+    if llvm_up_to_date(build, target) {
+        return
+    }
 
     let out_dir = build.llvm_out(target);
     let done_stamp = out_dir.join("llvm-finished-building");
-    if done_stamp.exists() {
-        let mut done_contents = String::new();
-        t!(t!(File::open(&done_stamp)).read_to_string(&mut done_contents));
-
-        // If LLVM was already built previously and contents of the rebuild-trigger file
-        // didn't change from the previous build, then no action is required.
-        if done_contents == rebuild_trigger_contents {
-            return
-        }
-    }
+    let rebuild_trigger_contents = llvm_rebuild_trigger(build);
     if build.config.llvm_clean_rebuild {
         drop(fs::remove_dir_all(&out_dir));
     }
@@ -196,6 +189,32 @@ pub fn llvm(build: &Build, target: &str) {
     t!(t!(File::create(&done_stamp)).write_all(rebuild_trigger_contents.as_bytes()));
 }
 
+/// Returns whether `llvm` would have nothing to do for `target`, either
+/// because a custom LLVM is used or because it's already been built since the
+/// rebuild trigger last changed.
+pub fn llvm_up_to_date(build: &Build, target: &str) -> bool {
+    if let Some(config) = build.config.target_config.get(target) {
+        if config.llvm_config.is_some() {
+            return true
+        }
+    }
+
+    let done_stamp = build.llvm_out(target).join("llvm-finished-building");
+    if !done_stamp.exists() {
+        return false
+    }
+    let mut done_contents = String::new();
+    t!(t!(File::open(&done_stamp)).read_to_string(&mut done_contents));
+    done_contents == llvm_rebuild_trigger(build)
+}
+
+fn llvm_rebuild_trigger(build: &Build) -> String {
+    let rebuild_trigger = build.src.join("src/rustllvm/llvm-rebuild-trigger");
+    let mut rebuild_trigger_contents = String::new();
+    t!(t!(File::open(&rebuild_trigger)).read_to_string(&mut rebuild_trigger_contents));
+    rebuild_trigger_contents
+}
+
 fn check_llvm_version(build: &Build, llvm_config: &Path) {
     if !build.config.llvm_version_check {
         return
@@ -213,11 +232,10 @@ fn check_llvm_version(build: &Build, llvm_config: &Path) {
 /// Compiles the `rust_test_helpers.c` library which we used in various
 /// `run-pass` test suites for ABI testing.
 pub fn test_helpers(build: &Build, target: &str) {
-    let dst = build.test_helpers_out(target);
-    let src = build.src.join("src/rt/rust_test_helpers.c");
-    if up_to_date(&src, &dst.join("librust_test_helpers.a")) {
+    if test_helpers_up_to_date(build, target) {
         return
     }
+    let dst = build.test_helpers_out(target);
 
     let _folder = build.fold_output(|| "build_test_helpers");
     println!("Building test helpers");
@@ -243,6 +261,13 @@ pub fn test_helpers(build: &Build, target: &str) {
        .file(build.src.join("src/rt/rust_test_helpers.c"))
        .compile("librust_test_helpers.a");
 }
+
+/// Returns whether the test helpers for `target` are newer than their source.
+pub fn test_helpers_up_to_date(build: &Build, target: &str) -> bool {
+    let dst = build.test_helpers_out(target).join("librust_test_helpers.a");
+    dst.exists() && up_to_date(&build.src.join("src/rt/rust_test_helpers.c"), &dst)
+}
+
 const OPENSSL_VERS: &'static str = "1.0.2k";
 const OPENSSL_SHA256: &'static str =
     "6b3977c61f2aedf0f96367dcfb5c6e578cf37e7b8d913b4ecb6643c3cb88d8c0";
@@ -0,0 +1,432 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Comparison of build artifacts, used to check that builds are reproducible.
+//!
+//! Knowing that two builds of an rlib differ isn't very helpful on its own, so
+//! archives are compared member by member and ELF objects section by section,
+//! to point at the symbol which differs. Anything else, like the metadata, is
+//! compared byte by byte.
+
+use std::cmp;
+use std::str;
+
+const ELF_MAGIC: &'static [u8] = b"\x7fELF";
+
+macro_rules! opt {
+    ($e:expr) => (match $e {
+        Some(e) => e,
+        None => return None,
+    })
+}
+
+/// Describes the first difference between the builds `a` and `b` of the
+/// artifact `name`, or returns `None` if they're identical.
+pub fn diff(name: &str, a: &[u8], b: &[u8]) -> Option<String> {
+    if a == b {
+        return None
+    }
+    if let (Some(members_a), Some(members_b)) = (archive(a), archive(b)) {
+        return Some(format!("{}: {}", name, diff_archives(a, b, &members_a, &members_b)))
+    }
+    if a.starts_with(ELF_MAGIC) && b.starts_with(ELF_MAGIC) {
+        return Some(match (Elf::parse(a), Elf::parse(b)) {
+            (Some(a), Some(b)) => format!("{}: {}", name, diff_elf(&a, &b)),
+            _ => format!("{}: malformed ELF object, {}", name, diff_bytes(a, b)),
+        })
+    }
+    Some(format!("{}: {}", name, diff_bytes(a, b)))
+}
+
+fn diff_archives(a: &[u8],
+                 b: &[u8],
+                 members_a: &[(String, &[u8])],
+                 members_b: &[(String, &[u8])]) -> String {
+    for i in 0..cmp::max(members_a.len(), members_b.len()) {
+        match (members_a.get(i), members_b.get(i)) {
+            (Some(&(ref name_a, data_a)), Some(&(ref name_b, data_b))) => {
+                if name_a != name_b {
+                    return format!("the members differ, `{}` in the first build and `{}` \
+                                    in the second", name_a, name_b)
+                }
+                if let Some(diff) = diff(name_a, data_a, data_b) {
+                    return diff
+                }
+            }
+            (Some(&(ref name, _)), None) => {
+                return format!("member `{}` is only in the first build", name)
+            }
+            (None, Some(&(ref name, _))) => {
+                return format!("member `{}` is only in the second build", name)
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    // The members are the same, so their headers must differ
+    format!("the archive headers differ at offset {:#x}", first_difference(a, b))
+}
+
+fn diff_elf(a: &Elf, b: &Elf) -> String {
+    for i in 0..cmp::max(a.sections.len(), b.sections.len()) {
+        let (section_a, section_b) = match (a.sections.get(i), b.sections.get(i)) {
+            (Some(section_a), Some(section_b)) => (section_a, section_b),
+            (Some(section), None) => {
+                return format!("section `{}` is only in the first build", section.name)
+            }
+            (None, Some(section)) => {
+                return format!("section `{}` is only in the second build", section.name)
+            }
+            (None, None) => unreachable!(),
+        };
+        if section_a.name != section_b.name {
+            return format!("the sections differ, `{}` in the first build and `{}` in \
+                            the second", section_a.name, section_b.name)
+        }
+        if section_a.data == section_b.data {
+            continue
+        }
+        let offset = first_difference(section_a.data, section_b.data);
+        let mut msg = format!("section `{}` differs at offset {:#x}", section_a.name, offset);
+        if let Some(symbol) = a.symbol_at(i, section_a.addr + offset as u64) {
+            msg.push_str(&format!(", in symbol `{}`", symbol));
+        }
+        if section_a.data.len() != section_b.data.len() {
+            msg.push_str(&format!(" (its size is {:#x} in the first build and {:#x} in \
+                                   the second)", section_a.data.len(), section_b.data.len()));
+        }
+        return msg
+    }
+    format!("the ELF headers differ at offset {:#x}", first_difference(a.data, b.data))
+}
+
+fn diff_bytes(a: &[u8], b: &[u8]) -> String {
+    let mut msg = format!("differs at offset {:#x}", first_difference(a, b));
+    if a.len() != b.len() {
+        msg.push_str(&format!(" (its size is {:#x} in the first build and {:#x} in the \
+                               second)", a.len(), b.len()));
+    }
+    msg
+}
+
+fn first_difference(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).position(|(a, b)| a != b).unwrap_or(cmp::min(a.len(), b.len()))
+}
+
+/// Returns the members of the `ar` archive `data`, along with their names.
+///
+/// Both the GNU and BSD variants of the format are understood, and the symbol
+/// table is included as a member named `/`.
+fn archive(data: &[u8]) -> Option<Vec<(String, &[u8])>> {
+    if !data.starts_with(b"!<arch>\n") {
+        return None
+    }
+    let mut members = Vec::new();
+    let mut names: &[u8] = &[];
+    let mut pos = 8;
+    while pos + 60 <= data.len() {
+        let header = &data[pos..pos + 60];
+        let size = opt!(str::from_utf8(&header[48..58]).ok()
+                            .and_then(|s| s.trim().parse::<usize>().ok()));
+        let start = pos + 60;
+        if start + size > data.len() {
+            return None
+        }
+        let mut body = &data[start..start + size];
+        pos = start + size + size % 2;
+
+        let name = opt!(str::from_utf8(&header[..16]).ok()).trim_right();
+        let name = if name == "//" {
+            // The GNU table of long names
+            names = body;
+            continue
+        } else if name.starts_with("#1/") {
+            // BSD names are at the start of the member itself
+            let len = opt!(name[3..].parse::<usize>().ok());
+            if len > body.len() {
+                return None
+            }
+            let name = String::from_utf8_lossy(&body[..len]);
+            body = &body[len..];
+            name.trim_right_matches('\0').to_string()
+        } else if name.len() > 1 && name.starts_with("/") && name != "/SYM64/" {
+            let offset = opt!(name[1..].parse::<usize>().ok());
+            if offset > names.len() {
+                return None
+            }
+            let end = names[offset..].iter().position(|&b| b == b'\n')
+                                     .map_or(names.len(), |i| offset + i);
+            String::from_utf8_lossy(&names[offset..end]).trim_right_matches('/').to_string()
+        } else if name == "/" || name == "/SYM64/" {
+            name.to_string()
+        } else {
+            name.trim_right_matches('/').to_string()
+        };
+        members.push((name, body));
+    }
+    Some(members)
+}
+
+/// Just enough of an ELF object to tell which section and symbol a difference
+/// is in.
+struct Elf<'a> {
+    data: &'a [u8],
+    sections: Vec<Section<'a>>,
+    symbols: Vec<Symbol>,
+}
+
+struct Section<'a> {
+    name: String,
+    addr: u64,
+    data: &'a [u8],
+}
+
+struct Symbol {
+    name: String,
+    section: usize,
+    value: u64,
+    size: u64,
+}
+
+impl<'a> Elf<'a> {
+    fn parse(data: &'a [u8]) -> Option<Elf<'a>> {
+        if data.len() < 16 || !data.starts_with(ELF_MAGIC) {
+            return None
+        }
+        let r = Reader {
+            data: data,
+            is_64: data[4] == 2,
+            big_endian: data[5] == 2,
+        };
+        let w = if r.is_64 {8} else {4};
+        let shoff = opt!(r.word(if r.is_64 {0x28} else {0x20})) as usize;
+        let ehsize = if r.is_64 {0x3a} else {0x2e};
+        let shentsize = opt!(r.int(ehsize, 2)) as usize;
+        let shnum = opt!(r.int(ehsize + 2, 2)) as usize;
+        let shstrndx = opt!(r.int(ehsize + 4, 2)) as usize;
+
+        // (name, type, addr, data, link) of each section
+        let mut headers = Vec::new();
+        for i in 0..shnum {
+            let h = opt!(i.checked_mul(shentsize).and_then(|h| h.checked_add(shoff)));
+            if h > data.len() {
+                return None
+            }
+            let name = opt!(r.int(h, 4)) as usize;
+            let kind = opt!(r.int(h + 4, 4));
+            let addr = opt!(r.word(h + 8 + w));
+            let offset = opt!(r.word(h + 8 + 2 * w)) as usize;
+            let size = opt!(r.word(h + 8 + 3 * w)) as usize;
+            let link = opt!(r.int(h + 8 + 4 * w, 4)) as usize;
+            // SHT_NOBITS sections take no room in the file
+            let data = if kind == 8 {
+                &data[..0]
+            } else {
+                match offset.checked_add(size) {
+                    Some(end) if end <= data.len() => &data[offset..end],
+                    _ => return None,
+                }
+            };
+            headers.push((name, kind, addr, data, link));
+        }
+        let strtab = |i: usize| headers.get(i).map_or(&data[..0], |h| h.3);
+        let name = |strtab: &[u8], offset: usize| {
+            let strtab = &strtab[cmp::min(offset, strtab.len())..];
+            let end = strtab.iter().position(|&b| b == 0).unwrap_or(strtab.len());
+            String::from_utf8_lossy(&strtab[..end]).into_owned()
+        };
+
+        let mut symbols = Vec::new();
+        // Symbols of the SHT_SYMTAB section, other than those of sections and
+        // files
+        for &(_, _, _, symtab, link) in headers.iter().filter(|h| h.1 == 2) {
+            let r = Reader { data: symtab, ..r };
+            let size = if r.is_64 {24} else {16};
+            for i in 0..symtab.len() / size {
+                let s = i * size;
+                let (info, shndx, value, size) = if r.is_64 {
+                    (opt!(r.int(s + 4, 1)), opt!(r.int(s + 6, 2)),
+                     opt!(r.int(s + 8, 8)), opt!(r.int(s + 16, 8)))
+                } else {
+                    (opt!(r.int(s + 12, 1)), opt!(r.int(s + 14, 2)),
+                     opt!(r.int(s + 4, 4)), opt!(r.int(s + 8, 4)))
+                };
+                if info & 0xf == 3 || info & 0xf == 4 {
+                    continue
+                }
+                symbols.push(Symbol {
+                    name: name(strtab(link), opt!(r.int(s, 4)) as usize),
+                    section: shndx as usize,
+                    value: value,
+                    size: size,
+                });
+            }
+        }
+
+        let sections = headers.iter().map(|&(n, _, addr, data, _)| {
+            Section {
+                name: name(strtab(shstrndx), n),
+                addr: addr,
+                data: data,
+            }
+        }).collect();
+        Some(Elf {
+            data: data,
+            sections: sections,
+            symbols: symbols,
+        })
+    }
+
+    /// Returns the name of the symbol of the section `section` containing
+    /// `addr`, or else the closest one before it.
+    fn symbol_at(&self, section: usize, addr: u64) -> Option<&str> {
+        let symbols = self.symbols.iter().filter(|s| s.section == section && s.value <= addr);
+        let containing = symbols.clone().filter(|s| addr < s.value + s.size).next();
+        containing.or_else(|| symbols.max_by_key(|s| s.value)).map(|s| &s.name[..])
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn int(&self, offset: usize, len: usize) -> Option<u64> {
+        let end = opt!(offset.checked_add(len));
+        if end > self.data.len() {
+            return None
+        }
+        let bytes = &self.data[offset..end];
+        let mut n = 0;
+        for i in 0..len {
+            let byte = if self.big_endian {bytes[i]} else {bytes[len - 1 - i]};
+            n = (n << 8) | byte as u64;
+        }
+        Some(n)
+    }
+
+    fn word(&self, offset: usize) -> Option<u64> {
+        self.int(offset, if self.is_64 {8} else {4})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+
+    fn push(buf: &mut Vec<u8>, n: u64, len: usize) {
+        for i in 0..len {
+            buf.push(if i < 8 {(n >> (8 * i)) as u8} else {0});
+        }
+    }
+
+    /// A 64-bit little endian ELF object with a `.text` section of `text`,
+    /// holding the symbol `foo` at offset 4.
+    fn object(text: &[u8]) -> Vec<u8> {
+        let strtab = b"\0foo\0";
+        let shstrtab = b"\0.text\0.symtab\0.strtab\0.shstrtab\0";
+        let mut symtab = vec![0; 24];
+        push(&mut symtab, 1, 4);
+        push(&mut symtab, 0x12, 1);
+        push(&mut symtab, 0, 1);
+        push(&mut symtab, 1, 2);
+        push(&mut symtab, 4, 8);
+        push(&mut symtab, 8, 8);
+
+        let mut data = Vec::new();
+        let mut sections = vec![(0, 0, 0, 0, 0)];
+        for &(name, kind, link, contents) in [(1, 1, 0, text),
+                                              (7, 2, 3, &symtab[..]),
+                                              (15, 3, 0, &strtab[..]),
+                                              (23, 3, 0, &shstrtab[..])].iter() {
+            sections.push((name, kind, link, 64 + data.len(), contents.len()));
+            data.extend_from_slice(contents);
+        }
+
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(16, 0);
+        push(&mut elf, 1, 2);
+        push(&mut elf, 0x3e, 2);
+        push(&mut elf, 1, 4);
+        push(&mut elf, 0, 16);
+        push(&mut elf, 64 + data.len() as u64, 8);
+        push(&mut elf, 0, 4);
+        push(&mut elf, 64, 2);
+        push(&mut elf, 0, 4);
+        push(&mut elf, 64, 2);
+        push(&mut elf, sections.len() as u64, 2);
+        push(&mut elf, 4, 2);
+        elf.extend(data);
+        for &(name, kind, link, offset, size) in sections.iter() {
+            push(&mut elf, name, 4);
+            push(&mut elf, kind, 4);
+            push(&mut elf, 0, 16);
+            push(&mut elf, offset as u64, 8);
+            push(&mut elf, size as u64, 8);
+            push(&mut elf, link, 4);
+            push(&mut elf, 0, 20);
+        }
+        elf
+    }
+
+    fn archive(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut ar = b"!<arch>\n".to_vec();
+        for &(name, data) in members {
+            ar.extend(format!("{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+                              format!("{}/", name), 0, 0, 0, 644, data.len()).bytes());
+            ar.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                ar.push(b'\n');
+            }
+        }
+        ar
+    }
+
+    #[test]
+    fn object_in_archive() {
+        let text = [0x90; 16];
+        let mut changed = text;
+        changed[6] = 0xc3;
+        let a = archive(&[("lib.rmeta", b"meta"), ("foo.0.o", &object(&text))]);
+        let b = archive(&[("lib.rmeta", b"meta"), ("foo.0.o", &object(&changed))]);
+        assert_eq!(diff("libfoo.rlib", &a, &a), None);
+        assert_eq!(diff("libfoo.rlib", &a, &b).unwrap(),
+                   "libfoo.rlib: foo.0.o: section `.text` differs at offset 0x6, \
+                    in symbol `foo`");
+    }
+
+    #[test]
+    fn malformed_object() {
+        let a = object(&[0x90; 16]);
+        // Make the size of `.text` overflow when added to its offset.
+        let size_field = a[0x28] as usize + 64 + 32;
+        let mut b = a.clone();
+        for byte in &mut b[size_field..size_field + 8] {
+            *byte = 0xff;
+        }
+        assert_eq!(diff("foo.o", &a, &b).unwrap(),
+                   format!("foo.o: malformed ELF object, differs at offset {:#x}", size_field));
+    }
+
+    #[test]
+    fn metadata_in_archive() {
+        let object = object(&[0x90; 16]);
+        let a = archive(&[("lib.rmeta", b"meta"), ("foo.0.o", &object)]);
+        let b = archive(&[("lib.rmeta", b"metadata"), ("foo.0.o", &object)]);
+        assert_eq!(diff("libfoo.rlib", &a, &b).unwrap(),
+                   "libfoo.rlib: lib.rmeta: differs at offset 0x4 (its size is 0x4 \
+                    in the first build and 0x8 in the second)");
+        let c = archive(&[("lib.rmeta", b"meta")]);
+        assert_eq!(diff("libfoo.rlib", &a, &c).unwrap(),
+                   "libfoo.rlib: member `foo.0.o` is only in the first build");
+    }
+}
@@ -143,12 +143,6 @@ pub fn check(build: &mut Build) {
         }
     }
 
-    // Externally configured LLVM requires FileCheck to exist
-    let filecheck = build.llvm_filecheck(&build.config.build);
-    if !filecheck.starts_with(&build.out) && !filecheck.exists() && build.config.codegen_tests {
-        panic!("FileCheck executable {:?} does not exist", filecheck);
-    }
-
     for target in build.config.target.iter() {
         // Can't compile for iOS unless we're on macOS
         if target.contains("apple-ios") &&
@@ -26,18 +26,26 @@
 //! along with the actual implementation elsewhere. You can find more comments
 //! about how to define rules themselves below.
 
-use std::collections::{BTreeMap, HashSet, HashMap};
+use std::collections::{BTreeMap, HashSet, HashMap, VecDeque};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
 use std::mem;
-use std::process;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{Duration, Instant};
+
+use build_helper::output;
+use rustc_serialize::json::Json;
 
 use check::{self, TestKind};
 use compile;
 use dist;
 use doc;
-use flags::Subcommand;
+use flags::{Changes, Subcommand};
 use install;
 use native;
-use {Compiler, Build, Mode};
+use {Compiler, Build, Crate, Mode};
 
 pub fn run(build: &Build) {
     let rules = build_rules(build);
@@ -110,7 +118,8 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
                  s.target(&build.config.build)
              }
          })
-         .run(move |s| native::llvm(build, s.target));
+         .run(move |s| native::llvm(build, s.target))
+         .up_to_date(move |s| native::llvm_up_to_date(build, s.target));
 
     // Ok! After that example rule  that's hopefully enough to explain what's
     // going on here. You can check out the API docs below and also see a bunch
@@ -270,19 +279,29 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
         rules.build(&krate.build_step, path)
              .dep(|s| s.name("startup-objects"))
              .dep(move |s| s.name("rustc").host(&build.config.build).target(s.host))
-             .run(move |s| compile::std(build, s.target, &s.compiler()));
+             .run(move |s| compile::std(build, s.target, &s.compiler()))
+             .up_to_date(move |s| {
+                 compile::libs_up_to_date(build, &s.compiler(), Mode::Libstd, s.target, "std")
+             });
     }
     for (krate, path, _default) in krates("test") {
         rules.build(&krate.build_step, path)
              .dep(|s| s.name("libstd-link"))
-             .run(move |s| compile::test(build, s.target, &s.compiler()));
+             .run(move |s| compile::test(build, s.target, &s.compiler()))
+             .up_to_date(move |s| {
+                 compile::libs_up_to_date(build, &s.compiler(), Mode::Libtest, s.target, "test")
+             });
     }
     for (krate, path, _default) in krates("rustc-main") {
         rules.build(&krate.build_step, path)
              .dep(|s| s.name("libtest-link"))
              .dep(move |s| s.name("llvm").host(&build.config.build).stage(0))
              .dep(|s| s.name("may-run-build-script"))
-             .run(move |s| compile::rustc(build, s.target, &s.compiler()));
+             .run(move |s| compile::rustc(build, s.target, &s.compiler()))
+             .up_to_date(move |s| {
+                 compile::libs_up_to_date(build, &s.compiler(), Mode::Librustc, s.target,
+                                          "rustc-main")
+             });
     }
 
     // Crates which have build scripts need to rely on this rule to ensure that
@@ -410,6 +429,7 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
          .dep(|s| s.name("libtest"))
          .dep(|s| s.name("remote-copy-libs"))
          .default(true)
+         .all_crates(true)
          .run(move |s| check::krate(build, &s.compiler(), s.target,
                                     Mode::Libstd, TestKind::Test, None));
 
@@ -441,6 +461,7 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
          .dep(|s| s.name("libtest"))
          .dep(|s| s.name("remote-copy-libs"))
          .default(true)
+         .all_crates(true)
          .run(move |s| check::krate(build, &s.compiler(), s.target,
                                     Mode::Libtest, TestKind::Test, None));
     for (krate, path, _default) in krates("rustc-main") {
@@ -456,6 +477,7 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
          .dep(|s| s.name("librustc"))
          .dep(|s| s.name("remote-copy-libs"))
          .default(true)
+         .all_crates(true)
          .host(true)
          .run(move |s| check::krate(build, &s.compiler(), s.target,
                                     Mode::Librustc, TestKind::Test, None));
@@ -496,9 +518,14 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
          .dep(|s| s.name("dist-plain-source-tarball"))
          .dep(|s| s.name("dist-src"))
          .run(move |_| check::distcheck(build));
+    rules.test("check-reproducible", "reproducible")
+         .dep(|s| s.name("startup-objects"))
+         .dep(move |s| s.name("rustc").host(&build.config.build).target(s.host))
+         .run(move |s| check::reproducible(build, &s.compiler(), s.target));
 
     rules.build("test-helpers", "src/rt/rust_test_helpers.c")
-         .run(move |s| native::test_helpers(build, s.target));
+         .run(move |s| native::test_helpers(build, s.target))
+         .up_to_date(move |s| native::test_helpers_up_to_date(build, s.target));
     rules.build("openssl", "path/to/nowhere")
          .run(move |s| native::openssl(build, s.target));
 
@@ -543,43 +570,59 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
     rules.build("tool-rustbook", "src/tools/rustbook")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("librustc-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "rustbook"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "rustbook"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "rustbook"));
     rules.build("tool-error-index", "src/tools/error_index_generator")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("librustc-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "error_index_generator"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "error_index_generator"))
+         .up_to_date(move |s| {
+             compile::tool_up_to_date(build, s.stage, s.target, "error_index_generator")
+         });
     rules.build("tool-tidy", "src/tools/tidy")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libstd-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "tidy"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "tidy"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "tidy"));
     rules.build("tool-linkchecker", "src/tools/linkchecker")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libstd-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "linkchecker"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "linkchecker"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "linkchecker"));
     rules.build("tool-cargotest", "src/tools/cargotest")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libstd-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "cargotest"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "cargotest"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "cargotest"));
     rules.build("tool-compiletest", "src/tools/compiletest")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libtest-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "compiletest"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "compiletest"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "compiletest"));
     rules.build("tool-build-manifest", "src/tools/build-manifest")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libstd-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "build-manifest"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "build-manifest"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "build-manifest"));
     rules.build("tool-remote-test-server", "src/tools/remote-test-server")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libstd-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "remote-test-server"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "remote-test-server"))
+         .up_to_date(move |s| {
+             compile::tool_up_to_date(build, s.stage, s.target, "remote-test-server")
+         });
     rules.build("tool-remote-test-client", "src/tools/remote-test-client")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libstd-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "remote-test-client"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "remote-test-client"))
+         .up_to_date(move |s| {
+             compile::tool_up_to_date(build, s.stage, s.target, "remote-test-client")
+         });
     rules.build("tool-rust-installer", "src/tools/rust-installer")
          .dep(|s| s.name("maybe-clean-tools"))
          .dep(|s| s.name("libstd-tool"))
-         .run(move |s| compile::tool(build, s.stage, s.target, "rust-installer"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "rust-installer"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "rust-installer"));
     rules.build("tool-cargo", "src/tools/cargo")
          .host(true)
          .default(build.config.extended)
@@ -593,7 +636,8 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
               .target(&build.config.build)
               .host(&build.config.build)
          })
-         .run(move |s| compile::tool(build, s.stage, s.target, "cargo"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "cargo"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "cargo"));
     rules.build("tool-rls", "src/tools/rls")
          .host(true)
          .default(build.config.extended)
@@ -605,7 +649,8 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
               .target(&build.config.build)
               .host(&build.config.build)
          })
-         .run(move |s| compile::tool(build, s.stage, s.target, "rls"));
+         .run(move |s| compile::tool(build, s.stage, s.target, "rls"))
+         .up_to_date(move |s| compile::tool_up_to_date(build, s.stage, s.target, "rls"));
 
     // "pseudo rule" which represents completely cleaning out the tools dir in
     // one stage. This needs to happen whenever a dependency changes (e.g.
@@ -868,6 +913,12 @@ struct Step<'a> {
     target: &'a str,
 }
 
+impl<'a> fmt::Display for Step<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} stage{} ({} -> {})", self.name, self.stage, self.host, self.target)
+    }
+}
+
 impl<'a> Step<'a> {
     fn noop() -> Step<'a> {
         Step { name: "", stage: 0, host: "", target: "" }
@@ -919,6 +970,11 @@ struct Rule<'a> {
     /// information and then executes it.
     run: Box<Fn(&Step<'a>) + 'a>,
 
+    /// Whether executing a step of this rule would do nothing because its
+    /// output is up to date, if that's known. Only used to annotate the plan
+    /// printed by `--dry-run` and the graph written by `--graph`.
+    up_to_date: Option<Box<Fn(&Step<'a>) -> bool + 'a>>,
+
     /// Whether or not this is a "default" rule. That basically means that if
     /// you run, for example, `./x.py test` whether it's included or not.
     default: bool,
@@ -936,6 +992,10 @@ struct Rule<'a> {
     /// targets.
     only_build: bool,
 
+    /// Whether this rule tests all the crates of std, test or rustc at once.
+    /// `test --changed` runs the rules testing the affected crates instead.
+    all_crates: bool,
+
     /// A list of "order only" dependencies. This rules does not actually
     /// depend on these rules, but if they show up in the dependency graph then
     /// this rule must be executed after all these rules.
@@ -958,12 +1018,14 @@ impl<'a> Rule<'a> {
             name: name,
             deps: Vec::new(),
             run: Box::new(|_| ()),
+            up_to_date: None,
             path: path,
             kind: kind,
             default: false,
             host: false,
             only_host_build: false,
             only_build: false,
+            all_crates: false,
             after: Vec::new(),
         }
     }
@@ -996,6 +1058,13 @@ impl<'a, 'b> RuleBuilder<'a, 'b> {
         self
     }
 
+    fn up_to_date<F>(&mut self, f: F) -> &mut Self
+        where F: Fn(&Step<'a>) -> bool + 'a,
+    {
+        self.rule.up_to_date = Some(Box::new(f));
+        self
+    }
+
     fn default(&mut self, default: bool) -> &mut Self {
         self.rule.default = default;
         self
@@ -1015,6 +1084,11 @@ impl<'a, 'b> RuleBuilder<'a, 'b> {
         self.rule.only_host_build = only_host_build;
         self
     }
+
+    fn all_crates(&mut self, all_crates: bool) -> &mut Self {
+        self.rule.all_crates = all_crates;
+        self
+    }
 }
 
 impl<'a, 'b> Drop for RuleBuilder<'a, 'b> {
@@ -1154,7 +1228,8 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
         //
         // 2. Next, we determine which rules we're actually executing. If a
         //    number of path filters were specified on the command line we look
-        //    for those, otherwise we look for anything tagged `default`.
+        //    for those, otherwise we look for anything tagged `default`. With
+        //    `test --changed` the tests affected by the changes are used.
         //    Here we also compute the priority of each rule based on how early
         //    in the command line the matching path filter showed up.
         //
@@ -1182,18 +1257,23 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
             Subcommand::Clean => panic!(),
         };
 
-        let mut rules: Vec<_> = self.rules.values().filter_map(|rule| {
-            if rule.kind != kind {
-                return None;
+        let mut rules: Vec<_> = match self.build.flags.cmd.changed() {
+            Some(changes) => self.plan_changed(changes),
+            None => {
+                self.rules.values().filter_map(|rule| {
+                    if rule.kind != kind {
+                        return None;
+                    }
+
+                    if paths.len() == 0 && rule.default {
+                        Some((rule, 0))
+                    } else {
+                        paths.iter().position(|path| path.ends_with(rule.path))
+                             .map(|priority| (rule, priority))
+                    }
+                }).collect()
             }
-
-            if paths.len() == 0 && rule.default {
-                Some((rule, 0))
-            } else {
-                paths.iter().position(|path| path.ends_with(rule.path))
-                     .map(|priority| (rule, priority))
-            }
-        }).collect();
+        };
 
         rules.sort_by_key(|&(_, priority)| priority);
 
@@ -1240,6 +1320,155 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
         }).collect()
     }
 
+    /// Selects the test rules affected by the files described by `changes`
+    /// for `./x.py test --changed`, printing why each of them was selected.
+    fn plan_changed(&self, changes: &Changes) -> Vec<(&Rule<'a>, usize)> {
+        let changed = changed_files(self.build, changes);
+        let selected = self.select_changed(&changed);
+        if selected.is_empty() {
+            println!("no tests are affected by the {} changed file(s)", changed.len());
+            return Vec::new();
+        }
+
+        println!("running the tests affected by the {} changed file(s):", changed.len());
+        for &(name, ref reason, ref chain) in selected.iter() {
+            println!("    {}: {}", name, reason);
+            if chain.len() > 1 {
+                println!("        via {}", chain[1..].join(" -> "));
+            }
+        }
+        selected.into_iter().map(|(name, _, _)| (&self.rules[name], 0)).collect()
+    }
+
+    /// Finds the test rules affected by the `changed` files, along with the
+    /// reason each of them is affected and, if it isn't directly affected, the
+    /// chain of dependencies leading to a rule which is.
+    ///
+    /// A changed file in one of the crates found by `metadata::build` affects
+    /// the rules for that crate and all the crates which depend on it. Any
+    /// other changed file, or one in a rule's path inside a crate, affects the
+    /// rules whose path contains it. A test rule is selected if it's affected
+    /// itself or depends on a rule which is.
+    ///
+    /// The rules considered are the default test rules and the rules testing
+    /// individual crates, which take the place of the rules testing all the
+    /// crates of std, test or rustc at once. A crate's test rule depends on
+    /// building all the crates alongside it, so the build rules of the crates
+    /// depending on it don't select it, only the changes to its own crate or
+    /// its dependencies do.
+    fn select_changed(&self, changed: &[PathBuf]) -> Vec<(&'a str, String, Vec<&'a str>)> {
+        let build = self.build;
+        let relative = |path: &Path| {
+            path.strip_prefix(&build.src).unwrap_or(path).display().to_string()
+        };
+        let mut crates = build.crates.values().collect::<Vec<_>>();
+        crates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Map each changed file to the crate most specifically containing it,
+        // unless the path of a rule is more specific still, in which case it's
+        // mapped to the rules whose path contains it.
+        let mut changed_crates = HashMap::new();
+        let mut affected = HashMap::new();
+        for file in changed {
+            let krate = crates.iter().filter(|k| file.starts_with(&k.path))
+                              .max_by_key(|k| k.path.components().count());
+            let depth = krate.map_or(0, |k| k.path.components().count());
+            let rules = self.rules.values().filter(|rule| {
+                let path = build.src.join(rule.path);
+                file.starts_with(&path) && path.components().count() > depth
+            }).collect::<Vec<_>>();
+            match krate {
+                Some(krate) if rules.is_empty() => {
+                    changed_crates.entry(&krate.name[..]).or_insert((&krate.name[..], file));
+                }
+                _ => {
+                    for rule in rules {
+                        affected.entry(rule.name).or_insert_with(|| {
+                            format!("`{}` is in `{}`", relative(file), rule.path)
+                        });
+                    }
+                }
+            }
+        }
+        for (name, (changed, file)) in dependents(&crates, changed_crates) {
+            let krate = &build.crates[name];
+            let reason = if name == changed {
+                format!("crate `{}` contains `{}`", name, relative(file))
+            } else {
+                format!("crate `{}` depends on `{}`, which contains `{}`",
+                        name, changed, relative(file))
+            };
+            for step in &[&krate.build_step, &krate.test_step, &krate.doc_step,
+                          &krate.bench_step] {
+                affected.entry(&step[..]).or_insert(reason.clone());
+            }
+        }
+
+        let crate_tests = crates.iter().map(|k| (&k.test_step[..], &k.name[..]))
+                                .collect::<HashMap<_, _>>();
+        let candidates = self.rules.values().filter(|rule| {
+            rule.kind == Kind::Test &&
+                ((rule.default && !rule.all_crates) ||
+                 crate_tests.contains_key(rule.name))
+        });
+
+        let mut nodes = HashMap::new();
+        nodes.insert(Step::noop(), 0);
+        let mut edges = HashMap::new();
+        edges.insert(0, HashSet::new());
+        let candidates = candidates.map(|rule| {
+            (rule, self.build_graph(self.sbuild.name(rule.name), &mut nodes, &mut edges))
+        }).collect::<Vec<_>>();
+        let idx_to_node = nodes.iter().map(|p| (*p.1, p.0)).collect::<HashMap<_, _>>();
+
+        let mut selected = Vec::new();
+        for (rule, idx) in candidates {
+            if let Some(reason) = affected.get(rule.name) {
+                selected.push((rule.name, reason.clone(), Vec::new()));
+                continue
+            }
+            let ignore = match crate_tests.get(rule.name) {
+                Some(&name) => {
+                    let mut root = HashMap::new();
+                    root.insert(name, ());
+                    dependents(&crates, root).keys()
+                        .map(|name| &build.crates[*name].build_step[..])
+                        .collect()
+                }
+                None => HashSet::new(),
+            };
+
+            // Search breadth first for the closest affected dependency.
+            let mut parent = HashMap::new();
+            parent.insert(idx, idx);
+            let mut queue = VecDeque::new();
+            queue.push_back(idx);
+            while let Some(cur) = queue.pop_front() {
+                let name = idx_to_node[&cur].name;
+                if !ignore.contains(name) && affected.contains_key(name) {
+                    let mut chain = vec![name];
+                    let mut cur = cur;
+                    while cur != idx {
+                        cur = parent[&cur];
+                        chain.push(idx_to_node[&cur].name);
+                    }
+                    chain.reverse();
+                    selected.push((rule.name, affected[name].clone(), chain));
+                    break
+                }
+                let mut deps = edges[&cur].iter().cloned().collect::<Vec<_>>();
+                deps.sort();
+                for dep in deps {
+                    if !parent.contains_key(&dep) {
+                        parent.insert(dep, cur);
+                        queue.push_back(dep);
+                    }
+                }
+            }
+        }
+        return selected
+    }
+
     /// Execute all top-level targets indicated by `steps`.
     ///
     /// This will take the list returned by `plan` and then execute each step
@@ -1260,14 +1489,48 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
             self.build.verbose(&format!("\t{:?}", step));
         }
 
-        // And finally, iterate over everything and execute it.
+        if let Some(ref path) = self.build.flags.graph {
+            let graph = self.graph(steps);
+            let mut file = t!(File::create(path));
+            t!(graph.render(&mut file));
+            println!("wrote the graph of {} steps to {}", graph.steps.len(), path.display());
+        }
+        if self.build.flags.dry_run {
+            println!("dry run, the following steps would be executed in order:");
+            for step in order.iter() {
+                match self.status(step) {
+                    Some(status) => println!("    {} ({})", step, status),
+                    None => println!("    {}", step),
+                }
+            }
+            return
+        }
+
+        // And finally, iterate over everything and execute it, timing each
+        // step as we go.
+        let start = Instant::now();
+        let mut timings = Vec::new();
         for step in order.iter() {
-            if self.build.flags.keep_stage.map_or(false, |s| step.stage <= s) {
+            if self.kept(step) {
                 self.build.verbose(&format!("keeping step {:?}", step));
                 continue;
             }
             self.build.verbose(&format!("executing step {:?}", step));
+            let begin = Instant::now();
+            // A failing step exits bootstrap, so the timeline of the steps
+            // executed so far is written before running each step, with that
+            // step as unfinished.
+            let running = (step, begin.duration_since(start));
+            self.write_timeline(&timings, Some(running), None, start.elapsed());
             (self.rules[step.name].run)(step);
+            timings.push(Timing {
+                step: step.clone(),
+                start: begin.duration_since(start),
+                duration: begin.elapsed(),
+            });
+        }
+        if timings.len() > 0 {
+            self.report_timings(steps, &order, &timings, start.elapsed());
         }
 
         // Check for postponed failures from `test --no-fail-fast`.
@@ -1278,6 +1541,176 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
         }
     }
 
+    /// Writes the timeline of the steps which were executed out of `order` to
+    /// `build/timeline.json`, and prints the slowest ones along with the
+    /// critical path through them.
+    fn report_timings(&self,
+                      steps: &[Step<'a>],
+                      order: &[Step<'a>],
+                      timings: &[Timing<'a>],
+                      total: Duration) {
+        let durations = timings.iter()
+                               .map(|t| (t.step.clone(), t.duration))
+                               .collect::<HashMap<_, _>>();
+        let (length, path) = self.critical_path(steps, order, &durations);
+        let dst = self.write_timeline(timings, None, Some((length, &path[..])), total);
+
+        let mut slowest = timings.iter().collect::<Vec<_>>();
+        slowest.sort_by(|a, b| b.duration.cmp(&a.duration));
+        println!("\nfinished {} step(s) in {:.1}s, the slowest were:",
+                 timings.len(), secs(total));
+        for timing in slowest.iter().take(10) {
+            println!("    {:>8.1}s  {}", secs(timing.duration), timing.step);
+        }
+        println!("critical path ({:.1}s):", secs(length));
+        for step in path.iter().filter(|step| durations.contains_key(step)) {
+            println!("    {:>8.1}s  {}", secs(durations[step]), step);
+        }
+        println!("wrote the timeline to {}", dst.display());
+    }
+
+    /// Writes `build/timeline.json`, returning its path. `running` is the step
+    /// still running, if any, with its start, and `critical` the critical path
+    /// once all the steps have run.
+    fn write_timeline(&self,
+                      timings: &[Timing<'a>],
+                      running: Option<(&Step<'a>, Duration)>,
+                      critical: Option<(Duration, &[Step<'a>])>,
+                      total: Duration) -> PathBuf {
+        fn step_json(step: &Step, start: Duration) -> BTreeMap<String, Json> {
+            let mut obj = BTreeMap::new();
+            obj.insert("name".to_string(), Json::String(step.name.to_string()));
+            obj.insert("stage".to_string(), Json::U64(step.stage as u64));
+            obj.insert("host".to_string(), Json::String(step.host.to_string()));
+            obj.insert("target".to_string(), Json::String(step.target.to_string()));
+            obj.insert("start".to_string(), Json::F64(secs(start)));
+            obj
+        }
+
+        let mut timeline = BTreeMap::new();
+        timeline.insert("total".to_string(), Json::F64(secs(total)));
+        if let Some((length, _)) = critical {
+            timeline.insert("critical_path".to_string(), Json::F64(secs(length)));
+        }
+        let mut list = Vec::new();
+        for timing in timings {
+            let mut obj = step_json(&timing.step, timing.start);
+            obj.insert("duration".to_string(), Json::F64(secs(timing.duration)));
+            if let Some((_, path)) = critical {
+                obj.insert("critical".to_string(), Json::Boolean(path.contains(&timing.step)));
+            }
+            list.push(Json::Object(obj));
+        }
+        timeline.insert("steps".to_string(), Json::Array(list));
+        if let Some((step, start)) = running {
+            timeline.insert("unfinished".to_string(), Json::Object(step_json(step, start)));
+        }
+        t!(fs::create_dir_all(&self.build.out));
+        let dst = self.build.out.join("timeline.json");
+        t!(t!(File::create(&dst)).write_all(Json::Object(timeline).to_string().as_bytes()));
+        dst
+    }
+
+    /// Finds the chain of dependent steps, out of those needed to run `steps`,
+    /// which takes the longest to run given the `durations` of each step.
+    ///
+    /// This is how long the build would take with unlimited parallelism, and
+    /// the steps on it are the ones worth making faster. Steps which weren't
+    /// executed are taken to be free. Returns the total duration along with
+    /// the path, from the first step to run to the last.
+    fn critical_path(&self,
+                     steps: &[Step<'a>],
+                     order: &[Step<'a>],
+                     durations: &HashMap<Step<'a>, Duration>)
+                     -> (Duration, Vec<Step<'a>>) {
+        let mut nodes = HashMap::new();
+        nodes.insert(Step::noop(), 0);
+        let mut edges = HashMap::new();
+        edges.insert(0, HashSet::new());
+        for step in steps {
+            self.build_graph(step.clone(), &mut nodes, &mut edges);
+        }
+        self.satisfy_after_deps(&nodes, &mut edges);
+
+        // As `order` is topologically sorted, each step is visited after all
+        // of its dependencies, so the longest path ending at each one is known
+        // by the time we get to it.
+        let zero = Duration::new(0, 0);
+        let mut finish = HashMap::new();
+        let mut prev = HashMap::new();
+        finish.insert(0, zero);
+        let mut last = 0;
+        for step in order {
+            let idx = nodes[step];
+            let (longest, dep) = edges[&idx].iter()
+                .map(|dep| (finish[dep], *dep))
+                .max()
+                .unwrap_or((zero, 0));
+            let end = longest + durations.get(step).cloned().unwrap_or(zero);
+            finish.insert(idx, end);
+            prev.insert(idx, dep);
+            if end > finish[&last] {
+                last = idx;
+            }
+        }
+
+        let idx_to_node = nodes.iter().map(|p| (*p.1, p.0)).collect::<HashMap<_, _>>();
+        let mut path = Vec::new();
+        let mut cur = last;
+        while cur != 0 {
+            path.push(idx_to_node[&cur].clone());
+            cur = prev[&cur];
+        }
+        path.reverse();
+        return (finish[&last], path)
+    }
+
+    /// Whether `step` is skipped because of `--keep-stage`.
+    fn kept(&self, step: &Step<'a>) -> bool {
+        self.build.flags.keep_stage.map_or(false, |s| step.stage <= s)
+    }
+
+    /// Describes why running `step` would do nothing, if it's known to.
+    fn status(&self, step: &Step<'a>) -> Option<&'static str> {
+        if self.kept(step) {
+            return Some("kept")
+        }
+        match self.rules[step.name].up_to_date {
+            Some(ref up_to_date) if up_to_date(step) => Some("up to date"),
+            _ => None,
+        }
+    }
+
+    /// Builds the graph of all the steps needed to run `steps` for `--graph`.
+    fn graph(&self, steps: &[Step<'a>]) -> StepGraph<'a> {
+        let mut nodes = HashMap::new();
+        nodes.insert(Step::noop(), 0);
+        let mut edges = HashMap::new();
+        edges.insert(0, HashSet::new());
+        for step in steps {
+            self.build_graph(step.clone(), &mut nodes, &mut edges);
+        }
+        let deps = edges.clone();
+        self.satisfy_after_deps(&nodes, &mut edges);
+
+        let mut graph = StepGraph {
+            steps: BTreeMap::new(),
+            edges: Vec::new(),
+        };
+        for (step, &idx) in nodes.iter() {
+            if idx != 0 {
+                graph.steps.insert(idx, (step.clone(), self.status(step)));
+            }
+        }
+        for (&idx, dep_idxs) in edges.iter() {
+            for &dep in dep_idxs.iter().filter(|&&dep| dep != 0) {
+                graph.edges.push((idx, dep, !deps[&idx].contains(&dep)));
+            }
+        }
+        graph.edges.sort();
+        return graph
+    }
+
     /// From the top level targets `steps` generate a topological ordering of
     /// all steps needed to run those steps.
     fn expand(&self, steps: &[Step<'a>]) -> Vec<Step<'a>> {
@@ -1396,9 +1829,121 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
     }
 }
 
+/// When an executed step started, relative to the start of the run, and how
+/// long it took.
+struct Timing<'a> {
+    step: Step<'a>,
+    start: Duration,
+    duration: Duration,
+}
+
+fn secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+/// The graph of steps written by `--graph`, with each step's status, and
+/// edges from steps to their dependencies which are flagged if they only
+/// order the steps, coming from `Rule::after`.
+struct StepGraph<'a> {
+    steps: BTreeMap<usize, (Step<'a>, Option<&'static str>)>,
+    edges: Vec<(usize, usize, bool)>,
+}
+
+impl<'a> StepGraph<'a> {
+    /// Writes the graph in the dot format of Graphviz. Steps with a status,
+    /// which won't do anything, are filled, and order-only dependencies are
+    /// dashed.
+    ///
+    /// This doesn't use libgraphviz, which needs the unstable `str_escape`
+    /// feature, as rustbuild itself is built by the beta stage0 compiler.
+    fn render<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "digraph rustbuild {{")?;
+        for (n, &(ref step, status)) in &self.steps {
+            let mut label = format!("{}\\nstage{} ({} -> {})",
+                                    dot_escape(step.name), step.stage,
+                                    dot_escape(step.host), dot_escape(step.target));
+            if let Some(status) = status {
+                label.push_str("\\n");
+                label.push_str(&dot_escape(status));
+            }
+            write!(out, "    step{}[label=\"{}\"]", n, label)?;
+            if status.is_some() {
+                write!(out, "[style=\"filled\"]")?;
+            }
+            writeln!(out, ";")?;
+        }
+        for &(source, target, order_only) in &self.edges {
+            write!(out, "    step{} -> step{}", source, target)?;
+            if order_only {
+                write!(out, "[style=\"dashed\"]")?;
+            }
+            writeln!(out, ";")?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+/// Escapes `s` for use in a quoted string in the dot format.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Returns the absolute paths of the files described by `changes`.
+fn changed_files(build: &Build, changes: &Changes) -> Vec<PathBuf> {
+    let files = match *changes {
+        Changes::Since(ref rev) => {
+            if !build.src_is_git {
+                panic!("--changed requires the source to be a git checkout, \
+                        try --changed-files instead");
+            }
+            let mut files = output(Command::new("git").current_dir(&build.src)
+                                          .arg("diff").arg("--name-only").arg(rev));
+            files.push_str(&output(Command::new("git").current_dir(&build.src)
+                                          .arg("ls-files")
+                                          .arg("--others")
+                                          .arg("--exclude-standard")));
+            files
+        }
+        Changes::List(ref list) => {
+            let mut files = String::new();
+            t!(t!(File::open(list)).read_to_string(&mut files));
+            files
+        }
+    };
+    files.lines()
+         .map(|line| line.trim())
+         .filter(|line| !line.is_empty())
+         .map(|line| build.src.join(line))
+         .collect()
+}
+
+/// Extends `roots`, a map from the names of some crates, to all the crates
+/// which depend on them, directly or not. Each crate is mapped to the value
+/// of one of the roots it depends on.
+fn dependents<'c, T: Copy>(crates: &[&'c Crate],
+                           mut roots: HashMap<&'c str, T>) -> HashMap<&'c str, T> {
+    loop {
+        let mut progress = false;
+        for krate in crates {
+            if roots.contains_key(&krate.name[..]) {
+                continue
+            }
+            let value = krate.deps.iter().filter_map(|dep| roots.get(&dep[..])).next().cloned();
+            if let Some(value) = value {
+                roots.insert(&krate.name, value);
+                progress = true;
+            }
+        }
+        if !progress {
+            return roots
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::path::PathBuf;
 
     use Build;
     use config::Config;
@@ -1768,4 +2313,96 @@ mod tests {
         assert!(!plan.iter().any(|s| s.name.contains("tidy")));
         assert!(plan.iter().any(|s| s.name.contains("valgrind")));
     }
+
+    #[test]
+    fn test_changed() {
+        let mut build = build(&["test"], &[], &[]);
+        let cwd = env::current_dir().unwrap();
+        build.crates.get_mut("rustc-main").unwrap().deps.push("syntax".to_string());
+        build.crates.insert("syntax".to_string(), ::Crate {
+            name: "syntax".to_string(),
+            deps: Vec::new(),
+            version: String::new(),
+            path: cwd.join("src/libsyntax"),
+            doc_step: "doc-crate-syntax".to_string(),
+            build_step: "build-crate-syntax".to_string(),
+            test_step: "test-crate-syntax".to_string(),
+            bench_step: "bench-crate-syntax".to_string(),
+        });
+        let rules = super::build_rules(&build);
+        let select = |file: PathBuf| rules.select_changed(&[file]);
+        let names = |file: PathBuf| {
+            select(file).into_iter().map(|(name, _, _)| name).collect::<Vec<_>>()
+        };
+
+        // a test file only selects its suite
+        assert_eq!(names(build.src.join("src/test/ui/foo.rs")), ["check-ui"]);
+        assert!(names(build.src.join("README.md")).is_empty());
+
+        // compiletest is used by the suites, but not by the crate tests
+        let selected = names(build.src.join("src/tools/compiletest/src/main.rs"));
+        assert!(selected.contains(&"check-ui"));
+        assert!(selected.contains(&"check-rustdoc"));
+        assert!(!selected.contains(&"test-crate-std"));
+        assert!(!selected.contains(&"check-tidy"));
+
+        // a crate selects itself and the crates depending on it, but not the
+        // `-all` rules testing all the crates
+        let selected = select(build.crates["syntax"].path.join("lib.rs"));
+        let ui = selected.iter().find(|s| s.0 == "check-ui").unwrap();
+        assert_eq!(ui.2.first(), Some(&"check-ui"));
+        assert_eq!(ui.2.last(), Some(&"build-crate-rustc-main"));
+        let selected = selected.into_iter().map(|(name, _, _)| name).collect::<Vec<_>>();
+        assert!(selected.contains(&"test-crate-syntax"));
+        assert!(selected.contains(&"test-crate-rustc-main"));
+        assert!(!selected.contains(&"check-rustc-all"));
+        assert!(!selected.contains(&"check-tidy"));
+
+        // but not the crates it depends on
+        let selected = names(build.crates["rustc-main"].path.join("rustc.rs"));
+        assert!(selected.contains(&"test-crate-rustc-main"));
+        assert!(!selected.contains(&"test-crate-syntax"));
+    }
+
+    #[test]
+    fn graph() {
+        let build = build(&["test"], &[], &[]);
+        let rules = super::build_rules(&build);
+        let plan = rules.plan();
+        let mut dot = Vec::new();
+        rules.graph(&plan).render(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+
+        assert!(dot.starts_with("digraph rustbuild {"));
+        assert!(dot.contains(r#"[label="check-ui\nstage2 (A -> A)"]"#));
+        assert!(dot.contains(r#"[label="tool-compiletest\nstage0 (A -> A)"]"#));
+        // `maybe-clean-tools` is only ordered after cleaning the tools
+        assert!(dot.contains(r#"[style="dashed"]"#));
+    }
+
+    #[test]
+    fn critical_path() {
+        use std::collections::HashMap;
+        use std::time::Duration;
+
+        let build = build(&["test"], &[], &[]);
+        let rules = super::build_rules(&build);
+        let plan = rules.plan();
+        let order = rules.expand(&plan);
+        let mut durations = order.iter()
+                                 .map(|step| (step.clone(), Duration::from_secs(1)))
+                                 .collect::<HashMap<_, _>>();
+        let slow = order.iter().find(|s| s.name == "tool-compiletest").unwrap().clone();
+        durations.insert(slow.clone(), Duration::from_secs(1000));
+
+        let (length, path) = rules.critical_path(&plan, &order, &durations);
+        assert!(path.contains(&slow));
+        assert_eq!(length, Duration::from_secs(999 + path.len() as u64));
+        // each step on the path runs after the one before it
+        for pair in path.windows(2) {
+            let before = order.iter().position(|s| *s == pair[0]).unwrap();
+            let after = order.iter().position(|s| *s == pair[1]).unwrap();
+            assert!(before < after);
+        }
+    }
 }
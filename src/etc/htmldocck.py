@@ -11,6 +11,10 @@
 r"""
 htmldocck.py is a custom checker script for Rustdoc HTML outputs.
 
+The rustdoc test suite is checked by compiletest itself, which implements
+the same commands in `src/tools/compiletest/src/htmldocck.rs`; this script
+is still used by run-make tests through `$(HTMLDOCCK)`.
+
 # How and why?
 
 The principle is simple: This script receives a path to generated HTML
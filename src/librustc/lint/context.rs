@@ -36,6 +36,7 @@ use lint::builtin;
 use rustc_serialize::{Decoder, Decodable, Encoder, Encodable};
 use util::nodemap::FxHashMap;
 
+use std::ascii::AsciiExt;
 use std::cmp;
 use std::default::Default as StdDefault;
 use std::mem;
@@ -279,6 +280,15 @@ impl LintStore {
         }
     }
 
+    /// Looks up a lint by the name it is given in attributes or on the
+    /// command line, following renamings.
+    pub fn find_lint_by_name(&self, name: &str) -> Option<&'static Lint> {
+        match self.by_name.get(&name.to_ascii_lowercase().replace("-", "_")) {
+            Some(&Id(lint_id)) | Some(&Renamed(_, lint_id)) => Some(lint_id.lint),
+            Some(&Removed(_)) | None => None,
+        }
+    }
+
     pub fn register_renamed(&mut self, old_name: &str, new_name: &str) {
         let target = match self.by_name.get(new_name) {
             Some(&Id(lint_id)) => lint_id.clone(),
@@ -525,6 +535,7 @@ pub fn raw_struct_lint<'a, S>(sess: &'a Session,
         (Deny, None)     => sess.struct_err(&msg[..]),
         _ => bug!("impossible level in raw_emit_lint"),
     };
+    err.lint(name.clone());
 
     match source {
         Default => {
@@ -68,6 +68,13 @@ pub struct Lint {
     ///
     /// e.g. "imports that are never used"
     pub desc: &'static str,
+
+    /// Long-form explanation of the lint, with examples, printed by
+    /// `rustc --explain`.
+    ///
+    /// Like the explanations of error codes, it is written in Markdown and
+    /// starts with a newline.
+    pub explanation: Option<&'static str>,
 }
 
 impl Lint {
@@ -81,15 +88,20 @@ impl Lint {
 #[macro_export]
 macro_rules! lint_initializer {
     ($name:ident, $level:ident, $desc:expr) => (
+        lint_initializer!($name, $level, $desc, None)
+    );
+    ($name:ident, $level:ident, $desc:expr, $explanation:expr) => (
         ::rustc::lint::Lint {
             name: stringify!($name),
             default_level: ::rustc::lint::$level,
             desc: $desc,
+            explanation: $explanation,
         }
     )
 }
 
-/// Declare a static item of type `&'static Lint`.
+/// Declare a static item of type `&'static Lint`, optionally with a long-form
+/// explanation after its description.
 #[macro_export]
 macro_rules! declare_lint {
     (pub $name:ident, $level:ident, $desc:expr) => (
@@ -100,6 +112,14 @@ macro_rules! declare_lint {
         static $name: &'static ::rustc::lint::Lint
             = &lint_initializer!($name, $level, $desc);
     );
+    (pub $name:ident, $level:ident, $desc:expr, $explanation:expr) => (
+        pub static $name: &'static ::rustc::lint::Lint
+            = &lint_initializer!($name, $level, $desc, Some($explanation));
+    );
+    ($name:ident, $level:ident, $desc:expr, $explanation:expr) => (
+        static $name: &'static ::rustc::lint::Lint
+            = &lint_initializer!($name, $level, $desc, Some($explanation));
+    );
 }
 
 /// Declare a static `LintArray` and return it as an expression.
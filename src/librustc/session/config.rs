@@ -135,6 +135,8 @@ impl OutputType {
 pub enum ErrorOutputType {
     HumanReadable(ColorConfig),
     Json,
+    /// One line per diagnostic, as `file:line:col: level[code]: message`.
+    Short(ColorConfig),
 }
 
 impl Default for ErrorOutputType {
@@ -1033,6 +1035,10 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "a single extra argument to prepend the linker invocation (can be used several times)"),
     pre_link_args: Option<Vec<String>> = (None, parse_opt_list, [UNTRACKED],
         "extra arguments to prepend to the linker invocation (space separated)"),
+    aggregate_diagnostics: bool = (false, parse_bool, [UNTRACKED],
+        "report repeated identical warnings once, with their count and expansion sites"),
+    diagnostic_limit: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "show at most this many warnings of each lint"),
 }
 
 pub fn default_lib_output() -> CrateType {
@@ -1242,7 +1248,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
         opt::opt_s("",  "out-dir", "Write output to compiler-chosen filename \
                                 in <dir>", "DIR"),
         opt::opt_s("", "explain", "Provide a detailed explanation of an error \
-                               message", "OPT"),
+                               message or a lint", "OPT"),
         opt::flag_s("", "test", "Build a test harness"),
         opt::opt_s("", "target", "Target triple for which the code is compiled", "TARGET"),
         opt::multi_s("W", "warn", "Set lint warnings", "OPT"),
@@ -1270,7 +1276,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
         opt::multi("Z", "", "Set internal debugging options", "FLAG"),
         opt::opt_s("", "error-format",
                       "How errors and other messages are produced",
-                      "human|json"),
+                      "human|json|short"),
         opt::opt_s("", "color", "Configure coloring of output:
                                  auto   = colorize, if output goes to a tty (default);
                                  always = always colorize output;
@@ -1290,6 +1296,11 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
                       `hir` (the HIR), `hir,identified`, or
                       `hir,typed` (HIR with types for each node).",
                      "TYPE"),
+        opt::flagopt("", "fix",
+                     "Apply the suggestions the compiler is sure about to the source,
+                      checking the crate again until none are left; with `diff`, print
+                      the changes as a unified diff instead of writing them.",
+                     "diff"),
     ]);
     opts
 }
@@ -1339,13 +1350,14 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         match matches.opt_str("error-format").as_ref().map(|s| &s[..]) {
             Some("human")   => ErrorOutputType::HumanReadable(color),
             Some("json") => ErrorOutputType::Json,
+            Some("short") => ErrorOutputType::Short(color),
 
             None => ErrorOutputType::HumanReadable(color),
 
             Some(arg) => {
                 early_error(ErrorOutputType::HumanReadable(color),
-                            &format!("argument for --error-format must be human or json (instead \
-                                      was `{}`)",
+                            &format!("argument for --error-format must be human, json or \
+                                      short (instead was `{}`)",
                                      arg))
             }
         }
@@ -2509,6 +2521,10 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.dump_mir_dir = Some(String::from("abc"));
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.aggregate_diagnostics = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.diagnostic_limit = Some(10);
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
 
         // Make sure changing a [TRACKED] option changes the hash
         opts = reference.clone();
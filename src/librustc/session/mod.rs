@@ -588,11 +588,23 @@ pub fn build_session_with_codemap(sopts: config::Options,
     let emitter: Box<Emitter> = match (sopts.error_format, emitter_dest) {
         (config::ErrorOutputType::HumanReadable(color_config), None) => {
             Box::new(EmitterWriter::stderr(color_config,
-                                           Some(codemap.clone())))
+                                           Some(codemap.clone()),
+                                           false))
         }
         (config::ErrorOutputType::HumanReadable(_), Some(dst)) => {
             Box::new(EmitterWriter::new(dst,
-                                        Some(codemap.clone())))
+                                        Some(codemap.clone()),
+                                        false))
+        }
+        (config::ErrorOutputType::Short(color_config), None) => {
+            Box::new(EmitterWriter::stderr(color_config,
+                                           Some(codemap.clone()),
+                                           true))
+        }
+        (config::ErrorOutputType::Short(_), Some(dst)) => {
+            Box::new(EmitterWriter::new(dst,
+                                        Some(codemap.clone()),
+                                        true))
         }
         (config::ErrorOutputType::Json, None) => {
             Box::new(JsonEmitter::stderr(Some(registry), codemap.clone()))
@@ -606,6 +618,8 @@ pub fn build_session_with_codemap(sopts: config::Options,
         errors::Handler::with_emitter(can_print_warnings,
                                       treat_err_as_bug,
                                       emitter);
+    diagnostic_handler.set_aggregate_warnings(sopts.debugging_opts.aggregate_diagnostics);
+    diagnostic_handler.set_warning_limit(sopts.debugging_opts.diagnostic_limit);
 
     build_session_(sopts,
                    dep_graph,
@@ -731,7 +745,13 @@ pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
     let emitter: Box<Emitter> = match output {
         config::ErrorOutputType::HumanReadable(color_config) => {
             Box::new(EmitterWriter::stderr(color_config,
-                                           None))
+                                           None,
+                                           false))
+        }
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr(color_config,
+                                           None,
+                                           true))
         }
         config::ErrorOutputType::Json => Box::new(JsonEmitter::basic()),
     };
@@ -744,7 +764,13 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
     let emitter: Box<Emitter> = match output {
         config::ErrorOutputType::HumanReadable(color_config) => {
             Box::new(EmitterWriter::stderr(color_config,
-                                           None))
+                                           None,
+                                           false))
+        }
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr(color_config,
+                                           None,
+                                           true))
         }
         config::ErrorOutputType::Json => Box::new(JsonEmitter::basic()),
     };
@@ -0,0 +1,600 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `rustc --fix`, applying the suggestions of the compiler to the source.
+//!
+//! The crate is checked while keeping the suggestions of all diagnostics, and
+//! the machine-applicable ones are spliced into the sources, in memory. As a
+//! fix can uncover others, the crate is then checked again against the fixed
+//! sources, until nothing is left to fix. Only then are the fixed sources
+//! written back, or printed as a unified diff with `--fix=diff`.
+//!
+//! The diagnostics of the rounds which found something to fix are dropped,
+//! only those of the last round are printed.
+
+use rustc::session::{early_error, CompileResult, Session};
+use rustc::session::config::ErrorOutputType;
+use errors::{Applicability, CodeSuggestion};
+use getopts;
+use syntax::codemap::{CodeMap, FileLoader, RealFileLoader};
+use syntax_pos::{FileMap, NO_EXPANSION};
+
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::{run_compiler_with_matches, CompilerCalls};
+
+/// How many times the crate is checked at most, in case applying some
+/// suggestion keeps leading to another.
+const MAX_ROUNDS: usize = 16;
+
+/// Number of unchanged lines shown around the changes of a diff.
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FixMode {
+    /// Write the fixed sources back to their files.
+    Write,
+    /// Print the changes to the sources as a unified diff.
+    Diff,
+}
+
+/// Returns how `--fix` was asked for on the command line, if it was.
+pub fn mode(matches: &getopts::Matches) -> Option<FixMode> {
+    if !matches.opt_present("fix") {
+        return None
+    }
+    match matches.opt_str("fix").as_ref().map(|s| &s[..]) {
+        None => Some(FixMode::Write),
+        Some("diff") => Some(FixMode::Diff),
+        Some(arg) => {
+            early_error(ErrorOutputType::default(),
+                        &format!("argument for --fix must be `diff` (instead was `{}`)", arg))
+        }
+    }
+}
+
+/// A file fixed by some of the rounds.
+struct FixedFile {
+    /// The name of the file in the codemap, which is not where it is on disk
+    /// when its path was remapped.
+    name: String,
+    original: String,
+    fixed: String,
+    fixes: usize,
+}
+
+pub fn run<'a>(mode: FixMode,
+               matches: &getopts::Matches,
+               callbacks: &mut CompilerCalls<'a>,
+               emitter_dest: Option<Box<Write + Send>>)
+               -> (CompileResult, Option<Session>) {
+    let output = Arc::new(Mutex::new(Output {
+        buffered: true,
+        buf: Vec::new(),
+        dst: emitter_dest.unwrap_or(Box::new(io::stderr())),
+    }));
+    let mut files = HashMap::new();
+
+    for round in 1.. {
+        let read = Rc::new(RefCell::new(Vec::new()));
+        let loader = FixedFileLoader {
+            fixed: files.iter().map(|(path, file): (&PathBuf, &FixedFile)| {
+                (path.clone(), file.fixed.clone())
+            }).collect(),
+            read: read.clone(),
+        };
+        let dst = SharedOutput(output.clone());
+        let _flush = FlushOnPanic(output.clone());
+        let (result, sess) = run_compiler_with_matches(matches,
+                                                       callbacks,
+                                                       Some(Box::new(loader)),
+                                                       Some(Box::new(dst)),
+                                                       true);
+        let sess = match sess {
+            Some(sess) => sess,
+            None => {
+                lock(&output).flush_buffered();
+                return (result, None)
+            }
+        };
+
+        let fixes = fix_round(&sess, &read.borrow(), &mut files);
+        if fixes > 0 && round < MAX_ROUNDS {
+            lock(&output).buf.clear();
+            continue
+        }
+
+        lock(&output).flush_buffered();
+        if fixes > 0 {
+            sess.warn(&format!("stopped fixing the crate after {} rounds, it may need \
+                                to be fixed again", MAX_ROUNDS));
+        }
+        let mut files = files.into_iter().collect::<Vec<_>>();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        for (path, file) in files {
+            if file.original == file.fixed {
+                continue
+            }
+            match mode {
+                FixMode::Write => {
+                    let written = File::create(&path).and_then(|mut f| {
+                        f.write_all(file.fixed.as_bytes())
+                    });
+                    match written {
+                        Ok(()) => {
+                            sess.note_without_error(&format!("applied {} fix(es) to {}",
+                                                             file.fixes,
+                                                             file.name));
+                        }
+                        Err(e) => sess.err(&format!("failed to write {}: {}", file.name, e)),
+                    }
+                }
+                FixMode::Diff => {
+                    print!("{}", unified_diff(&file.name, &file.original, &file.fixed))
+                }
+            }
+        }
+        let result = if sess.err_count() > 0 {
+            Err(sess.err_count())
+        } else {
+            result
+        };
+        return (result, Some(sess))
+    }
+    unreachable!()
+}
+
+/// Applies the machine-applicable suggestions of the diagnostics emitted in
+/// `sess` to the files they are about, returning how many were applied.
+///
+/// The files are keyed by their path on disk, found among the paths `read`
+/// by the round, as the names of the files in the codemap are remapped by
+/// `--remap-path-prefix`.
+fn fix_round(sess: &Session,
+             read: &[PathBuf],
+             files: &mut HashMap<PathBuf, FixedFile>)
+             -> usize {
+    let codemap = sess.codemap();
+    let paths = read.iter().map(|path| {
+        let name = path.to_string_lossy().into_owned();
+        (codemap.path_mapping().map_prefix(name).0, path.clone())
+    }).collect::<HashMap<_, _>>();
+    let mut suggestions = HashMap::new();
+    for suggestion in sess.diagnostic().take_suggestions() {
+        if suggestion.applicability != Applicability::MachineApplicable {
+            continue
+        }
+        if let Some((fm, edits)) = file_edits(codemap, &suggestion) {
+            suggestions.entry(fm.name.clone())
+                       .or_insert((fm, Vec::new()))
+                       .1.push(edits);
+        }
+    }
+
+    let mut total = 0;
+    for (name, (fm, suggestions)) in suggestions {
+        let src = match fm.src {
+            Some(ref src) => src,
+            None => continue,
+        };
+        let path = match paths.get(&name) {
+            Some(path) => path.clone(),
+            None => continue,
+        };
+        let (fixes, edits) = pick_edits(suggestions);
+        let file = files.entry(path).or_insert_with(|| {
+            FixedFile {
+                name: name,
+                original: (**src).clone(),
+                fixed: String::new(),
+                fixes: 0,
+            }
+        });
+        file.fixed = splice(src, &edits);
+        file.fixes += fixes;
+        total += fixes;
+    }
+    total
+}
+
+/// A replacement of the bytes `lo..hi` of a file by `text`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Edit {
+    lo: usize,
+    hi: usize,
+    text: String,
+}
+
+impl Edit {
+    fn overlaps(&self, other: &Edit) -> bool {
+        (self.lo < other.hi && other.lo < self.hi) || self.lo == other.lo
+    }
+}
+
+/// Turns `suggestion` into edits of the file it is about, unless it can't be
+/// applied without a human choosing how: when it has several alternatives,
+/// is about code expanded from a macro, or spans several files.
+fn file_edits(codemap: &CodeMap,
+              suggestion: &CodeSuggestion)
+              -> Option<(Rc<FileMap>, Vec<Edit>)> {
+    let mut file: Option<Rc<FileMap>> = None;
+    let mut edits = Vec::new();
+    for part in &suggestion.substitution_parts {
+        if part.substitutions.len() != 1 || part.span.ctxt != NO_EXPANSION {
+            return None
+        }
+        let lo = codemap.lookup_byte_offset(part.span.lo);
+        let hi = codemap.lookup_byte_offset(part.span.hi);
+        if !lo.fm.is_real_file() || lo.fm.is_imported() || lo.fm.start_pos != hi.fm.start_pos {
+            return None
+        }
+        if let Some(ref file) = file {
+            if file.start_pos != lo.fm.start_pos {
+                return None
+            }
+        }
+        edits.push(Edit {
+            lo: lo.pos.0 as usize,
+            hi: hi.pos.0 as usize,
+            text: part.substitutions[0].clone(),
+        });
+        file = Some(lo.fm);
+    }
+    file.map(|file| (file, edits))
+}
+
+/// Picks the suggestions to apply to a file, each given as its edits, and
+/// returns how many were picked along with all their edits, in order.
+///
+/// Suggestions are picked by their position in the file, skipping those
+/// touching code an earlier one replaces; they are left for the next round.
+fn pick_edits(mut suggestions: Vec<Vec<Edit>>) -> (usize, Vec<Edit>) {
+    for suggestion in &mut suggestions {
+        suggestion.sort();
+    }
+    suggestions.sort();
+    // the same diagnostic may have been emitted more than once
+    suggestions.dedup();
+
+    let mut picked = 0;
+    let mut edits: Vec<Edit> = Vec::new();
+    for suggestion in suggestions {
+        if suggestion.iter().any(|a| edits.iter().any(|b| a.overlaps(b))) {
+            continue
+        }
+        picked += 1;
+        edits.extend(suggestion);
+    }
+    edits.sort();
+    (picked, edits)
+}
+
+/// Applies the sorted, disjoint `edits` to `src`.
+fn splice(src: &str, edits: &[Edit]) -> String {
+    let mut fixed = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits {
+        fixed.push_str(&src[pos..edit.lo]);
+        fixed.push_str(&edit.text);
+        pos = edit.hi;
+    }
+    fixed.push_str(&src[pos..]);
+    fixed
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Returns the lines of `a` and `b` as a shortest edit script turning one into
+/// the other, following Myers' "An O(ND) Difference Algorithm".
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let offset = n + m + 1;
+    let mut v = vec![0; 2 * offset as usize + 1];
+    // `v` as it was before each step, to walk the edits back from the end
+    let mut trace = Vec::new();
+
+    'search: for d in 0..n + m + 1 {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'search
+            }
+            k += 2;
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[(k - 1 + offset) as usize] <
+                                              v[(k + 1 + offset) as usize]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            lines.push(DiffLine::Same(a[x as usize - 1]));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                lines.push(DiffLine::Added(b[y as usize - 1]));
+            } else {
+                lines.push(DiffLine::Removed(a[x as usize - 1]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    lines.reverse();
+    lines
+}
+
+/// Splits `s` into its lines, each ending with its `\n` if it has one.
+fn split_lines(s: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut rest = s;
+    while let Some(i) = rest.find('\n') {
+        lines.push(&rest[..i + 1]);
+        rest = &rest[i + 1..];
+    }
+    if !rest.is_empty() {
+        lines.push(rest);
+    }
+    lines
+}
+
+/// Returns the differences between the `old` and `new` contents of the file
+/// `name`, in the unified format understood by `patch`.
+fn unified_diff(name: &str, old: &str, new: &str) -> String {
+    let old = split_lines(old);
+    let new = split_lines(new);
+    let lines = diff_lines(&old, &new);
+    let changes = lines.iter().enumerate().filter(|&(_, line)| {
+        match *line {
+            DiffLine::Same(_) => false,
+            _ => true,
+        }
+    }).map(|(i, _)| i).collect::<Vec<_>>();
+
+    let mut out = format!("--- {}\n+++ {}\n", name, name);
+    let mut i = 0;
+    while i < changes.len() {
+        // gather the changes close enough to share their context
+        let mut j = i;
+        while j + 1 < changes.len() && changes[j + 1] - changes[j] <= 2 * DIFF_CONTEXT {
+            j += 1;
+        }
+        let start = changes[i].saturating_sub(DIFF_CONTEXT);
+        let end = cmp::min(changes[j] + DIFF_CONTEXT + 1, lines.len());
+
+        // line numbers of the start of the hunk in both files
+        let (mut old_line, mut new_line) = (0, 0);
+        for line in &lines[..start] {
+            match *line {
+                DiffLine::Same(_) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                DiffLine::Removed(_) => old_line += 1,
+                DiffLine::Added(_) => new_line += 1,
+            }
+        }
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|l| match **l {
+            DiffLine::Added(_) => false,
+            _ => true,
+        }).count();
+        let new_len = hunk.iter().filter(|l| match **l {
+            DiffLine::Removed(_) => false,
+            _ => true,
+        }).count();
+        // an empty range is numbered after the line it follows
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n",
+                              if old_len == 0 { old_line } else { old_line + 1 },
+                              old_len,
+                              if new_len == 0 { new_line } else { new_line + 1 },
+                              new_len));
+        for line in hunk {
+            let (prefix, l) = match *line {
+                DiffLine::Same(l) => (' ', l),
+                DiffLine::Removed(l) => ('-', l),
+                DiffLine::Added(l) => ('+', l),
+            };
+            out.push(prefix);
+            out.push_str(l);
+            // the last line differs from the same line ending the file
+            if !l.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        i = j + 1;
+    }
+    out
+}
+
+/// Serves the fixed sources instead of the files on disk, and records the
+/// paths of the files read.
+struct FixedFileLoader {
+    fixed: HashMap<PathBuf, String>,
+    read: Rc<RefCell<Vec<PathBuf>>>,
+}
+
+impl FileLoader for FixedFileLoader {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.fixed.contains_key(path) || RealFileLoader.file_exists(path)
+    }
+
+    fn abs_path(&self, path: &Path) -> Option<PathBuf> {
+        RealFileLoader.abs_path(path)
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        self.read.borrow_mut().push(path.to_path_buf());
+        match self.fixed.get(path) {
+            Some(src) => Ok(src.clone()),
+            None => RealFileLoader.read_file(path),
+        }
+    }
+}
+
+/// Where the diagnostics of the rounds go: they are buffered until it is
+/// known whether the round is the last one.
+struct Output {
+    buffered: bool,
+    buf: Vec<u8>,
+    dst: Box<Write + Send>,
+}
+
+impl Output {
+    /// Prints what was buffered, and stops buffering.
+    fn flush_buffered(&mut self) {
+        if self.buffered {
+            self.buffered = false;
+            let _ = self.dst.write_all(&self.buf);
+            self.buf.clear();
+        }
+    }
+}
+
+fn lock(output: &Mutex<Output>) -> ::std::sync::MutexGuard<Output> {
+    output.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+struct SharedOutput(Arc<Mutex<Output>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut output = lock(&self.0);
+        if output.buffered {
+            output.buf.extend_from_slice(buf);
+            Ok(buf.len())
+        } else {
+            output.dst.write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        lock(&self.0).dst.flush()
+    }
+}
+
+/// Prints the diagnostics of a round which ended in a fatal error.
+struct FlushOnPanic(Arc<Mutex<Output>>);
+
+impl Drop for FlushOnPanic {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            lock(&self.0).flush_buffered();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edit, pick_edits, splice, unified_diff};
+
+    fn edit(lo: usize, hi: usize, text: &str) -> Edit {
+        Edit { lo: lo, hi: hi, text: text.to_string() }
+    }
+
+    #[test]
+    fn overlapping_suggestions() {
+        let src = "let mut x = (1);";
+        let suggestions = vec![
+            vec![edit(12, 15, "1")],
+            vec![edit(4, 9, "x")],
+            // within the code the first one replaces, left for another round
+            vec![edit(13, 13, "0")],
+            // emitted twice
+            vec![edit(4, 9, "x")],
+        ];
+        let (picked, edits) = pick_edits(suggestions);
+        assert_eq!(picked, 2);
+        assert_eq!(splice(src, &edits), "let x = 1;");
+    }
+
+    #[test]
+    fn diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nm\nn\n";
+        assert_eq!(unified_diff("x.rs", old, new), "\
+--- x.rs
++++ x.rs
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -9,5 +9,5 @@
+ i
+ j
+ k
+-l
+ m
++n
+");
+    }
+
+    #[test]
+    fn diff_newline_at_end_of_file() {
+        assert_eq!(unified_diff("x.rs", "a\nb", "a\nb\n"), "\
+--- x.rs
++++ x.rs
+@@ -1,2 +1,2 @@
+ a
+-b
+\\ No newline at end of file
++b
+");
+        assert_eq!(unified_diff("x.rs", "a\nb\n", "a\nc"), "\
+--- x.rs
++++ x.rs
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+\\ No newline at end of file
+");
+    }
+}
@@ -110,6 +110,7 @@ pub mod driver;
 pub mod pretty;
 pub mod target_features;
 mod derive_registrar;
+mod fix;
 
 const BUG_REPORT_URL: &'static str = "https://github.com/rust-lang/rust/blob/master/CONTRIBUTING.\
                                       md#bug-reports";
@@ -142,7 +143,9 @@ pub fn run<F>(run_compiler: F) -> isize
                     Some(sess) => sess.fatal(&abort_msg(err_count)),
                     None => {
                         let emitter =
-                            errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto, None);
+                            errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto,
+                                                                   None,
+                                                                   false);
                         let handler = errors::Handler::with_emitter(true, false, Box::new(emitter));
                         handler.emit(&MultiSpan::new(),
                                      &abort_msg(err_count),
@@ -164,6 +167,27 @@ pub fn run_compiler<'a>(args: &[String],
                         file_loader: Option<Box<FileLoader + 'static>>,
                         emitter_dest: Option<Box<Write + Send>>)
                         -> (CompileResult, Option<Session>)
+{
+    let matches = match handle_options(args) {
+        Some(matches) => matches,
+        None => return (Ok(()), None),
+    };
+
+    if let Some(mode) = fix::mode(&matches) {
+        return fix::run(mode, &matches, callbacks, emitter_dest);
+    }
+    run_compiler_with_matches(&matches, callbacks, file_loader, emitter_dest, false)
+}
+
+// Run the compiler with the already parsed command line. When `fixing`, the
+// suggestions of all diagnostics are kept for `rustc --fix`, and the crate is
+// only checked, not translated.
+fn run_compiler_with_matches<'a>(matches: &getopts::Matches,
+                                 callbacks: &mut CompilerCalls<'a>,
+                                 file_loader: Option<Box<FileLoader + 'static>>,
+                                 emitter_dest: Option<Box<Write + Send>>,
+                                 fixing: bool)
+                                 -> (CompileResult, Option<Session>)
 {
     macro_rules! do_or_return {($expr: expr, $sess: expr) => {
         match $expr {
@@ -172,12 +196,7 @@ pub fn run_compiler<'a>(args: &[String],
         }
     }}
 
-    let matches = match handle_options(args) {
-        Some(matches) => matches,
-        None => return (Ok(()), None),
-    };
-
-    let (sopts, cfg) = config::build_session_options_and_crate_config(&matches);
+    let (sopts, cfg) = config::build_session_options_and_crate_config(matches);
 
     if sopts.debugging_opts.debug_llvm {
         rustc_trans::enable_llvm_debug();
@@ -185,17 +204,17 @@ pub fn run_compiler<'a>(args: &[String],
 
     let descriptions = diagnostics_registry();
 
-    do_or_return!(callbacks.early_callback(&matches,
+    do_or_return!(callbacks.early_callback(matches,
                                            &sopts,
                                            &cfg,
                                            &descriptions,
                                            sopts.error_format),
                                            None);
 
-    let (odir, ofile) = make_output(&matches);
+    let (odir, ofile) = make_output(matches);
     let (input, input_file_path) = match make_input(&matches.free) {
         Some((input, input_file_path)) => callbacks.some_input(input, input_file_path),
-        None => match callbacks.no_input(&matches, &sopts, &cfg, &odir, &ofile, &descriptions) {
+        None => match callbacks.no_input(matches, &sopts, &cfg, &odir, &ofile, &descriptions) {
             Some((input, input_file_path)) => (input, input_file_path),
             None => return (Ok(()), None),
         },
@@ -211,17 +230,25 @@ pub fn run_compiler<'a>(args: &[String],
     );
     rustc_trans::init(&sess);
     rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
+    if fixing {
+        sess.diagnostic().keep_suggestions();
+    }
 
     let mut cfg = config::build_configuration(&sess, cfg);
     target_features::add_configuration(&mut cfg, &sess);
     sess.parse_sess.config = cfg;
 
-    do_or_return!(callbacks.late_callback(&matches, &sess, &input, &odir, &ofile), Some(sess));
+    do_or_return!(callbacks.late_callback(matches, &sess, &input, &odir, &ofile), Some(sess));
 
     let plugins = sess.opts.debugging_opts.extra_plugins.clone();
-    let control = callbacks.build_controller(&sess, &matches);
-    (driver::compile_input(&sess, &cstore, &input, &odir, &ofile, Some(plugins), &control),
-     Some(sess))
+    let mut control = callbacks.build_controller(&sess, matches);
+    if fixing {
+        control.after_analysis.stop = Compilation::Stop;
+    }
+    let result = driver::compile_input(&sess, &cstore, &input, &odir, &ofile, Some(plugins),
+                                       &control);
+    sess.diagnostic().flush_warnings();
+    (result, Some(sess))
 }
 
 // Extract output directory and file from matches.
@@ -353,12 +380,21 @@ pub struct RustcDefaultCalls;
 fn handle_explain(code: &str,
                   descriptions: &errors::registry::Registry,
                   output: ErrorOutputType) {
-    let normalised = if code.starts_with("E") {
-        code.to_string()
+    let digits = if code.starts_with("E") { &code[1..] } else { code };
+    let explanation = if digits.chars().all(|c| c.is_digit(10)) {
+        let normalised = if code.starts_with("E") {
+            code.to_string()
+        } else {
+            format!("E{0:0>4}", code)
+        };
+        descriptions.find_description(&normalised)
     } else {
-        format!("E{0:0>4}", code)
+        // Not an error code, so maybe the name of a lint.
+        let mut lints = lint::LintStore::new();
+        rustc_lint::register_builtins(&mut lints, None);
+        lints.find_lint_by_name(code).and_then(|lint| lint.explanation)
     };
-    match descriptions.find_description(&normalised) {
+    match explanation {
         Some(ref description) => {
             // Slice off the leading newline and print.
             print!("{}", &(&description[1..]).split("\n").map(|x| {
@@ -1089,7 +1125,9 @@ pub fn monitor<F: FnOnce() + Send + 'static>(f: F) {
         // Thread panicked without emitting a fatal diagnostic
         if !value.is::<errors::FatalError>() {
             let emitter =
-                Box::new(errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto, None));
+                Box::new(errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto,
+                                                                None,
+                                                                false));
             let handler = errors::Handler::with_emitter(true, false, emitter);
 
             // a .span_bug or .bug call has already printed what
@@ -0,0 +1,164 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Aggregation of repeated warnings, and the limit on the number of warnings
+//! shown for each lint.
+//!
+//! Code produced by macros, or translated once per monomorphization, can make
+//! the compiler report the same warning many times over. When aggregation is
+//! enabled, warnings are held back until the end of the compilation instead,
+//! and each of them is reported once, along with the number of times it
+//! occurred and the macro invocations it occurred in.
+
+use Diagnostic;
+
+use std::collections::HashMap;
+use syntax_pos::{BytePos, Span};
+
+/// What makes two warnings the same: their message, code and lint, and the
+/// source text their primary spans point at, whatever they were expanded
+/// from.
+#[derive(PartialEq, Eq, Hash)]
+struct Key {
+    message: String,
+    code: Option<String>,
+    lint: Option<String>,
+    spans: Vec<(BytePos, BytePos)>,
+}
+
+impl Key {
+    fn new(diagnostic: &Diagnostic) -> Key {
+        Key {
+            message: diagnostic.message(),
+            code: diagnostic.code.clone(),
+            lint: diagnostic.lint.clone(),
+            spans: diagnostic.span.primary_spans().iter().map(|sp| (sp.lo, sp.hi)).collect(),
+        }
+    }
+}
+
+/// A warning held back until the end of the compilation.
+pub struct HeldWarning {
+    pub diagnostic: Diagnostic,
+    /// The number of times the warning was reported.
+    pub count: usize,
+    /// The macro invocations the warning was reported in, in the order they
+    /// were first seen.
+    pub expansion_sites: Vec<Span>,
+}
+
+impl HeldWarning {
+    fn add_expansion_sites(&mut self, diagnostic: &Diagnostic) {
+        for &sp in diagnostic.span.primary_spans() {
+            let site = sp.source_callsite();
+            if site != sp && !self.expansion_sites.iter().any(|s| s.source_equal(&site)) {
+                self.expansion_sites.push(site);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Warnings {
+    /// Whether repeated warnings are aggregated.
+    pub aggregate: bool,
+    /// The number of warnings shown for each lint, if limited.
+    pub limit: Option<usize>,
+    held: Vec<HeldWarning>,
+    /// The warnings seen so far, with the index of their entry in `held`, or
+    /// `None` if they were left out because of the limit.
+    seen: HashMap<Key, Option<usize>>,
+    /// The number of warnings shown so far for each lint.
+    shown: HashMap<String, usize>,
+    /// The number of warnings left out for each lint, in the order the lints
+    /// first reached the limit.
+    omitted: Vec<(String, usize)>,
+}
+
+impl Warnings {
+    /// Records the warning `diagnostic`, and returns whether it should be
+    /// emitted right away. If not, it was either held back to be emitted by
+    /// `take_held`, or left out because of the limit.
+    pub fn admit(&mut self, diagnostic: &Diagnostic) -> bool {
+        if !self.aggregate && self.limit.is_none() {
+            return true;
+        }
+
+        let key = if self.aggregate {
+            let key = Key::new(diagnostic);
+            match self.seen.get(&key) {
+                Some(&Some(index)) => {
+                    let held = &mut self.held[index];
+                    held.count += 1;
+                    held.add_expansion_sites(diagnostic);
+                    return false;
+                }
+                Some(&None) => return false,
+                None => {}
+            }
+            Some(key)
+        } else {
+            None
+        };
+
+        let within_limit = self.within_limit(diagnostic);
+        match key {
+            Some(key) => {
+                let index = if within_limit {
+                    let mut held = HeldWarning {
+                        diagnostic: diagnostic.clone(),
+                        count: 1,
+                        expansion_sites: vec![],
+                    };
+                    held.add_expansion_sites(diagnostic);
+                    self.held.push(held);
+                    Some(self.held.len() - 1)
+                } else {
+                    None
+                };
+                self.seen.insert(key, index);
+                false
+            }
+            None => within_limit,
+        }
+    }
+
+    /// Counts `diagnostic` as shown, unless its lint already reached the limit.
+    fn within_limit(&mut self, diagnostic: &Diagnostic) -> bool {
+        let (limit, lint) = match (self.limit, diagnostic.lint.as_ref()) {
+            (Some(limit), Some(lint)) => (limit, lint),
+            _ => return true,
+        };
+
+        let shown = self.shown.entry(lint.clone()).or_insert(0);
+        if *shown < limit {
+            *shown += 1;
+            return true;
+        }
+
+        if let Some(&mut (_, ref mut count)) = self.omitted.iter_mut().find(|o| o.0 == *lint) {
+            *count += 1;
+            return false;
+        }
+        self.omitted.push((lint.clone(), 1));
+        false
+    }
+
+    /// Returns the warnings held back so far.
+    pub fn take_held(&mut self) -> Vec<HeldWarning> {
+        self.seen.clear();
+        ::std::mem::replace(&mut self.held, vec![])
+    }
+
+    /// Returns the number of warnings left out so far for each lint.
+    pub fn take_omitted(&mut self) -> Vec<(String, usize)> {
+        ::std::mem::replace(&mut self.omitted, vec![])
+    }
+}
@@ -8,6 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use Applicability;
 use Diagnostic;
 use DiagnosticStyledString;
 
@@ -97,7 +98,12 @@ impl<'a> DiagnosticBuilder<'a> {
             }
         }
 
-        self.handler.emitter.borrow_mut().emit(&self);
+        if let Some(ref mut kept) = *self.handler.kept_suggestions.borrow_mut() {
+            kept.extend(self.suggestions.iter().cloned());
+        }
+        if self.level != Level::Warning || self.handler.warnings.borrow_mut().admit(&self) {
+            self.handler.emitter.borrow_mut().emit(&self);
+        }
         self.cancel();
 
         if self.level == Level::Error {
@@ -156,8 +162,21 @@ impl<'a> DiagnosticBuilder<'a> {
                                      msg: &str,
                                      suggestions: Vec<String>)
                                      -> &mut Self);
+    forward!(pub fn span_suggestion_with_applicability(&mut self,
+                                                       sp: Span,
+                                                       msg: &str,
+                                                       suggestion: String,
+                                                       applicability: Applicability)
+                                                       -> &mut Self);
+    forward!(pub fn span_suggestions_with_applicability(&mut self,
+                                                        sp: Span,
+                                                        msg: &str,
+                                                        suggestions: Vec<String>,
+                                                        applicability: Applicability)
+                                                        -> &mut Self);
     forward!(pub fn set_span<S: Into<MultiSpan>>(&mut self, sp: S) -> &mut Self);
     forward!(pub fn code(&mut self, s: String) -> &mut Self);
+    forward!(pub fn lint(&mut self, name: String) -> &mut Self);
 
     /// Convenience function for internal use, clients should use one of the
     /// struct_* methods on Handler.
@@ -178,6 +197,16 @@ impl<'a> DiagnosticBuilder<'a> {
         }
     }
 
+    /// Creates a new `DiagnosticBuilder` with an already constructed
+    /// diagnostic.
+    pub fn new_diagnostic(handler: &'a Handler, diagnostic: Diagnostic)
+                          -> DiagnosticBuilder<'a> {
+        DiagnosticBuilder {
+            handler: handler,
+            diagnostic: diagnostic,
+        }
+    }
+
     pub fn into_diagnostic(mut self) -> Diagnostic {
         // annoyingly, the Drop impl means we can't actually move
         let result = self.diagnostic.clone();
@@ -46,7 +46,12 @@ impl Emitter for EmitterWriter {
                // don't display multiline suggestions as labels
                sugg.substitution_parts[0].substitutions[0].find('\n').is_none() {
                 let substitution = &sugg.substitution_parts[0].substitutions[0];
-                let msg = format!("help: {} `{}`", sugg.msg, substitution);
+                let msg = if substitution.is_empty() {
+                    // the suggestion is to remove the code under the span
+                    format!("help: {}", sugg.msg)
+                } else {
+                    format!("help: {} `{}`", sugg.msg, substitution)
+                };
                 primary_span.push_span_label(sugg.substitution_spans().next().unwrap(), msg);
             } else {
                 // if there are multiple suggestions, print them all in full
@@ -65,11 +70,15 @@ impl Emitter for EmitterWriter {
         }
 
         self.fix_multispans_in_std_macros(&mut primary_span, &mut children);
-        self.emit_messages_default(&db.level,
-                                   &db.styled_message(),
-                                   &db.code,
-                                   &primary_span,
-                                   &children);
+        if self.short_message {
+            self.emit_messages_short(&db.level, &db.styled_message(), &db.code, &primary_span);
+        } else {
+            self.emit_messages_default(&db.level,
+                                       &db.styled_message(),
+                                       &db.code,
+                                       &primary_span,
+                                       &children);
+        }
     }
 }
 
@@ -100,6 +109,7 @@ impl ColorConfig {
 pub struct EmitterWriter {
     dst: Destination,
     cm: Option<Rc<CodeMapper>>,
+    short_message: bool,
 }
 
 struct FileWithAnnotatedLines {
@@ -109,25 +119,34 @@ struct FileWithAnnotatedLines {
 }
 
 impl EmitterWriter {
-    pub fn stderr(color_config: ColorConfig, code_map: Option<Rc<CodeMapper>>) -> EmitterWriter {
+    pub fn stderr(color_config: ColorConfig,
+                  code_map: Option<Rc<CodeMapper>>,
+                  short_message: bool)
+                  -> EmitterWriter {
         if color_config.use_color() {
             let dst = Destination::from_stderr();
             EmitterWriter {
                 dst: dst,
                 cm: code_map,
+                short_message: short_message,
             }
         } else {
             EmitterWriter {
                 dst: Raw(Box::new(io::stderr())),
                 cm: code_map,
+                short_message: short_message,
             }
         }
     }
 
-    pub fn new(dst: Box<Write + Send>, code_map: Option<Rc<CodeMapper>>) -> EmitterWriter {
+    pub fn new(dst: Box<Write + Send>,
+               code_map: Option<Rc<CodeMapper>>,
+               short_message: bool)
+               -> EmitterWriter {
         EmitterWriter {
             dst: Raw(dst),
             cm: code_map,
+            short_message: short_message,
         }
     }
 
@@ -1093,6 +1112,35 @@ impl EmitterWriter {
         }
         Ok(())
     }
+    /// Emits the message on a single line, as `file:line:col: level[code]: message`, leaving
+    /// out the source and the children, for grepping or the quickfix lists of editors.
+    fn emit_messages_short(&mut self,
+                           level: &Level,
+                           message: &Vec<(String, Style)>,
+                           code: &Option<String>,
+                           span: &MultiSpan) {
+        let mut buffer = StyledBuffer::new();
+        if let (Some(ref cm), Some(primary_span)) = (self.cm.as_ref(), span.primary_span()) {
+            if primary_span != DUMMY_SP {
+                let loc = cm.lookup_char_pos(primary_span.lo);
+                buffer.append(0,
+                              &format!("{}:{}:{}: ", loc.file.name, loc.line, loc.col.0 + 1),
+                              Style::LineNumber);
+            }
+        }
+        buffer.append(0, &level.to_string(), Style::Level(level.clone()));
+        if let Some(ref code) = *code {
+            buffer.append(0, &format!("[{}]", code), Style::Level(level.clone()));
+        }
+        buffer.append(0, ": ", Style::HeaderMsg);
+        for &(ref text, _) in message.iter() {
+            buffer.append(0, &text.replace('\n', " "), Style::HeaderMsg);
+        }
+        if let Err(e) = emit_to_destination(&buffer.render(), level, &mut self.dst) {
+            panic!("failed to emit error: {}", e);
+        }
+    }
+
     fn emit_messages_default(&mut self,
                              level: &Level,
                              message: &Vec<(String, Style)>,
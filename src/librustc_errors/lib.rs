@@ -47,6 +47,7 @@ pub mod emitter;
 pub mod snippet;
 pub mod registry;
 pub mod styled_buffer;
+mod aggregate;
 mod lock;
 
 use syntax_pos::{BytePos, Loc, FileLinesResult, FileName, MultiSpan, Span, NO_EXPANSION};
@@ -87,6 +88,43 @@ pub struct CodeSuggestion {
     /// ```
     pub substitution_parts: Vec<Substitution>,
     pub msg: String,
+    /// Whether the substitutions can be applied without a human looking at
+    /// them first.
+    pub applicability: Applicability,
+}
+
+/// How confident the compiler is that a suggestion is what the user wants.
+///
+/// Tools applying suggestions automatically, like editors, should only apply
+/// the `MachineApplicable` ones without asking.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, RustcEncodable, RustcDecodable)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended, and applying it
+    /// results in valid code.
+    MachineApplicable,
+
+    /// The suggestion may be what the user intended, but it is uncertain,
+    /// and applying it may result in code which doesn't compile or which
+    /// behaves differently.
+    MaybeIncorrect,
+
+    /// The suggestion contains placeholders like `<expression>` which have to
+    /// be filled in by the user before the code compiles.
+    HasPlaceholders,
+
+    /// The applicability of the suggestion is not known.
+    Unspecified,
+}
+
+impl Applicability {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+            Applicability::HasPlaceholders => "has-placeholders",
+            Applicability::Unspecified => "unspecified",
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, RustcEncodable, RustcDecodable)]
@@ -263,6 +301,9 @@ pub struct Handler {
     treat_err_as_bug: bool,
     continue_after_error: Cell<bool>,
     delayed_span_bug: RefCell<Option<(MultiSpan, String)>>,
+    /// The suggestions of the diagnostics emitted so far, if they are kept.
+    kept_suggestions: RefCell<Option<Vec<CodeSuggestion>>>,
+    warnings: RefCell<aggregate::Warnings>,
 }
 
 impl Handler {
@@ -271,7 +312,7 @@ impl Handler {
                             treat_err_as_bug: bool,
                             cm: Option<Rc<CodeMapper>>)
                             -> Handler {
-        let emitter = Box::new(EmitterWriter::stderr(color_config, cm));
+        let emitter = Box::new(EmitterWriter::stderr(color_config, cm, false));
         Handler::with_emitter(can_emit_warnings, treat_err_as_bug, emitter)
     }
 
@@ -286,6 +327,8 @@ impl Handler {
             treat_err_as_bug: treat_err_as_bug,
             continue_after_error: Cell::new(true),
             delayed_span_bug: RefCell::new(None),
+            kept_suggestions: RefCell::new(None),
+            warnings: RefCell::new(aggregate::Warnings::default()),
         }
     }
 
@@ -293,6 +336,58 @@ impl Handler {
         self.continue_after_error.set(continue_after_error);
     }
 
+    /// Keeps the suggestions of the diagnostics emitted from now on, so that
+    /// they can be applied to the source with `take_suggestions`.
+    pub fn keep_suggestions(&self) {
+        *self.kept_suggestions.borrow_mut() = Some(Vec::new());
+    }
+
+    /// Returns the suggestions kept since `keep_suggestions` was called, and
+    /// stops keeping them.
+    pub fn take_suggestions(&self) -> Vec<CodeSuggestion> {
+        self.kept_suggestions.borrow_mut().take().unwrap_or(Vec::new())
+    }
+
+    /// Holds back the warnings emitted from now on until `flush_warnings` is
+    /// called or the handler is dropped, reporting each of them once however
+    /// many times it was emitted.
+    pub fn set_aggregate_warnings(&self, aggregate: bool) {
+        self.warnings.borrow_mut().aggregate = aggregate;
+    }
+
+    /// Shows at most `limit` warnings of each lint, the others being counted
+    /// by a note when `flush_warnings` is called.
+    pub fn set_warning_limit(&self, limit: Option<usize>) {
+        self.warnings.borrow_mut().limit = limit;
+    }
+
+    /// Emits the warnings held back so far, and notes for the warnings left
+    /// out because of the limit.
+    pub fn flush_warnings(&self) {
+        let held = self.warnings.borrow_mut().take_held();
+        for warning in held {
+            let mut db = DiagnosticBuilder::new_diagnostic(self, warning.diagnostic);
+            if warning.count > 1 {
+                db.note(&format!("this warning occurred {} times", warning.count));
+            }
+            if warning.expansion_sites.len() > 1 {
+                db.span_note(MultiSpan::from_spans(warning.expansion_sites),
+                             "in these macro invocations");
+            }
+            self.emitter.borrow_mut().emit(&db);
+            db.cancel();
+        }
+
+        let omitted = self.warnings.borrow_mut().take_omitted();
+        for (lint, count) in omitted {
+            self.note_without_error(&format!("{} more warning{} of this kind (`{}`) {} not shown",
+                                             count,
+                                             if count == 1 { "" } else { "s" },
+                                             lint,
+                                             if count == 1 { "was" } else { "were" }));
+        }
+    }
+
     pub fn struct_dummy<'a>(&'a self) -> DiagnosticBuilder<'a> {
         DiagnosticBuilder::new(self, Level::Cancelled, "")
     }
@@ -511,6 +606,8 @@ impl Handler {
             _ => s = "aborting due to previous error(s)".to_string(),
         }
 
+        self.flush_warnings();
+
         panic!(self.fatal(&s));
     }
     pub fn emit(&self, msp: &MultiSpan, msg: &str, lvl: Level) {
@@ -537,6 +634,16 @@ impl Handler {
     }
 }
 
+impl Drop for Handler {
+    fn drop(&mut self) {
+        // the compilation may have ended with a fatal error or a panic instead
+        // of `abort_if_errors`, the warnings held back would be lost then
+        if self.emitter.try_borrow_mut().is_ok() {
+            self.flush_warnings();
+        }
+    }
+}
+
 
 #[derive(Copy, PartialEq, Clone, Debug, RustcEncodable, RustcDecodable)]
 pub enum Level {
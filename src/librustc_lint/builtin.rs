@@ -43,6 +43,7 @@ use std::collections::HashSet;
 
 use syntax::ast;
 use syntax::attr;
+use syntax::errors::Applicability;
 use syntax::feature_gate::{AttributeGate, AttributeType, Stability, deprecated_attributes};
 use syntax_pos::Span;
 use syntax::symbol::keywords;
@@ -640,10 +641,14 @@ impl EarlyLintPass for DeprecatedAttr {
                                              ref name,
                                              ref reason,
                                              _) = g {
-                    cx.span_lint(DEPRECATED,
-                                 attr.span,
-                                 &format!("use of deprecated attribute `{}`: {}. See {}",
-                                          name, reason, link));
+                    let msg = format!("use of deprecated attribute `{}`: {}. See {}",
+                                      name, reason, link);
+                    cx.struct_span_lint(DEPRECATED, attr.span, &msg)
+                      .span_suggestion_with_applicability(attr.span,
+                                                          "remove this attribute",
+                                                          String::new(),
+                                                          Applicability::MachineApplicable)
+                      .emit();
                 }
                 return;
             }
@@ -729,7 +734,32 @@ impl EarlyLintPass for IllegalFloatLiteralPattern {
 declare_lint! {
     pub UNCONDITIONAL_RECURSION,
     Warn,
-    "functions that cannot return without calling themselves"
+    "functions that cannot return without calling themselves",
+    r##"
+A function or method calls itself on every path through its body, so it can
+never return.
+
+Erroneous code example:
+
+```
+fn foo() {
+    foo(); // warning: function cannot return without recurring
+}
+```
+
+Such a function recurses until it overflows the stack, and is usually the
+result of a typo, for example calling a method of the same name on the wrong
+value. Make sure there is at least one path through the function that does not
+call it again:
+
+```
+fn count_down(n: u32) {
+    if n > 0 {
+        count_down(n - 1);
+    }
+}
+```
+"##
 }
 
 #[derive(Copy, Clone)]
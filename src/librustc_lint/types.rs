@@ -52,7 +52,41 @@ declare_lint! {
 declare_lint! {
     VARIANT_SIZE_DIFFERENCES,
     Allow,
-    "detects enums with widely varying variant sizes"
+    "detects enums with widely varying variant sizes",
+    r##"
+The largest variant of an enum is more than three times as large as the
+second largest one.
+
+Erroneous code example:
+
+```compile_fail
+#![deny(variant_size_differences)]
+
+enum Message {
+    Ping,
+    Tick(u32),
+    Data([u8; 1024]), // error: enum variant is more than three times larger
+                      //        (1024 bytes) than the next largest
+}
+# fn main() {}
+```
+
+A value of an enum is as large as its largest variant, so every `Message` takes
+more than a kilobyte of memory, even the ones which are only a `Ping`. This lint
+is allowed by default, as this is often fine, but it can be worth boxing the
+data of the largest variant when most values are of the smaller ones:
+
+```
+#![deny(variant_size_differences)]
+
+enum Message {
+    Ping,
+    Tick(u32),
+    Data(Box<[u8; 1024]>), // ok!
+}
+# fn main() {}
+```
+"##
 }
 
 #[derive(Copy, Clone)]
@@ -328,7 +362,37 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for TypeLimits {
 declare_lint! {
     IMPROPER_CTYPES,
     Warn,
-    "proper use of libc types in foreign modules"
+    "proper use of libc types in foreign modules",
+    r##"
+A type which does not have a stable layout compatible with C is used in the
+signature of a function declared in an `extern` block.
+
+Erroneous code example:
+
+```
+extern {
+    fn puts(s: &str); // warning: found Rust type `str` in foreign module;
+                      //          consider using a `*const libc::c_char`
+}
+# fn main() {}
+```
+
+Foreign functions are called with the C ABI, so the types of their arguments and
+return values must have the same layout in Rust and in C. `str` slices, `String`,
+`Vec`, trait objects, tuples and structs or enums without a `#[repr(C)]`
+attribute have no defined layout, and `char` or `isize` have no exact
+equivalent in C. Use types matching the C declaration instead, such as raw
+pointers, `#[repr(C)]` structs or the types of the `libc` crate:
+
+```
+use std::os::raw::c_char;
+
+extern {
+    fn puts(s: *const c_char) -> i32; // ok!
+}
+# fn main() {}
+```
+"##
 }
 
 struct ImproperCTypesVisitor<'a, 'tcx: 'a> {
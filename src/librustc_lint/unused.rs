@@ -18,6 +18,7 @@ use std::collections::hash_map::Entry::{Occupied, Vacant};
 
 use syntax::ast;
 use syntax::attr;
+use syntax::errors::Applicability;
 use syntax::feature_gate::{BUILTIN_ATTRIBUTES, AttributeType};
 use syntax::symbol::keywords;
 use syntax::ptr::P;
@@ -63,9 +64,24 @@ impl UnusedMut {
         let used_mutables = cx.tcx.used_mut_nodes.borrow();
         for (_, v) in &mutables {
             if !v.iter().any(|e| used_mutables.contains(e)) {
-                cx.span_lint(UNUSED_MUT,
-                             cx.tcx.hir.span(v[0]),
-                             "variable does not need to be mutable");
+                let span = cx.tcx.hir.span(v[0]);
+                let mut err = cx.struct_span_lint(UNUSED_MUT,
+                                                  span,
+                                                  "variable does not need to be mutable");
+                // removing the `mut` of only one of the patterns binding the
+                // name would make them disagree, so only suggest it for one
+                if v.len() == 1 {
+                    if let Ok(snippet) = cx.tcx.sess.codemap().span_to_snippet(span) {
+                        if snippet.starts_with("mut ") {
+                            err.span_suggestion_with_applicability(
+                                span,
+                                "remove this `mut`:",
+                                snippet["mut ".len()..].trim_left().to_string(),
+                                Applicability::MachineApplicable);
+                        }
+                    }
+                }
+                err.emit();
             }
         }
     }
@@ -337,9 +353,28 @@ impl UnusedParens {
         if let ast::ExprKind::Paren(ref inner) = value.node {
             let necessary = struct_lit_needs_parens && contains_exterior_struct_lit(&inner);
             if !necessary {
-                cx.span_lint(UNUSED_PARENS,
-                             value.span,
-                             &format!("unnecessary parentheses around {}", msg))
+                let mut err = cx.struct_span_lint(UNUSED_PARENS,
+                                                  value.span,
+                                                  &format!("unnecessary parentheses around {}",
+                                                           msg));
+                let codemap = cx.sess().codemap();
+                if let Ok(mut snippet) = codemap.span_to_snippet(inner.span) {
+                    // keep `if(x)` from becoming `ifx`
+                    let start = codemap.lookup_byte_offset(value.span.lo);
+                    let joined = start.fm.src.as_ref().map_or(false, |src| {
+                        src[..start.pos.0 as usize].chars().next_back().map_or(false, |c| {
+                            c.is_alphanumeric() || c == '_'
+                        })
+                    });
+                    if joined {
+                        snippet.insert(0, ' ');
+                    }
+                    err.span_suggestion_with_applicability(value.span,
+                                                           "remove these parentheses:",
+                                                           snippet,
+                                                           Applicability::MachineApplicable);
+                }
+                err.emit();
             }
         }
 
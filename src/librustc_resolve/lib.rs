@@ -66,7 +66,7 @@ use syntax::ast::{QSelf, TraitItemKind, TraitRef, Ty, TyKind};
 use syntax::feature_gate::{feature_err, emit_feature_err, GateIssue};
 
 use syntax_pos::{Span, DUMMY_SP, MultiSpan};
-use errors::DiagnosticBuilder;
+use errors::{Applicability, DiagnosticBuilder};
 
 use std::cell::{Cell, RefCell};
 use std::cmp;
@@ -3621,7 +3621,9 @@ fn show_candidates(err: &mut DiagnosticBuilder,
         *candidate = format!("use {};\n", candidate);
     }
 
-    err.span_suggestions(span, &msg, path_strings);
+    // Only one of the candidates, if any, is the right one to import.
+    err.span_suggestions_with_applicability(span, &msg, path_strings,
+                                            Applicability::MaybeIncorrect);
 }
 
 /// A somewhat inefficient routine to obtain the name of a module.
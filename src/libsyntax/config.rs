@@ -358,6 +358,8 @@ fn is_cfg(attr: &ast::Attribute) -> bool {
     attr.check_name("cfg")
 }
 
+// Also true for the fixtures of tests, which only exist in test builds as well.
 pub fn is_test_or_bench(attr: &ast::Attribute) -> bool {
-    attr.check_name("test") || attr.check_name("bench")
+    attr.check_name("test") || attr.check_name("bench") ||
+        attr.check_name("test_setup") || attr.check_name("test_teardown")
 }
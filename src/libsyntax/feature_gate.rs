@@ -352,6 +352,12 @@ declare_features! (
 
     // rustc internal
     (active, abi_thiscall, "1.19.0", None),
+
+    // Allows `#[test_setup]` and `#[test_teardown]` functions in modules with tests
+    (active, test_fixtures, "1.19.0", None),
+
+    // Allows `#[test(retries = N)]`
+    (active, test_retries, "1.19.0", None),
 );
 
 declare_features! (
@@ -764,6 +770,14 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
         Stability::Unstable, "used",
         "the `#[used]` attribute is an experimental feature",
         cfg_fn!(used))),
+    ("test_setup", Normal, Gated(
+        Stability::Unstable, "test_fixtures",
+        "the `#[test_setup]` attribute is an experimental feature",
+        cfg_fn!(test_fixtures))),
+    ("test_teardown", Normal, Gated(
+        Stability::Unstable, "test_fixtures",
+        "the `#[test_teardown]` attribute is an experimental feature",
+        cfg_fn!(test_fixtures))),
 
     // used in resolve
     ("prelude_import", Whitelisted, Gated(Stability::Unstable,
@@ -1116,7 +1130,10 @@ impl<'a> Visitor<'a> for PostExpansionVisitor<'a> {
         }
 
         let meta = panictry!(attr.parse_meta(self.context.parse_sess));
-        if contains_novel_literal(&meta) {
+        if attr.check_name("test") && meta.is_meta_item_list() {
+            gate_feature_post!(&self, test_retries, attr.span,
+                               "arguments to the `#[test]` attribute are experimental");
+        } else if contains_novel_literal(&meta) {
             gate_feature_post!(&self, attr_literals, attr.span,
                                "non-string literals in attributes, or string \
                                literals in top-level positions, are experimental");
@@ -23,11 +23,13 @@ use codemap::{CodeMap, FilePathMapping};
 use syntax_pos::{self, MacroBacktrace, Span, SpanLabel, MultiSpan};
 use errors::registry::Registry;
 use errors::{DiagnosticBuilder, SubDiagnostic, RenderSpan, CodeSuggestion, CodeMapper};
-use errors::emitter::Emitter;
+use errors::Applicability;
+use errors::emitter::{Emitter, EmitterWriter};
 
 use std::rc::Rc;
 use std::io::{self, Write};
 use std::vec;
+use std::sync::{Arc, Mutex};
 
 use rustc_serialize::json::as_json;
 
@@ -84,9 +86,10 @@ struct Diagnostic {
     spans: Vec<DiagnosticSpan>,
     /// Associated diagnostic messages.
     children: Vec<Diagnostic>,
-    /// The message as rustc would render it. Currently this is only
-    /// `Some` for "suggestions", but eventually it will include all
-    /// snippets.
+    /// The message as rustc would render it with `--error-format=human`,
+    /// children included. This is `None` for the children, except those
+    /// holding a suggestion, for which it is the code with the suggestion
+    /// applied.
     rendered: Option<String>,
 }
 
@@ -113,6 +116,10 @@ struct DiagnosticSpan {
     /// load the fully rendered version from the parent `Diagnostic`,
     /// however.
     suggested_replacement: Option<String>,
+    /// If `suggested_replacement` is set, how safe it is to apply without
+    /// a human looking at it: "machine-applicable", "maybe-incorrect",
+    /// "has-placeholders" or "unspecified".
+    suggestion_applicability: Option<&'static str>,
     /// Macro invocations that created the code at this span, if any.
     expansion: Option<Box<DiagnosticSpanMacroExpansion>>,
 }
@@ -173,7 +180,7 @@ impl Diagnostic {
             children: db.children.iter().map(|c| {
                 Diagnostic::from_sub_diagnostic(c, je)
             }).chain(sugg).collect(),
-            rendered: None,
+            rendered: Some(je.render_diagnostic(db)),
         }
     }
 
@@ -193,7 +200,7 @@ impl Diagnostic {
 
 impl DiagnosticSpan {
     fn from_span_label(span: SpanLabel,
-                       suggestion: Option<&String>,
+                       suggestion: Option<(&String, Applicability)>,
                        je: &JsonEmitter)
                        -> DiagnosticSpan {
         Self::from_span_etc(span.span,
@@ -206,7 +213,7 @@ impl DiagnosticSpan {
     fn from_span_etc(span: Span,
                      is_primary: bool,
                      label: Option<String>,
-                     suggestion: Option<&String>,
+                     suggestion: Option<(&String, Applicability)>,
                      je: &JsonEmitter)
                      -> DiagnosticSpan {
         // obtain the full backtrace from the `macro_backtrace`
@@ -226,7 +233,7 @@ impl DiagnosticSpan {
     fn from_span_full(span: Span,
                       is_primary: bool,
                       label: Option<String>,
-                      suggestion: Option<&String>,
+                      suggestion: Option<(&String, Applicability)>,
                       mut backtrace: vec::IntoIter<MacroBacktrace>,
                       je: &JsonEmitter)
                       -> DiagnosticSpan {
@@ -264,7 +271,8 @@ impl DiagnosticSpan {
             column_end: end.col.0 + 1,
             is_primary: is_primary,
             text: DiagnosticSpanLine::from_span(span, je),
-            suggested_replacement: suggestion.cloned(),
+            suggested_replacement: suggestion.map(|x| x.0.clone()),
+            suggestion_applicability: suggestion.map(|x| x.1.to_str()),
             expansion: backtrace_step,
             label: label,
         }
@@ -282,14 +290,15 @@ impl DiagnosticSpan {
         suggestion.substitution_parts
                       .iter()
                       .flat_map(|substitution| {
-                          substitution.substitutions.iter().map(move |suggestion| {
+                          substitution.substitutions.iter().map(move |replacement| {
                               let span_label = SpanLabel {
                                   span: substitution.span,
                                   is_primary: true,
                                   label: None,
                               };
                               DiagnosticSpan::from_span_label(span_label,
-                                                              Some(suggestion),
+                                                              Some((replacement,
+                                                                    suggestion.applicability)),
                                                               je)
                           })
                       })
@@ -361,5 +370,25 @@ impl JsonEmitter {
     fn render(&self, suggestion: &CodeSuggestion) -> Vec<String> {
         suggestion.splice_lines(&*self.cm)
     }
+
+    /// Renders `db` like the human readable emitter does, without colors.
+    fn render_diagnostic(&self, db: &DiagnosticBuilder) -> String {
+        #[derive(Clone, Default)]
+        struct BufWriter(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for BufWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buf = BufWriter::default();
+        EmitterWriter::new(Box::new(buf.clone()), Some(self.cm.clone()), false).emit(db);
+        let rendered = buf.0.lock().unwrap();
+        String::from_utf8_lossy(&rendered).into_owned()
+    }
 }
 
@@ -18,6 +18,7 @@ use self::HasTestSignature::*;
 use std::iter;
 use std::slice;
 use std::mem;
+use std::u32;
 use std::vec;
 use attr::{self, HasAttrs};
 use syntax_pos::{self, DUMMY_SP, NO_EXPANSION, Span, FileMap, BytePos};
@@ -52,7 +53,24 @@ struct Test {
     path: Vec<Ident> ,
     bench: bool,
     ignore: bool,
-    should_panic: ShouldPanic
+    should_panic: ShouldPanic,
+    // from #[test(retries = N)]
+    retries: Option<usize>,
+    // the fixtures of the enclosing modules, outermost first
+    fixtures: Vec<Fixture>,
+}
+
+// The paths to the #[test_setup] and #[test_teardown] functions of a module
+#[derive(Clone)]
+struct Fixture {
+    setup: Option<Vec<Ident>>,
+    teardown: Option<Vec<Ident>>,
+}
+
+impl Fixture {
+    fn is_empty(&self) -> bool {
+        self.setup.is_none() && self.teardown.is_none()
+    }
 }
 
 struct TestCtxt<'a> {
@@ -61,6 +79,8 @@ struct TestCtxt<'a> {
     path: Vec<Ident>,
     ext_cx: ExtCtxt<'a>,
     testfns: Vec<Test>,
+    // the fixtures of the modules we are in, outermost first
+    fixtures: Vec<Fixture>,
     reexport_test_harness_main: Option<Symbol>,
     is_test_crate: bool,
     ctxt: SyntaxContext,
@@ -101,7 +121,10 @@ struct TestHarnessGenerator<'a> {
 
 impl<'a> fold::Folder for TestHarnessGenerator<'a> {
     fn fold_crate(&mut self, c: ast::Crate) -> ast::Crate {
+        let fixture = module_fixture(&self.cx, &c.module);
+        self.cx.fixtures.push(fixture);
         let mut folded = fold::noop_fold_crate(c, self);
+        self.cx.fixtures.pop();
 
         // Add a special __test module to the crate that will contain code
         // generated for the test harness
@@ -133,12 +156,20 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
                         path: self.cx.path.clone(),
                         bench: is_bench_fn(&self.cx, &i),
                         ignore: is_ignored(&i),
-                        should_panic: should_panic(&i, &self.cx)
+                        should_panic: should_panic(&i, &self.cx),
+                        retries: retries(&i, &self.cx),
+                        fixtures: self.cx.fixtures.iter()
+                                                  .filter(|f| !f.is_empty())
+                                                  .cloned()
+                                                  .collect(),
                     };
                     self.cx.testfns.push(test);
                     self.tests.push(i.ident);
                 }
             }
+        } else if is_fixture_fn(&i) {
+            // Fixtures are called from the test harness like the tests
+            self.tests.push(i.ident);
         }
 
         let mut item = i.unwrap();
@@ -147,7 +178,10 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
         if let ast::ItemKind::Mod(module) = item.node {
             let tests = mem::replace(&mut self.tests, Vec::new());
             let tested_submods = mem::replace(&mut self.tested_submods, Vec::new());
+            let fixture = module_fixture(&self.cx, &module);
+            self.cx.fixtures.push(fixture);
             let mut mod_folded = fold::noop_fold_mod(module, self);
+            self.cx.fixtures.pop();
             let tests = mem::replace(&mut self.tests, tests);
             let tested_submods = mem::replace(&mut self.tested_submods, tested_submods);
 
@@ -275,6 +309,7 @@ fn generate_test_harness(sess: &ParseSess,
         ext_cx: ExtCtxt::new(sess, ExpansionConfig::default("test".to_string()), resolver),
         path: Vec::new(),
         testfns: Vec::new(),
+        fixtures: Vec::new(),
         reexport_test_harness_main: reexport_test_harness_main,
         is_test_crate: is_test_crate(&krate),
         toplevel_reexport: None,
@@ -312,28 +347,28 @@ enum HasTestSignature {
     NotEvenAFunction,
 }
 
-fn is_test_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
-    let has_test_attr = attr::contains_name(&i.attrs, "test");
-
-    fn has_test_signature(i: &ast::Item) -> HasTestSignature {
-        match i.node {
-          ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
-            let no_output = match decl.output {
-                ast::FunctionRetTy::Default(..) => true,
-                ast::FunctionRetTy::Ty(ref t) if t.node == ast::TyKind::Tup(vec![]) => true,
-                _ => false
-            };
-            if decl.inputs.is_empty()
-                   && no_output
-                   && !generics.is_parameterized() {
-                Yes
-            } else {
-                No
-            }
-          }
-          _ => NotEvenAFunction,
+fn has_test_signature(i: &ast::Item) -> HasTestSignature {
+    match i.node {
+      ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
+        let no_output = match decl.output {
+            ast::FunctionRetTy::Default(..) => true,
+            ast::FunctionRetTy::Ty(ref t) if t.node == ast::TyKind::Tup(vec![]) => true,
+            _ => false
+        };
+        if decl.inputs.is_empty()
+               && no_output
+               && !generics.is_parameterized() {
+            Yes
+        } else {
+            No
         }
+      }
+      _ => NotEvenAFunction,
     }
+}
+
+fn is_test_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
+    let has_test_attr = attr::contains_name(&i.attrs, "test");
 
     if has_test_attr {
         let diag = cx.span_diagnostic;
@@ -379,10 +414,101 @@ fn is_bench_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
     has_bench_attr && has_test_signature(i)
 }
 
+fn fixture_attr(i: &ast::Item) -> Option<&'static str> {
+    if attr::contains_name(&i.attrs, "test_setup") {
+        Some("test_setup")
+    } else if attr::contains_name(&i.attrs, "test_teardown") {
+        Some("test_teardown")
+    } else {
+        None
+    }
+}
+
+fn is_fixture_fn(i: &ast::Item) -> bool {
+    fixture_attr(i).is_some() && has_test_signature(i) == Yes &&
+        match i.node {
+            ast::ItemKind::Fn(_, ast::Unsafety::Unsafe, _, _, _, _) => false,
+            _ => true,
+        }
+}
+
+// Find the #[test_setup] and #[test_teardown] functions of the module at
+// `cx.path`, reporting any which can't be used as such.
+fn module_fixture(cx: &TestCtxt, module: &ast::Mod) -> Fixture {
+    let diag = cx.span_diagnostic;
+    let mut fixture = Fixture { setup: None, teardown: None };
+    for item in &module.items {
+        let name = match fixture_attr(item) {
+            Some(name) => name,
+            None => continue,
+        };
+        if attr::contains_name(&item.attrs, "test_setup") &&
+           attr::contains_name(&item.attrs, "test_teardown") {
+            diag.span_err(item.span, "a function cannot be both `#[test_setup]` and \
+                                      `#[test_teardown]`");
+            continue
+        }
+        if attr::contains_name(&item.attrs, "test") || attr::contains_name(&item.attrs, "bench") {
+            diag.span_err(item.span, &format!("`#[{}]` functions cannot be tests", name));
+            continue
+        }
+        if !is_fixture_fn(item) {
+            diag.span_err(item.span, &format!("functions used as `#[{}]` must have signature \
+                                               `fn() -> ()`", name));
+            continue
+        }
+
+        let slot = if name == "test_setup" { &mut fixture.setup } else { &mut fixture.teardown };
+        if slot.is_some() {
+            diag.span_err(item.span, &format!("a module can only have one `#[{}]` function",
+                                              name));
+            continue
+        }
+        let mut path = cx.path.clone();
+        path.push(item.ident);
+        *slot = Some(path);
+    }
+    fixture
+}
+
 fn is_ignored(i: &ast::Item) -> bool {
     i.attrs.iter().any(|attr| attr.check_name("ignore"))
 }
 
+fn retries(i: &ast::Item, cx: &TestCtxt) -> Option<usize> {
+    let attr = match i.attrs.iter().find(|attr| attr.check_name("test")) {
+        Some(attr) => attr,
+        None => return None,
+    };
+    let list = match attr.meta_item_list() {
+        Some(list) => list,
+        None => return None,
+    };
+    let retries = list.iter()
+        .find(|mi| mi.check_name("retries"))
+        .and_then(|mi| mi.meta_item())
+        .and_then(|mi| match mi.node {
+            ast::MetaItemKind::NameValue(ref lit) => match lit.node {
+                ast::LitKind::Int(n, _) => Some((n, lit.span)),
+                _ => None,
+            },
+            _ => None,
+        });
+    if list.len() != 1 || retries.is_none() {
+        cx.span_diagnostic.span_err(attr.span(), "argument must be of the form: `retries = N`");
+    }
+    // the number ends up in a `usize` of the target, whose size isn't known here
+    match retries {
+        Some((n, _)) if n <= u32::MAX as u128 => Some(n as usize),
+        Some((_, span)) => {
+            cx.span_diagnostic.span_err(span, &format!("the number of retries can be at most {}",
+                                                       u32::MAX));
+            None
+        }
+        None => None,
+    }
+}
+
 fn should_panic(i: &ast::Item, cx: &TestCtxt) -> ShouldPanic {
     match i.attrs.iter().find(|attr| attr.check_name("should_panic")) {
         Some(attr) => {
@@ -669,29 +795,59 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
         }
     };
 
+    let retries_expr = match test.retries {
+        Some(n) => ecx.expr_some(span, ecx.expr_usize(span, n)),
+        None => ecx.expr_none(span),
+    };
+
     // self::test::TestDesc { ... }
     let desc_expr = ecx.expr_struct(
         span,
         test_path("TestDesc"),
         vec![field("name", name_expr),
              field("ignore", ignore_expr),
-             field("should_panic", fail_expr)]);
+             field("should_panic", fail_expr),
+             field("retries", retries_expr)]);
 
 
-    let mut visible_path = match cx.toplevel_reexport {
-        Some(id) => vec![id],
-        None => {
-            let diag = cx.span_diagnostic;
-            diag.bug("expected to find top-level re-export name, but found None");
-        }
+    // creates the path to a function through the re-exports
+    let visible_fn_expr = |path: &[Ident]| {
+        let mut visible_path = match cx.toplevel_reexport {
+            Some(id) => vec![id],
+            None => {
+                let diag = cx.span_diagnostic;
+                diag.bug("expected to find top-level re-export name, but found None");
+            }
+        };
+        visible_path.extend(path.iter().cloned());
+        ecx.expr_path(ecx.path_global(span, visible_path))
     };
-    visible_path.extend(path);
 
-    let fn_expr = ecx.expr_path(ecx.path_global(span, visible_path));
+    let fn_expr = visible_fn_expr(&path[..]);
 
-    let variant_name = if test.bench { "StaticBenchFn" } else { "StaticTestFn" };
-    // self::test::$variant_name($fn_expr)
-    let testfn_expr = ecx.expr_call(span, ecx.expr_path(test_path(variant_name)), vec![fn_expr]);
+    let testfn_expr = if test.bench || test.fixtures.is_empty() {
+        let variant_name = if test.bench { "StaticBenchFn" } else { "StaticTestFn" };
+        // self::test::$variant_name($fn_expr)
+        ecx.expr_call(span, ecx.expr_path(test_path(variant_name)), vec![fn_expr])
+    } else {
+        let hook_expr = |hook: &Option<Vec<Ident>>| {
+            match *hook {
+                Some(ref path) => ecx.expr_some(span, visible_fn_expr(&path[..])),
+                None => ecx.expr_none(span),
+            }
+        };
+        // self::test::Fixture { ... }
+        let fixture_exprs = test.fixtures.iter().map(|fixture| {
+            ecx.expr_struct(span,
+                            test_path("Fixture"),
+                            vec![field("setup", hook_expr(&fixture.setup)),
+                                 field("teardown", hook_expr(&fixture.teardown))])
+        }).collect();
+        // self::test::StaticFixtureTestFn(&[...], $fn_expr)
+        ecx.expr_call(span,
+                      ecx.expr_path(test_path("StaticFixtureTestFn")),
+                      vec![ecx.expr_vec_slice(span, fixture_exprs), fn_expr])
+    };
 
     // self::test::TestDescAndFn { ... }
     ecx.expr_struct(span,
@@ -0,0 +1,175 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Saved benchmark results, for `--save-baseline` and `--baseline`.
+//!
+//! A baseline records the median and the raw samples of every benchmark of
+//! a run. It is stored as JSON in `<dir>/<suite>/<name>.json`, where `<dir>`
+//! is `RUST_BENCH_BASELINE_DIR` if set and otherwise a `bench-baselines`
+//! directory next to the test executable.
+//!
+//! When comparing against a baseline, a benchmark is only reported as having
+//! regressed (or improved) if its median changed by more than
+//! `THRESHOLD_PCT` and a Mann-Whitney U test on the samples says the change
+//! is unlikely to be noise.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use json::{self, Json, ToJson};
+
+use stats;
+use super::{BenchSamples, TestDesc, suite_name};
+
+/// The change of the median, in percent, below which a benchmark is
+/// considered unchanged.
+pub const THRESHOLD_PCT: f64 = 5.0;
+
+/// The p-value below which a change is considered significant.
+pub const SIGNIFICANCE: f64 = 0.05;
+
+struct Entry {
+    median: f64,
+    samples: Vec<f64>,
+}
+
+pub struct Baseline {
+    benches: BTreeMap<String, Entry>,
+}
+
+/// How a benchmark compares to its result in a baseline.
+pub struct Comparison {
+    /// The change of the median relative to the baseline, in percent.
+    pub change_pct: f64,
+    /// The p-value of the Mann-Whitney U test on the two sets of samples.
+    pub p_value: f64,
+}
+
+impl Comparison {
+    fn is_significant(&self) -> bool {
+        self.p_value < SIGNIFICANCE && self.change_pct.abs() > THRESHOLD_PCT
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.is_significant() && self.change_pct > 0.0
+    }
+
+    pub fn is_improvement(&self) -> bool {
+        self.is_significant() && self.change_pct < 0.0
+    }
+}
+
+/// Returns the directory baselines are stored in: `RUST_BENCH_BASELINE_DIR`
+/// if set, and otherwise `bench-baselines` next to the test executable.
+pub fn default_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = env::var_os("RUST_BENCH_BASELINE_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let exe = env::current_exe()?;
+    Ok(match exe.parent() {
+        Some(parent) => parent.join("bench-baselines"),
+        None => PathBuf::from("bench-baselines"),
+    })
+}
+
+/// Returns the file the baseline called `name` is stored in, under `dir`.
+pub fn path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(suite_name()).join(format!("{}.json", name))
+}
+
+impl Baseline {
+    pub fn new() -> Baseline {
+        Baseline { benches: BTreeMap::new() }
+    }
+
+    pub fn load(dir: &Path, name: &str) -> io::Result<Baseline> {
+        let path = path(dir, name);
+        let mut contents = String::new();
+        File::open(&path).and_then(|mut f| f.read_to_string(&mut contents)).map_err(|e| {
+            io::Error::new(e.kind(),
+                           format!("failed to read baseline `{}` from {}: {}",
+                                   name,
+                                   path.display(),
+                                   e))
+        })?;
+        let invalid = || {
+            io::Error::new(io::ErrorKind::InvalidData,
+                           format!("baseline `{}` in {} is malformed", name, path.display()))
+        };
+
+        let json = json::from_str(&contents).map_err(|_| invalid())?;
+        let mut benches = BTreeMap::new();
+        for (bench, entry) in json.as_object().ok_or_else(&invalid)? {
+            let median = entry.find("median").and_then(|j| j.as_f64()).ok_or_else(&invalid)?;
+            let samples = entry.find("samples")
+                               .and_then(|j| j.as_array())
+                               .ok_or_else(&invalid)?
+                               .iter()
+                               .map(|j| j.as_f64())
+                               .collect::<Option<Vec<_>>>()
+                               .ok_or_else(&invalid)?;
+            benches.insert(bench.clone(), Entry { median: median, samples: samples });
+        }
+        Ok(Baseline { benches: benches })
+    }
+
+    /// Writes the baseline to disk, returning the path it was written to.
+    pub fn save(&self, dir: &Path, name: &str) -> io::Result<PathBuf> {
+        let path = path(dir, name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = File::create(&path)?;
+        writeln!(file, "{}", self.to_json())?;
+        Ok(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.benches.is_empty()
+    }
+
+    pub fn insert(&mut self, desc: &TestDesc, bs: &BenchSamples) {
+        self.benches.insert(desc.name.to_string(),
+                            Entry {
+                                median: bs.ns_iter_summ.median,
+                                samples: bs.ns_iter_samples.clone(),
+                            });
+    }
+
+    /// Compares a benchmark result with the baseline, if the baseline
+    /// contains that benchmark.
+    pub fn compare(&self, desc: &TestDesc, bs: &BenchSamples) -> Option<Comparison> {
+        let entry = match self.benches.get(&desc.name.to_string()) {
+            Some(entry) if entry.median > 0.0 => entry,
+            _ => return None,
+        };
+        Some(Comparison {
+            change_pct: (bs.ns_iter_summ.median - entry.median) / entry.median * 100.0,
+            p_value: stats::mann_whitney_u(&entry.samples, &bs.ns_iter_samples),
+        })
+    }
+}
+
+impl ToJson for Baseline {
+    fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        for (bench, entry) in &self.benches {
+            let mut e = BTreeMap::new();
+            e.insert("median".to_owned(), entry.median.to_json());
+            e.insert("samples".to_owned(), entry.samples.to_json());
+            obj.insert(bench.clone(), Json::Object(e));
+        }
+        Json::Object(obj)
+    }
+}
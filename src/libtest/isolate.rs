@@ -0,0 +1,205 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Running tests in their own process, for `--isolate`.
+//!
+//! The test binary is executed again with an exact filter for the test and
+//! `--format json`, and the result is read back from the JSON events it
+//! prints. A test which brings the process down without reporting a result
+//! (by aborting, crashing, or exiting early) is reported as failed.
+//!
+//! Only tests with a static function are found again in the child process,
+//! so tests created at runtime fail without being run, and `--isolate` can't
+//! be combined with `--bench`.
+
+use std::env;
+use std::io::prelude::*;
+use std::io;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use json::{self, Json};
+
+use super::{TestDesc, TestResult, TrOk, TrFailed, TrFailedMsg, TrIgnored, fmt_duration};
+
+/// Set in the environment of the child processes.
+pub const CHILD_ENV: &'static str = "__RUST_TEST_ISOLATED";
+
+/// The options of the parent test runner which are passed on to the child.
+#[derive(Clone, Copy)]
+pub struct IsolateOpts {
+    pub run_ignored: bool,
+    pub nocapture: bool,
+    pub test_timeout: Option<Duration>,
+}
+
+/// Runs the test described by `desc` in a new process, returning its result,
+/// execution time and captured output.
+pub fn run_test_in_process(desc: &TestDesc, opts: IsolateOpts) -> (TestResult, Duration, Vec<u8>) {
+    let start = Instant::now();
+    let mut child = match spawn(desc, opts) {
+        Ok(child) => child,
+        Err(e) => {
+            let msg = format!("failed to spawn test process: {}", e);
+            return (TrFailedMsg(msg), start.elapsed(), Vec::new());
+        }
+    };
+
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+    let status = wait_with_timeout(&mut child, opts.test_timeout);
+    let stdout = stdout.join().unwrap_or(Vec::new());
+    let stderr = stderr.join().unwrap_or(Vec::new());
+    let exec_time = start.elapsed();
+
+    // Anything the child printed which isn't one of its JSON events was
+    // written around the harness, e.g. by a crashing test or with
+    // `--nocapture`, and is kept as part of the test's output.
+    let mut output = Vec::new();
+    let mut reported = None;
+    for line in String::from_utf8_lossy(&stdout).lines() {
+        match json::from_str(line) {
+            Ok(ref event) if is_test_result(event) => reported = Some(parse_result(event)),
+            Ok(_) => {}
+            Err(_) => {
+                output.extend_from_slice(line.as_bytes());
+                output.push(b'\n');
+            }
+        }
+    }
+    if opts.nocapture {
+        io::stdout().write_all(&output).unwrap();
+        output.clear();
+    }
+    output.extend_from_slice(&stderr);
+
+    match (status, reported) {
+        (None, _) => {
+            let limit = opts.test_timeout.unwrap();
+            let msg = format!("test did not finish within {}, its process was killed",
+                              fmt_duration(limit));
+            (TrFailedMsg(msg), exec_time, output)
+        }
+        (Some(_), Some((result, exec_time, mut captured))) => {
+            captured.extend_from_slice(&output);
+            (result, exec_time, captured)
+        }
+        (Some(status), None) => {
+            let msg = match signal_name(&status) {
+                Some(signal) => format!("test process was terminated by signal {}", signal),
+                None => format!("test process exited with {} without reporting a result",
+                                status),
+            };
+            (TrFailedMsg(msg), exec_time, output)
+        }
+    }
+}
+
+fn spawn(desc: &TestDesc, opts: IsolateOpts) -> io::Result<Child> {
+    let mut cmd = Command::new(env::current_exe()?);
+    cmd.arg(desc.name.to_string())
+       .arg("--exact")
+       .arg("--test-threads").arg("1")
+       .arg("--format").arg("json")
+       .env(CHILD_ENV, "1")
+       .stdin(Stdio::null())
+       .stdout(Stdio::piped())
+       .stderr(if opts.nocapture { Stdio::inherit() } else { Stdio::piped() });
+    if opts.run_ignored {
+        cmd.arg("--ignored");
+    }
+    if opts.nocapture {
+        cmd.arg("--nocapture");
+    }
+    cmd.spawn()
+}
+
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>)
+                                                -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Waits for `child` to exit, killing it once `timeout` has passed. Returns
+/// `None` if it had to be killed.
+fn wait_with_timeout(child: &mut Child, timeout: Option<Duration>) -> Option<ExitStatus> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return Some(child.wait().unwrap()),
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return Some(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn field<'a>(event: &'a Json, name: &str) -> Option<&'a str> {
+    event.find(name).and_then(|j| j.as_string())
+}
+
+fn is_test_result(event: &Json) -> bool {
+    field(event, "type") == Some("test") &&
+        match field(event, "event") {
+            Some("ok") | Some("failed") | Some("ignored") => true,
+            _ => false,
+        }
+}
+
+fn parse_result(event: &Json) -> (TestResult, Duration, Vec<u8>) {
+    let result = match (field(event, "event"), field(event, "message")) {
+        (Some("ok"), _) => TrOk,
+        (Some("ignored"), _) => TrIgnored,
+        (_, Some(msg)) => TrFailedMsg(msg.to_owned()),
+        (_, None) => TrFailed,
+    };
+    let secs = event.find("exec_time").and_then(|j| j.as_f64()).unwrap_or(0.0);
+    let exec_time = Duration::new(secs as u64, (secs.fract() * 1_000_000_000.0) as u32);
+    let stdout = field(event, "stdout").map(|s| s.as_bytes().to_vec()).unwrap_or(Vec::new());
+    (result, exec_time, stdout)
+}
+
+#[cfg(unix)]
+fn signal_name(status: &ExitStatus) -> Option<String> {
+    use std::os::unix::process::ExitStatusExt;
+    use libc;
+
+    status.signal().map(|signal| {
+        match signal {
+            libc::SIGABRT => "SIGABRT".to_owned(),
+            libc::SIGBUS => "SIGBUS".to_owned(),
+            libc::SIGFPE => "SIGFPE".to_owned(),
+            libc::SIGILL => "SIGILL".to_owned(),
+            libc::SIGKILL => "SIGKILL".to_owned(),
+            libc::SIGSEGV => "SIGSEGV".to_owned(),
+            libc::SIGTERM => "SIGTERM".to_owned(),
+            libc::SIGTRAP => "SIGTRAP".to_owned(),
+            n => n.to_string(),
+        }
+    })
+}
+
+#[cfg(not(unix))]
+fn signal_name(_status: &ExitStatus) -> Option<String> {
+    None
+}
@@ -0,0 +1,527 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The events emitted by the console test runner with `--format json`.
+//!
+//! Every event is printed as a single JSON object on its own line. Each
+//! object has a `type` field (`suite`, `test`, `bench` or `metric`) and,
+//! except for benchmarks and metrics, an `event` field describing what
+//! happened. A failed test which is run again is reported with a `retry`
+//! event, followed by the events of its next run. Benchmarks compared with
+//! `--baseline` carry a `baseline` object with the change of the median in
+//! percent and its p-value.
+//!
+//! The format of the JSON output should be considered *unstable*.
+//!
+//! This module also holds the small JSON value type the events are built
+//! from, along with a parser for reading them back in `--isolate` and for
+//! loading baselines. `serialize::json` can't be used instead: libtest and
+//! its dependencies are built before the compiler and copied into the same
+//! sysroot as the compiler crates, which build `serialize` themselves. The
+//! sysroot would then hold two `serialize` crates, and `extern crate
+//! serialize` outside of Cargo, as in the fulldeps tests, would be
+//! ambiguous.
+
+use std::char;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::Chars;
+use std::iter::Peekable;
+use std::time::Duration;
+
+use baseline::Comparison;
+use super::{TestDesc, TestResult, MetricMap, TrOk, TrFailed, TrFailedMsg, TrIgnored,
+            TrMetrics, TrBench};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Json {
+    Null,
+    Boolean(bool),
+    U64(u64),
+    F64(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Object),
+}
+
+pub type Object = BTreeMap<String, Json>;
+
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+impl Json {
+    /// Returns the value of the field `key`, if this is an object.
+    pub fn find(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref obj) => obj.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match *self {
+            Json::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::U64(n) => Some(n as f64),
+            Json::F64(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match *self {
+            Json::Array(ref array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Object> {
+        match *self {
+            Json::Object(ref obj) => Some(obj),
+            _ => None,
+        }
+    }
+}
+
+/// Writes `s` as a JSON string literal, quotes included.
+fn escape_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 || c == '\u{7f}' => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    /// Writes the value as compact JSON, on a single line.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => f.write_str("null"),
+            Json::Boolean(b) => write!(f, "{}", b),
+            Json::U64(n) => write!(f, "{}", n),
+            // JSON has no representation for NaN or the infinities.
+            Json::F64(n) if !n.is_finite() => f.write_str("null"),
+            Json::F64(n) if n.fract() == 0.0 => write!(f, "{}.0", n),
+            Json::F64(n) => write!(f, "{}", n),
+            Json::String(ref s) => escape_str(f, s),
+            Json::Array(ref array) => {
+                f.write_str("[")?;
+                for (i, elem) in array.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(ref obj) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in obj.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    escape_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Json {
+        Json::Boolean(*self)
+    }
+}
+
+impl ToJson for usize {
+    fn to_json(&self) -> Json {
+        Json::U64(*self as u64)
+    }
+}
+
+impl ToJson for u64 {
+    fn to_json(&self) -> Json {
+        Json::U64(*self)
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self) -> Json {
+        Json::F64(*self)
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Json {
+        Json::String(self.to_owned())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Json {
+        Json::String(self.clone())
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(|elem| elem.to_json()).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Json {
+        self[..].to_json()
+    }
+}
+
+/// Parses a single JSON value, which may be surrounded by whitespace.
+pub fn from_str(s: &str) -> Result<Json, ()> {
+    let mut parser = Parser { chars: s.chars().peekable() };
+    let value = parser.parse_value();
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None => value,
+        Some(_) => Err(()),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek().cloned() {
+            match c {
+                ' ' | '\t' | '\n' | '\r' => {
+                    self.chars.next();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&expected) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        word.chars().all(|c| self.chars.next() == Some(c))
+    }
+
+    fn parse_value(&mut self) -> Result<Json, ()> {
+        self.skip_whitespace();
+        match self.chars.peek().cloned() {
+            Some('n') if self.eat_word("null") => Ok(Json::Null),
+            Some('t') if self.eat_word("true") => Ok(Json::Boolean(true)),
+            Some('f') if self.eat_word("false") => Ok(Json::Boolean(false)),
+            Some('"') => self.parse_string().map(Json::String),
+            Some('[') => self.parse_array(),
+            Some('{') => self.parse_object(),
+            Some(c) if c == '-' || c.is_digit(10) => self.parse_number(),
+            _ => Err(()),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Json, ()> {
+        let mut s = String::new();
+        while let Some(c) = self.chars.peek().cloned() {
+            match c {
+                '0'...'9' | '-' | '+' | '.' | 'e' | 'E' => {
+                    s.push(c);
+                    self.chars.next();
+                }
+                _ => break,
+            }
+        }
+        match s.parse::<u64>() {
+            Ok(n) => Ok(Json::U64(n)),
+            Err(_) => s.parse::<f64>().map(Json::F64).map_err(|_| ()),
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ()> {
+        let mut n = 0;
+        for _ in 0..4 {
+            n = n * 16 + self.chars.next().and_then(|c| c.to_digit(16)).ok_or(())?;
+        }
+        Ok(n)
+    }
+
+    fn parse_string(&mut self) -> Result<String, ()> {
+        self.chars.next();
+        let mut s = String::new();
+        loop {
+            match self.chars.next().ok_or(())? {
+                '"' => return Ok(s),
+                '\\' => {
+                    let c = match self.chars.next().ok_or(())? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\x08',
+                        'f' => '\x0c',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let mut n = self.parse_hex4()?;
+                            // Characters outside the BMP are written as a
+                            // surrogate pair.
+                            if n >= 0xd800 && n < 0xdc00 {
+                                if !self.eat_word("\\u") {
+                                    return Err(());
+                                }
+                                let low = self.parse_hex4()?;
+                                if low < 0xdc00 || low >= 0xe000 {
+                                    return Err(());
+                                }
+                                n = 0x10000 + ((n - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            char::from_u32(n).ok_or(())?
+                        }
+                        _ => return Err(()),
+                    };
+                    s.push(c);
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, ()> {
+        self.chars.next();
+        let mut array = Vec::new();
+        if self.eat(']') {
+            return Ok(Json::Array(array));
+        }
+        loop {
+            array.push(self.parse_value()?);
+            if self.eat(']') {
+                return Ok(Json::Array(array));
+            }
+            if !self.eat(',') {
+                return Err(());
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, ()> {
+        self.chars.next();
+        let mut obj = BTreeMap::new();
+        if self.eat('}') {
+            return Ok(Json::Object(obj));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() != Some(&'"') {
+                return Err(());
+            }
+            let key = self.parse_string()?;
+            if !self.eat(':') {
+                return Err(());
+            }
+            let value = self.parse_value()?;
+            obj.insert(key, value);
+            if self.eat('}') {
+                return Ok(Json::Object(obj));
+            }
+            if !self.eat(',') {
+                return Err(());
+            }
+        }
+    }
+}
+
+pub struct SuiteStarted {
+    pub test_count: usize,
+}
+
+pub struct SuiteFinished {
+    pub success: bool,
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub measured: usize,
+    pub filtered_out: usize,
+    pub flaky: usize,
+    pub shuffle_seed: Option<u64>,
+}
+
+pub struct TestStarted<'a> {
+    pub desc: &'a TestDesc,
+}
+
+pub struct TestTimeout<'a> {
+    pub desc: &'a TestDesc,
+}
+
+pub struct TestRetry<'a> {
+    pub desc: &'a TestDesc,
+    pub result: &'a TestResult,
+    pub exec_time: Duration,
+    pub stdout: &'a [u8],
+}
+
+pub struct TestFinished<'a> {
+    pub desc: &'a TestDesc,
+    pub result: &'a TestResult,
+    pub exec_time: Duration,
+    pub stdout: &'a [u8],
+    pub comparison: Option<Comparison>,
+}
+
+fn event(ty: &str, event: &str) -> Object {
+    let mut obj = BTreeMap::new();
+    obj.insert("type".to_owned(), ty.to_json());
+    obj.insert("event".to_owned(), event.to_json());
+    obj
+}
+
+fn secs(dur: Duration) -> f64 {
+    dur.as_secs() as f64 + dur.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+impl ToJson for SuiteStarted {
+    fn to_json(&self) -> Json {
+        let mut obj = event("suite", "started");
+        obj.insert("test_count".to_owned(), self.test_count.to_json());
+        Json::Object(obj)
+    }
+}
+
+impl ToJson for SuiteFinished {
+    fn to_json(&self) -> Json {
+        let mut obj = event("suite", if self.success { "ok" } else { "failed" });
+        obj.insert("passed".to_owned(), self.passed.to_json());
+        obj.insert("failed".to_owned(), self.failed.to_json());
+        obj.insert("ignored".to_owned(), self.ignored.to_json());
+        obj.insert("measured".to_owned(), self.measured.to_json());
+        obj.insert("filtered_out".to_owned(), self.filtered_out.to_json());
+        obj.insert("flaky".to_owned(), self.flaky.to_json());
+        if let Some(seed) = self.shuffle_seed {
+            obj.insert("shuffle_seed".to_owned(), seed.to_json());
+        }
+        Json::Object(obj)
+    }
+}
+
+impl<'a> ToJson for TestStarted<'a> {
+    fn to_json(&self) -> Json {
+        let mut obj = event("test", "started");
+        obj.insert("name".to_owned(), self.desc.name.to_string().to_json());
+        Json::Object(obj)
+    }
+}
+
+impl<'a> ToJson for TestTimeout<'a> {
+    fn to_json(&self) -> Json {
+        let mut obj = event("test", "timeout");
+        obj.insert("name".to_owned(), self.desc.name.to_string().to_json());
+        Json::Object(obj)
+    }
+}
+
+impl<'a> ToJson for TestRetry<'a> {
+    fn to_json(&self) -> Json {
+        let mut obj = event("test", "retry");
+        obj.insert("name".to_owned(), self.desc.name.to_string().to_json());
+        if let TrFailedMsg(ref msg) = *self.result {
+            obj.insert("message".to_owned(), msg.to_json());
+        }
+        obj.insert("exec_time".to_owned(), secs(self.exec_time).to_json());
+        if !self.stdout.is_empty() {
+            obj.insert("stdout".to_owned(),
+                       String::from_utf8_lossy(self.stdout).to_json());
+        }
+        Json::Object(obj)
+    }
+}
+
+impl<'a> ToJson for TestFinished<'a> {
+    fn to_json(&self) -> Json {
+        let mut obj = match *self.result {
+            TrOk => event("test", "ok"),
+            TrFailed => event("test", "failed"),
+            TrFailedMsg(ref msg) => {
+                let mut obj = event("test", "failed");
+                obj.insert("message".to_owned(), msg.to_json());
+                obj
+            }
+            TrIgnored => event("test", "ignored"),
+            TrMetrics(ref mm) => {
+                let mut obj = BTreeMap::new();
+                obj.insert("type".to_owned(), "metric".to_json());
+                obj.insert("metrics".to_owned(), mm.to_json());
+                obj
+            }
+            TrBench(ref bs) => {
+                let summ = &bs.ns_iter_summ;
+                let mut obj = BTreeMap::new();
+                obj.insert("type".to_owned(), "bench".to_json());
+                obj.insert("median".to_owned(), summ.median.to_json());
+                obj.insert("deviation".to_owned(), (summ.max - summ.min).to_json());
+                obj.insert("mib_per_second".to_owned(), bs.mb_s.to_json());
+                obj
+            }
+        };
+        obj.insert("name".to_owned(), self.desc.name.to_string().to_json());
+        obj.insert("exec_time".to_owned(), secs(self.exec_time).to_json());
+        if !self.stdout.is_empty() {
+            obj.insert("stdout".to_owned(),
+                       String::from_utf8_lossy(self.stdout).to_json());
+        }
+        if let Some(ref cmp) = self.comparison {
+            let mut baseline = BTreeMap::new();
+            baseline.insert("change_pct".to_owned(), cmp.change_pct.to_json());
+            baseline.insert("p_value".to_owned(), cmp.p_value.to_json());
+            baseline.insert("regression".to_owned(), cmp.is_regression().to_json());
+            obj.insert("baseline".to_owned(), Json::Object(baseline));
+        }
+        Json::Object(obj)
+    }
+}
+
+impl ToJson for MetricMap {
+    fn to_json(&self) -> Json {
+        let MetricMap(ref mm) = *self;
+        let mut obj = BTreeMap::new();
+        for (name, metric) in mm {
+            let mut m = BTreeMap::new();
+            m.insert("value".to_owned(), metric.value.to_json());
+            m.insert("noise".to_owned(), metric.noise.to_json());
+            obj.insert(name.clone(), Json::Object(m));
+        }
+        Json::Object(obj)
+    }
+}
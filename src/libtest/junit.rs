@@ -0,0 +1,164 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A JUnit-compatible XML report of a test run, written with `--junit`.
+//!
+//! Results are collected as they come in and the whole document is written
+//! out once the run has finished, since the `<testsuite>` element carries
+//! the totals as attributes. Ignored tests are reported as skipped, and
+//! benchmarks and metrics as passing test cases.
+
+use std::io::prelude::*;
+use std::io;
+use std::time::{Duration, Instant};
+
+use super::{TestDesc, TestResult, TrOk, TrFailed, TrFailedMsg, TrIgnored, TrMetrics, TrBench,
+            fmt_bench_samples};
+
+struct TestCase {
+    name: String,
+    result: TestResult,
+    exec_time: Duration,
+    stdout: Vec<u8>,
+}
+
+pub struct JunitReport<W> {
+    out: W,
+    suite: String,
+    start: Instant,
+    cases: Vec<TestCase>,
+}
+
+impl<W: Write> JunitReport<W> {
+    pub fn new(out: W, suite: String) -> JunitReport<W> {
+        JunitReport {
+            out: out,
+            suite: suite,
+            start: Instant::now(),
+            cases: Vec::new(),
+        }
+    }
+
+    pub fn add_result(&mut self,
+                      desc: &TestDesc,
+                      result: &TestResult,
+                      exec_time: Duration,
+                      stdout: &[u8]) {
+        self.cases.push(TestCase {
+            name: desc.name.to_string(),
+            result: result.clone(),
+            exec_time: exec_time,
+            stdout: stdout.to_vec(),
+        });
+    }
+
+    /// Writes out the report, returning the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let failures = self.cases.iter().filter(|c| match c.result {
+            TrFailed | TrFailedMsg(_) => true,
+            _ => false,
+        }).count();
+        let skipped = self.cases.iter().filter(|c| c.result == TrIgnored).count();
+
+        writeln!(self.out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(self.out, "<testsuites>")?;
+        writeln!(self.out,
+                 concat!(r#"<testsuite name="{}" errors="0" failures="{}" skipped="{}" "#,
+                         r#"tests="{}" time="{}">"#),
+                 escape(&self.suite),
+                 failures,
+                 skipped,
+                 self.cases.len(),
+                 secs(self.start.elapsed()))?;
+
+        for case in &self.cases {
+            // Test names are module paths, so split off the last component
+            // as the test case name and use the rest as the class name.
+            let (classname, name) = match case.name.rfind("::") {
+                Some(i) => (&case.name[..i], &case.name[i + 2..]),
+                None => (&self.suite[..], &case.name[..]),
+            };
+            write!(self.out,
+                   r#"<testcase classname="{}" name="{}" time="{}""#,
+                   escape(classname),
+                   escape(name),
+                   secs(case.exec_time))?;
+
+            let output = escape(&String::from_utf8_lossy(&case.stdout));
+            match case.result {
+                TrOk if output.is_empty() => {
+                    writeln!(self.out, "/>")?;
+                    continue
+                }
+                TrOk => {
+                    writeln!(self.out, ">")?;
+                    writeln!(self.out, "<system-out>{}</system-out>", output)?;
+                }
+                TrFailed => {
+                    writeln!(self.out, ">")?;
+                    writeln!(self.out, r#"<failure message="test failed">{}</failure>"#, output)?;
+                }
+                TrFailedMsg(ref msg) => {
+                    writeln!(self.out, ">")?;
+                    writeln!(self.out,
+                             r#"<failure message="{}">{}</failure>"#,
+                             escape(msg),
+                             output)?;
+                }
+                TrIgnored => {
+                    writeln!(self.out, ">")?;
+                    writeln!(self.out, "<skipped/>")?;
+                }
+                TrMetrics(ref mm) => {
+                    writeln!(self.out, ">")?;
+                    writeln!(self.out, "<system-out>{}</system-out>", escape(&mm.fmt_metrics()))?;
+                }
+                TrBench(ref bs) => {
+                    writeln!(self.out, ">")?;
+                    writeln!(self.out,
+                             "<system-out>{}</system-out>",
+                             escape(&fmt_bench_samples(bs)))?;
+                }
+            }
+            writeln!(self.out, "</testcase>")?;
+        }
+
+        writeln!(self.out, "</testsuite>")?;
+        writeln!(self.out, "</testsuites>")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn secs(dur: Duration) -> String {
+    format!("{}.{:03}", dur.as_secs(), dur.subsec_nanos() / 1_000_000)
+}
+
+/// Escapes `s` for use in XML text and attribute values.
+///
+/// Characters which may not appear in an XML document at all (most control
+/// characters, such as the escape sequences of colored output) are replaced
+/// with U+FFFD.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
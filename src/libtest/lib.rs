@@ -39,11 +39,13 @@
 #![feature(set_stdio)]
 #![feature(staged_api)]
 #![feature(panic_unwind)]
+#![feature(rand)]
 
 extern crate getopts;
 extern crate term;
 extern crate libc;
 extern crate panic_unwind;
+extern crate rand;
 
 pub use self::TestFn::*;
 pub use self::ColorConfig::*;
@@ -53,7 +55,7 @@ use self::TestEvent::*;
 use self::NamePadding::*;
 use self::OutputLocation::*;
 
-use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 use std::any::Any;
 use std::cmp;
 use std::collections::BTreeMap;
@@ -67,7 +69,10 @@ use std::path::PathBuf;
 use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Instant, Duration};
+use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+use json::ToJson;
 
 const TEST_WARN_TIMEOUT_S: u64 = 60;
 
@@ -76,10 +81,15 @@ pub mod test {
     pub use {Bencher, TestName, TestResult, TestDesc, TestDescAndFn, TestOpts, TrFailed,
              TrFailedMsg, TrIgnored, TrOk, Metric, MetricMap, StaticTestFn, StaticTestName,
              DynTestName, DynTestFn, run_test, test_main, test_main_static, filter_tests,
-             parse_opts, StaticBenchFn, ShouldPanic, Options};
+             parse_opts, StaticBenchFn, StaticFixtureTestFn, Fixture, ShouldPanic, Options,
+             OutputFormat};
 }
 
 pub mod stats;
+mod baseline;
+mod isolate;
+mod json;
+mod junit;
 
 // The name of a test. By convention this follows the rules for rust
 // paths; i.e. it should be a series of identifiers separated by double
@@ -126,6 +136,14 @@ impl TestDesc {
     }
 }
 
+/// The `#[test_setup]` and `#[test_teardown]` functions of a module, which
+/// are run around each of the tests in it.
+#[derive(Clone, Copy)]
+pub struct Fixture {
+    pub setup: Option<fn()>,
+    pub teardown: Option<fn()>,
+}
+
 /// Represents a benchmark function.
 pub trait TDynBenchFn: Send {
     fn run(&self, harness: &mut Bencher);
@@ -147,6 +165,7 @@ impl<T, F: FnOnce(T) + Send + 'static> FnBox<T> for F {
 // to support isolation of tests into threads.
 pub enum TestFn {
     StaticTestFn(fn()),
+    StaticFixtureTestFn(&'static [Fixture], fn()),
     StaticBenchFn(fn(&mut Bencher)),
     StaticMetricFn(fn(&mut MetricMap)),
     DynTestFn(Box<FnBox<()>>),
@@ -158,6 +177,7 @@ impl TestFn {
     fn padding(&self) -> NamePadding {
         match *self {
             StaticTestFn(..) => PadNone,
+            StaticFixtureTestFn(..) => PadNone,
             StaticBenchFn(..) => PadOnRight,
             StaticMetricFn(..) => PadOnRight,
             DynTestFn(..) => PadNone,
@@ -171,6 +191,7 @@ impl fmt::Debug for TestFn {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(match *self {
             StaticTestFn(..) => "StaticTestFn(..)",
+            StaticFixtureTestFn(..) => "StaticFixtureTestFn(..)",
             StaticBenchFn(..) => "StaticBenchFn(..)",
             StaticMetricFn(..) => "StaticMetricFn(..)",
             DynTestFn(..) => "DynTestFn(..)",
@@ -189,6 +210,7 @@ impl fmt::Debug for TestFn {
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
 }
 
@@ -212,6 +234,8 @@ pub struct TestDesc {
     pub name: TestName,
     pub ignore: bool,
     pub should_panic: ShouldPanic,
+    /// How often to run the test again if it fails, overriding `--retries`.
+    pub retries: Option<usize>,
 }
 
 #[derive(Clone)]
@@ -311,6 +335,12 @@ pub fn test_main_static(tests: &[TestDescAndFn]) {
                                            desc: t.desc.clone(),
                                        }
                                    }
+                                   StaticFixtureTestFn(fixtures, f) => {
+                                       TestDescAndFn {
+                                           testfn: StaticFixtureTestFn(fixtures, f),
+                                           desc: t.desc.clone(),
+                                       }
+                                   }
                                    StaticBenchFn(f) => {
                                        TestDescAndFn {
                                            testfn: StaticBenchFn(f),
@@ -331,6 +361,17 @@ pub enum ColorConfig {
     NeverColor,
 }
 
+/// How the console test runner reports its progress.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One line per test (the default).
+    Pretty,
+    /// One character per test.
+    Terse,
+    /// One JSON object per test event, for consumption by other tools.
+    Json,
+}
+
 #[derive(Debug)]
 pub struct TestOpts {
     pub list: bool,
@@ -340,15 +381,43 @@ pub struct TestOpts {
     pub run_tests: bool,
     pub bench_benchmarks: bool,
     pub logfile: Option<PathBuf>,
+    pub junit: Option<PathBuf>,
     pub nocapture: bool,
     pub color: ColorConfig,
+    /// Display one character per test; the same as a `format` of
+    /// `OutputFormat::Terse`, which takes precedence over it when it isn't
+    /// `OutputFormat::Pretty`.
     pub quiet: bool,
+    pub format: OutputFormat,
     pub test_threads: Option<usize>,
+    pub report_time: Option<usize>,
+    pub test_timeout: Option<Duration>,
+    pub isolate: bool,
+    pub shuffle: bool,
+    /// The seed of the test order permutation when `shuffle` is set. If this
+    /// is `None` a seed is picked by `run_tests` and can't be reported.
+    pub shuffle_seed: Option<u64>,
+    pub save_baseline: Option<String>,
+    pub baseline: Option<String>,
+    /// Only run the tests of one shard, given as `(index, count)` with a
+    /// zero-based `index`. Tests are assigned to shards by their name.
+    pub shard: Option<(usize, usize)>,
+    /// How often to run a failed test again before reporting it as failed.
+    pub retries: usize,
     pub skip: Vec<String>,
     pub options: Options,
 }
 
 impl TestOpts {
+    /// The format the console test runner reports its progress in, given
+    /// both `format` and `quiet`.
+    pub fn output_format(&self) -> OutputFormat {
+        match self.format {
+            OutputFormat::Pretty if self.quiet => OutputFormat::Terse,
+            format => format,
+        }
+    }
+
     #[cfg(test)]
     fn new() -> TestOpts {
         TestOpts {
@@ -359,10 +428,21 @@ impl TestOpts {
             run_tests: false,
             bench_benchmarks: false,
             logfile: None,
+            junit: None,
             nocapture: false,
             color: AutoColor,
             quiet: false,
+            format: OutputFormat::Pretty,
             test_threads: None,
+            report_time: None,
+            test_timeout: None,
+            isolate: false,
+            shuffle: false,
+            shuffle_seed: None,
+            save_baseline: None,
+            baseline: None,
+            shard: None,
+            retries: 0,
             skip: vec![],
             options: Options::new(),
         }
@@ -381,10 +461,29 @@ fn optgroups() -> Vec<getopts::OptGroup> {
       getopts::optflag("h", "help", "Display this message (longer with --help)"),
       getopts::optopt("", "logfile", "Write logs to the specified file instead \
                           of stdout", "PATH"),
+      getopts::optopt("", "junit", "Write a JUnit XML report of the test run to the \
+                                    specified file", "PATH"),
       getopts::optflag("", "nocapture", "don't capture stdout/stderr of each \
                                          task, allow printing directly"),
       getopts::optopt("", "test-threads", "Number of threads used for running tests \
                                            in parallel", "n_threads"),
+      getopts::optflagopt("", "report-time", "Show the execution time of each test and list \
+                                              the N slowest tests (10 by default) at the end \
+                                              of the run", "N"),
+      getopts::optopt("", "test-timeout", "Fail tests which run for longer than the given \
+                                           number of seconds", "SECONDS"),
+      getopts::optflag("", "isolate", "Run each test in its own process"),
+      getopts::optflag("", "shuffle", "Run tests in a random order"),
+      getopts::optopt("", "shuffle-seed", "Run tests in the random order given by SEED \
+                                           (implies --shuffle)", "SEED"),
+      getopts::optopt("", "save-baseline", "Save the benchmark results as a baseline with \
+                                            the given name", "NAME"),
+      getopts::optopt("", "baseline", "Compare the benchmark results with the baseline \
+                                       of the given name", "NAME"),
+      getopts::optopt("", "retries", "Run failed tests up to N more times, reporting tests \
+                                      which pass on a retry as flaky", "N"),
+      getopts::optopt("", "shard", "Split the tests into COUNT shards and only run the \
+                                    INDEX-th one (counting from 1)", "INDEX/COUNT"),
       getopts::optmulti("", "skip", "Skip tests whose names contain FILTER (this flag can \
                                      be used multiple times)","FILTER"),
       getopts::optflag("q", "quiet", "Display one character per test instead of one line"),
@@ -392,7 +491,11 @@ fn optgroups() -> Vec<getopts::OptGroup> {
       getopts::optopt("", "color", "Configure coloring of output:
             auto   = colorize if stdout is a tty and tests are run on serially (default);
             always = always colorize output;
-            never  = never colorize output;", "auto|always|never")]
+            never  = never colorize output;", "auto|always|never"),
+      getopts::optopt("", "format", "Configure formatting of output:
+            pretty = print verbose output (default);
+            terse  = display one character per test;
+            json   = output a json document per test event;", "pretty|terse|json")]
 }
 
 fn usage(binary: &str) {
@@ -410,6 +513,16 @@ All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
 
+Benchmark results saved with --save-baseline are stored in the directory given
+by the RUST_BENCH_BASELINE_DIR environment variable, or in a bench-baselines
+directory next to the test executable. When comparing with --baseline, changes
+of more than 5% which are statistically significant are reported as
+regressions or improvements.
+
+Failed tests are run again with --retries or #[test(retries = N)]. Only tests
+with a static function can be run again, others are reported as failed with a
+note that they were not retried.
+
 Test Attributes:
 
     #[test]        - Indicates a function is a test to be run. This function
@@ -454,9 +567,17 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
     let logfile = matches.opt_str("logfile");
     let logfile = logfile.map(|s| PathBuf::from(&s));
 
+    let junit = matches.opt_str("junit").map(PathBuf::from);
+
     let bench_benchmarks = matches.opt_present("bench");
     let run_tests = !bench_benchmarks || matches.opt_present("test");
 
+    let isolate = matches.opt_present("isolate");
+    if isolate && bench_benchmarks {
+        return Some(Err(format!("--isolate can't be used with --bench, benchmarks are \
+                                 measured in the process of the test runner")));
+    }
+
     let mut nocapture = matches.opt_present("nocapture");
     if !nocapture {
         nocapture = match env::var("RUST_TEST_NOCAPTURE") {
@@ -479,6 +600,81 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
             None,
     };
 
+    let report_time = if matches.opt_present("report-time") {
+        match matches.opt_str("report-time") {
+            Some(n_str) =>
+                match n_str.parse::<usize>() {
+                    Ok(n) => Some(n),
+                    Err(e) =>
+                        return Some(Err(format!("argument for --report-time must be a number \
+                                                 (error: {})", e)))
+                },
+            None => Some(10),
+        }
+    } else {
+        None
+    };
+
+    let test_timeout = match matches.opt_str("test-timeout") {
+        Some(secs_str) =>
+            match secs_str.parse::<u64>() {
+                Ok(0) =>
+                    return Some(Err(format!("argument for --test-timeout must not be 0"))),
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(e) =>
+                    return Some(Err(format!("argument for --test-timeout must be a number > 0 \
+                                             (error: {})", e)))
+            },
+        None =>
+            None,
+    };
+
+    let shuffle_seed = match matches.opt_str("shuffle-seed") {
+        Some(seed_str) =>
+            match seed_str.parse::<u64>() {
+                Ok(seed) => Some(seed),
+                Err(e) =>
+                    return Some(Err(format!("argument for --shuffle-seed must be a number \
+                                             (error: {})", e)))
+            },
+        // Pick the seed up front so that it can be printed in the summary.
+        None if matches.opt_present("shuffle") => Some(random_shuffle_seed()),
+        None => None,
+    };
+
+    let save_baseline = matches.opt_str("save-baseline");
+    let baseline = matches.opt_str("baseline");
+    for name in save_baseline.iter().chain(baseline.iter()) {
+        if name.is_empty() || name.starts_with('.') || name.contains(|c| c == '/' || c == '\\') {
+            return Some(Err(format!("invalid baseline name `{}`", name)));
+        }
+    }
+
+    let retries = match matches.opt_str("retries") {
+        Some(n_str) =>
+            match n_str.parse::<usize>() {
+                Ok(n) => n,
+                Err(e) =>
+                    return Some(Err(format!("argument for --retries must be a number \
+                                             (error: {})", e)))
+            },
+        None =>
+            0,
+    };
+
+    let shard = match matches.opt_str("shard") {
+        Some(shard_str) =>
+            match parse_shard(&shard_str) {
+                Some(shard) => Some(shard),
+                None =>
+                    return Some(Err(format!("argument for --shard must be of the form \
+                                             INDEX/COUNT with 1 <= INDEX <= COUNT (was {})",
+                                            shard_str)))
+            },
+        None =>
+            None,
+    };
+
     let color = match matches.opt_str("color").as_ref().map(|s| &**s) {
         Some("auto") | None => AutoColor,
         Some("always") => AlwaysColor,
@@ -491,6 +687,18 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         }
     };
 
+    let format = match matches.opt_str("format").as_ref().map(|s| &**s) {
+        Some("pretty") | None => OutputFormat::Pretty,
+        Some("terse") => OutputFormat::Terse,
+        Some("json") => OutputFormat::Json,
+
+        Some(v) => {
+            return Some(Err(format!("argument for --format must be pretty, terse, or json (was \
+                                     {})",
+                                    v)))
+        }
+    };
+
     let test_opts = TestOpts {
         list: list,
         filter: filter,
@@ -499,10 +707,21 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         run_tests: run_tests,
         bench_benchmarks: bench_benchmarks,
         logfile: logfile,
+        junit: junit,
         nocapture: nocapture,
         color: color,
         quiet: quiet,
+        format: format,
         test_threads: test_threads,
+        report_time: report_time,
+        test_timeout: test_timeout,
+        isolate: isolate,
+        shuffle: shuffle_seed.is_some(),
+        shuffle_seed: shuffle_seed,
+        save_baseline: save_baseline,
+        baseline: baseline,
+        shard: shard,
+        retries: retries,
         skip: matches.opt_strs("skip"),
         options: Options::new(),
     };
@@ -510,9 +729,22 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
     Some(Ok(test_opts))
 }
 
+// Parses a one-based `INDEX/COUNT` shard specification into a zero-based
+// `(index, count)` pair.
+fn parse_shard(s: &str) -> Option<(usize, usize)> {
+    let mut parts = s.splitn(2, '/');
+    let index = parts.next().and_then(|n| n.parse::<usize>().ok());
+    let count = parts.next().and_then(|n| n.parse::<usize>().ok());
+    match (index, count) {
+        (Some(index), Some(count)) if 1 <= index && index <= count => Some((index - 1, count)),
+        _ => None,
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct BenchSamples {
     ns_iter_summ: stats::Summary,
+    ns_iter_samples: Vec<f64>,
     mb_s: usize,
 }
 
@@ -535,9 +767,10 @@ enum OutputLocation<T> {
 
 struct ConsoleTestState<T> {
     log_out: Option<File>,
+    junit: Option<junit::JunitReport<File>>,
     out: OutputLocation<T>,
     use_color: bool,
-    quiet: bool,
+    format: OutputFormat,
     total: usize,
     passed: usize,
     failed: usize,
@@ -547,6 +780,14 @@ struct ConsoleTestState<T> {
     metrics: MetricMap,
     failures: Vec<(TestDesc, Vec<u8>)>,
     not_failures: Vec<(TestDesc, Vec<u8>)>,
+    exec_times: Vec<(TestDesc, Duration)>,
+    report_time: Option<usize>,
+    shuffle_seed: Option<u64>,
+    baseline: Option<(String, baseline::Baseline)>,
+    save_baseline: Option<(String, baseline::Baseline)>,
+    regressions: Vec<(TestDesc, f64)>,
+    retried: Vec<TestDesc>, // one entry for every failed run which was retried
+    flaky: Vec<(TestDesc, usize)>,
     max_name_len: usize, // number of columns to fill when aligning names
     options: Options,
 }
@@ -557,6 +798,17 @@ impl<T: Write> ConsoleTestState<T> {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let junit = match opts.junit {
+            Some(ref path) => Some(junit::JunitReport::new(File::create(path)?, suite_name())),
+            None => None,
+        };
+        let baseline = match opts.baseline {
+            Some(ref name) => {
+                let dir = baseline::default_dir()?;
+                Some((name.clone(), baseline::Baseline::load(&dir, name)?))
+            }
+            None => None,
+        };
         let out = match term::stdout() {
             None => Raw(io::stdout()),
             Some(t) => Pretty(t),
@@ -565,8 +817,9 @@ impl<T: Write> ConsoleTestState<T> {
         Ok(ConsoleTestState {
             out: out,
             log_out: log_out,
+            junit: junit,
             use_color: use_color(opts),
-            quiet: opts.quiet,
+            format: opts.output_format(),
             total: 0,
             passed: 0,
             failed: 0,
@@ -576,21 +829,37 @@ impl<T: Write> ConsoleTestState<T> {
             metrics: MetricMap::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
+            exec_times: Vec::new(),
+            report_time: opts.report_time,
+            shuffle_seed: if opts.shuffle { opts.shuffle_seed } else { None },
+            baseline: baseline,
+            save_baseline: opts.save_baseline.clone().map(|name| (name, baseline::Baseline::new())),
+            regressions: Vec::new(),
+            retried: Vec::new(),
+            flaky: Vec::new(),
             max_name_len: 0,
             options: opts.options,
         })
     }
 
-    pub fn write_ok(&mut self) -> io::Result<()> {
-        self.write_short_result("ok", ".", term::color::GREEN)
+    pub fn write_ok(&mut self, exec_time: Duration) -> io::Result<()> {
+        self.write_short_result("ok", ".", term::color::GREEN, Some(exec_time))
+    }
+
+    pub fn write_failed(&mut self, exec_time: Duration) -> io::Result<()> {
+        self.write_short_result("FAILED", "F", term::color::RED, Some(exec_time))
     }
 
-    pub fn write_failed(&mut self) -> io::Result<()> {
-        self.write_short_result("FAILED", "F", term::color::RED)
+    pub fn write_flaky(&mut self, exec_time: Duration) -> io::Result<()> {
+        self.write_short_result("flaky", "f", term::color::YELLOW, Some(exec_time))
+    }
+
+    pub fn write_retrying(&mut self, exec_time: Duration) -> io::Result<()> {
+        self.write_short_result("FAILED (retrying)", "r", term::color::YELLOW, Some(exec_time))
     }
 
     pub fn write_ignored(&mut self) -> io::Result<()> {
-        self.write_short_result("ignored", "i", term::color::YELLOW)
+        self.write_short_result("ignored", "i", term::color::YELLOW, None)
     }
 
     pub fn write_metric(&mut self) -> io::Result<()> {
@@ -601,12 +870,19 @@ impl<T: Write> ConsoleTestState<T> {
         self.write_pretty("bench", term::color::CYAN)
     }
 
-    pub fn write_short_result(&mut self, verbose: &str, quiet: &str, color: term::color::Color)
+    pub fn write_short_result(&mut self,
+                              verbose: &str,
+                              quiet: &str,
+                              color: term::color::Color,
+                              exec_time: Option<Duration>)
                               -> io::Result<()> {
-        if self.quiet {
+        if self.format == OutputFormat::Terse {
             self.write_pretty(quiet, color)
         } else {
             self.write_pretty(verbose, color)?;
+            if let (Some(_), Some(exec_time)) = (self.report_time, exec_time) {
+                self.write_plain(&format!(" <{}>", fmt_duration(exec_time)))?;
+            }
             self.write_plain("\n")
         }
     }
@@ -644,8 +920,15 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
+    pub fn write_json<J: ToJson>(&mut self, event: &J) -> io::Result<()> {
+        self.write_plain(&format!("{}\n", event.to_json()))
+    }
+
     pub fn write_run_start(&mut self, len: usize) -> io::Result<()> {
         self.total = len;
+        if self.format == OutputFormat::Json {
+            return self.write_json(&json::SuiteStarted { test_count: len });
+        }
         let noun = if len != 1 {
             "tests"
         } else {
@@ -655,7 +938,9 @@ impl<T: Write> ConsoleTestState<T> {
     }
 
     pub fn write_test_start(&mut self, test: &TestDesc, align: NamePadding) -> io::Result<()> {
-        if self.quiet && align != PadOnRight {
+        if self.format == OutputFormat::Json {
+            self.write_json(&json::TestStarted { desc: test })
+        } else if self.format == OutputFormat::Terse && align != PadOnRight {
             Ok(())
         } else {
             let name = test.padded_name(self.max_name_len, align);
@@ -663,10 +948,29 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
-    pub fn write_result(&mut self, result: &TestResult) -> io::Result<()> {
+    pub fn write_result(&mut self,
+                        test: &TestDesc,
+                        result: &TestResult,
+                        exec_time: Duration,
+                        stdout: &[u8])
+                        -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            let comparison = match *result {
+                TrBench(ref bs) => self.compare_with_baseline(test, bs),
+                _ => None,
+            };
+            return self.write_json(&json::TestFinished {
+                desc: test,
+                result: result,
+                exec_time: exec_time,
+                stdout: stdout,
+                comparison: comparison,
+            });
+        }
         match *result {
-            TrOk => self.write_ok(),
-            TrFailed | TrFailedMsg(_) => self.write_failed(),
+            TrOk if self.failed_attempts(test) > 0 => self.write_flaky(exec_time),
+            TrOk => self.write_ok(exec_time),
+            TrFailed | TrFailedMsg(_) => self.write_failed(exec_time),
             TrIgnored => self.write_ignored(),
             TrMetrics(ref mm) => {
                 self.write_metric()?;
@@ -674,12 +978,67 @@ impl<T: Write> ConsoleTestState<T> {
             }
             TrBench(ref bs) => {
                 self.write_bench()?;
-                self.write_plain(&format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(cmp) = self.compare_with_baseline(test, bs) {
+                    self.write_plain(&format!(" change: {:+.2}% (p = {:.3})",
+                                              cmp.change_pct,
+                                              cmp.p_value))?;
+                    if cmp.is_regression() {
+                        self.write_plain(" ")?;
+                        self.write_pretty("regressed", term::color::RED)?;
+                    } else if cmp.is_improvement() {
+                        self.write_plain(" ")?;
+                        self.write_pretty("improved", term::color::GREEN)?;
+                    }
+                }
+                self.write_plain("\n")
             }
         }
     }
 
+    fn compare_with_baseline(&self, test: &TestDesc, bs: &BenchSamples)
+                             -> Option<baseline::Comparison> {
+        self.baseline.as_ref().and_then(|&(_, ref baseline)| baseline.compare(test, bs))
+    }
+
+    pub fn record_bench(&mut self, test: &TestDesc, bs: &BenchSamples) {
+        if let Some((_, ref mut baseline)) = self.save_baseline {
+            baseline.insert(test, bs);
+        }
+        if let Some(cmp) = self.compare_with_baseline(test, bs) {
+            if cmp.is_regression() {
+                self.regressions.push((test.clone(), cmp.change_pct));
+            }
+        }
+    }
+
+    pub fn write_retry(&mut self,
+                       test: &TestDesc,
+                       result: &TestResult,
+                       exec_time: Duration,
+                       stdout: &[u8])
+                       -> io::Result<()> {
+        self.retried.push(test.clone());
+        self.write_log(format!("retry {}\n", test.name))?;
+        if self.format == OutputFormat::Json {
+            return self.write_json(&json::TestRetry {
+                desc: test,
+                result: result,
+                exec_time: exec_time,
+                stdout: stdout,
+            });
+        }
+        self.write_retrying(exec_time)
+    }
+
+    fn failed_attempts(&self, test: &TestDesc) -> usize {
+        self.retried.iter().filter(|t| *t == test).count()
+    }
+
     pub fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            return self.write_json(&json::TestTimeout { desc: desc });
+        }
         self.write_plain(&format!("test {} has been running for over {} seconds\n",
                                   desc.name,
                                   TEST_WARN_TIMEOUT_S))
@@ -697,6 +1056,7 @@ impl<T: Write> ConsoleTestState<T> {
         self.write_log(
             format!("{} {}\n",
                     match *result {
+                        TrOk if self.failed_attempts(test) > 0 => "flaky".to_owned(),
                         TrOk => "ok".to_owned(),
                         TrFailed => "failed".to_owned(),
                         TrFailedMsg(ref msg) => format!("failed: {}", msg),
@@ -707,6 +1067,16 @@ impl<T: Write> ConsoleTestState<T> {
                     test.name))
     }
 
+    pub fn write_junit_result(&mut self,
+                              test: &TestDesc,
+                              result: &TestResult,
+                              exec_time: Duration,
+                              stdout: &[u8]) {
+        if let Some(ref mut junit) = self.junit {
+            junit.add_result(test, result, exec_time, stdout);
+        }
+    }
+
     pub fn write_failures(&mut self) -> io::Result<()> {
         self.write_plain("\nfailures:\n")?;
         let mut failures = Vec::new();
@@ -759,16 +1129,104 @@ impl<T: Write> ConsoleTestState<T> {
         Ok(())
     }
 
+    pub fn write_flaky_tests(&mut self) -> io::Result<()> {
+        let mut flaky = self.flaky.clone();
+        flaky.sort_by(|a, b| a.0.name.as_slice().cmp(b.0.name.as_slice()));
+
+        self.write_plain("\nflaky tests:\n")?;
+        for (desc, attempts) in flaky {
+            let runs = if attempts == 1 { "run" } else { "runs" };
+            self.write_plain(&format!("    {} (passed after {} failed {})\n",
+                                      desc.name,
+                                      attempts,
+                                      runs))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_slowest(&mut self, count: usize) -> io::Result<()> {
+        let mut exec_times = self.exec_times.clone();
+        exec_times.sort_by(|a, b| b.1.cmp(&a.1));
+        exec_times.truncate(count);
+
+        self.write_plain("\nslowest tests:\n")?;
+        for (desc, exec_time) in exec_times {
+            self.write_plain(&format!("    {:>10}  {}\n", fmt_duration(exec_time), desc.name))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_regressions(&mut self) -> io::Result<()> {
+        let name = match self.baseline {
+            Some((ref name, _)) => name.clone(),
+            None => return Ok(()),
+        };
+        let mut regressions = self.regressions.clone();
+        regressions.sort_by(|a, b| a.0.name.as_slice().cmp(b.0.name.as_slice()));
+
+        self.write_plain(&format!("\nbenchmarks regressed compared to baseline `{}`:\n", name))?;
+        for (desc, change_pct) in regressions {
+            self.write_plain(&format!("    {} ({:+.2}%)\n", desc.name, change_pct))?;
+        }
+        Ok(())
+    }
+
     pub fn write_run_finish(&mut self) -> io::Result<bool> {
         assert!(self.passed + self.failed + self.ignored + self.measured == self.total);
 
+        let success = self.failed == 0;
+        if let Some(junit) = self.junit.take() {
+            junit.finish()?;
+        }
+        let mut saved_baseline = None;
+        if let Some((name, baseline)) = self.save_baseline.take() {
+            if !baseline.is_empty() {
+                let dir = baseline::default_dir()?;
+                saved_baseline = Some((baseline.save(&dir, &name)?, name));
+            }
+        }
+
+        if self.format == OutputFormat::Json {
+            self.write_json(&json::SuiteFinished {
+                success: success,
+                passed: self.passed,
+                failed: self.failed,
+                ignored: self.ignored,
+                measured: self.measured,
+                filtered_out: self.filtered_out,
+                flaky: self.flaky.len(),
+                shuffle_seed: self.shuffle_seed,
+            })?;
+            return Ok(success);
+        }
+
         if self.options.display_output {
             self.write_outputs()?;
         }
-        let success = self.failed == 0;
         if !success {
             self.write_failures()?;
         }
+        if !self.flaky.is_empty() {
+            self.write_flaky_tests()?;
+        }
+        if let Some(count) = self.report_time {
+            if count > 0 && !self.exec_times.is_empty() {
+                self.write_slowest(count)?;
+            }
+        }
+        if !self.regressions.is_empty() {
+            self.write_regressions()?;
+        }
+        if let Some((path, name)) = saved_baseline {
+            self.write_plain(&format!("\nsaved benchmark results as baseline `{}` in {}\n",
+                                      name,
+                                      path.display()))?;
+        }
+        if let Some(seed) = self.shuffle_seed {
+            self.write_plain(&format!("\nnote: tests were run in a random order, pass \
+                                       `--shuffle-seed {}` to reproduce it\n",
+                                      seed))?;
+        }
 
         self.write_plain("\ntest result: ")?;
         if success {
@@ -788,6 +1246,11 @@ impl<T: Write> ConsoleTestState<T> {
     }
 }
 
+// Format a duration as seconds with millisecond precision
+fn fmt_duration(dur: Duration) -> String {
+    format!("{}.{:03}s", dur.as_secs(), dur.subsec_nanos() / 1_000_000)
+}
+
 // Format a number with thousands separators
 fn fmt_thousands_sep(mut n: usize, sep: char) -> String {
     use std::fmt::Write;
@@ -843,7 +1306,10 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
         let TestDescAndFn { desc: TestDesc { name, .. }, testfn } = test;
 
         let fntype = match testfn {
-            StaticTestFn(..) | DynTestFn(..) => { ntest += 1; "test" },
+            StaticTestFn(..) | StaticFixtureTestFn(..) | DynTestFn(..) => {
+                ntest += 1;
+                "test"
+            }
             StaticBenchFn(..) | DynBenchFn(..) => { nbench += 1; "benchmark" },
             StaticMetricFn(..) | DynMetricFn(..) => { nmetric += 1; "metric" },
         };
@@ -859,7 +1325,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
         }
     }
 
-    if !opts.quiet {
+    if opts.output_format() != OutputFormat::Terse {
         if ntest != 0 || nbench != 0 || nmetric != 0 {
             st.write_plain("\n")?;
         }
@@ -881,11 +1347,25 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             TeFilteredOut(filtered_out) => Ok(st.filtered_out = filtered_out),
             TeWait(ref test, padding) => st.write_test_start(test, padding),
             TeTimeout(ref test) => st.write_timeout(test),
-            TeResult(test, result, stdout) => {
+            TeRetry(ref test, ref result, exec_time, ref stdout) => {
+                st.write_retry(test, result, exec_time, stdout)
+            }
+            TeResult(test, result, exec_time, stdout) => {
                 st.write_log_result(&test, &result)?;
-                st.write_result(&result)?;
+                st.write_junit_result(&test, &result, exec_time, &stdout);
+                st.write_result(&test, &result, exec_time, &stdout)?;
+                match result {
+                    TrOk | TrFailed | TrFailedMsg(_) => {
+                        st.exec_times.push((test.clone(), exec_time));
+                    }
+                    _ => {}
+                }
                 match result {
                     TrOk => {
+                        let attempts = st.failed_attempts(&test);
+                        if attempts > 0 {
+                            st.flaky.push((test.clone(), attempts));
+                        }
                         st.passed += 1;
                         st.not_failures.push((test, stdout));
                     }
@@ -900,6 +1380,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
                         st.measured += 1
                     }
                     TrBench(bs) => {
+                        st.record_bench(&test, &bs);
                         st.metrics.insert_metric(test.name.as_slice(),
                                                  bs.ns_iter_summ.median,
                                                  bs.ns_iter_summ.max - bs.ns_iter_summ.min);
@@ -944,19 +1425,22 @@ fn should_sort_failures_before_printing_them() {
         name: StaticTestName("a"),
         ignore: false,
         should_panic: ShouldPanic::No,
+        retries: None,
     };
 
     let test_b = TestDesc {
         name: StaticTestName("b"),
         ignore: false,
         should_panic: ShouldPanic::No,
+        retries: None,
     };
 
     let mut st = ConsoleTestState {
         log_out: None,
+        junit: None,
         out: Raw(Vec::new()),
         use_color: false,
-        quiet: false,
+        format: OutputFormat::Pretty,
         total: 0,
         passed: 0,
         failed: 0,
@@ -968,6 +1452,14 @@ fn should_sort_failures_before_printing_them() {
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
         options: Options::new(),
         not_failures: Vec::new(),
+        exec_times: Vec::new(),
+        report_time: None,
+        shuffle_seed: None,
+        baseline: None,
+        save_baseline: None,
+        regressions: Vec::new(),
+        retried: Vec::new(),
+        flaky: Vec::new(),
     };
 
     st.write_failures().unwrap();
@@ -981,6 +1473,13 @@ fn should_sort_failures_before_printing_them() {
     assert!(apos < bpos);
 }
 
+// The name of the running test binary, used to name the suite in reports.
+fn suite_name() -> String {
+    env::current_exe().ok()
+                      .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                      .unwrap_or_else(|| "test".to_owned())
+}
+
 fn use_color(opts: &TestOpts) -> bool {
     match opts.color {
         AutoColor => !opts.nocapture && stdout_isatty(),
@@ -1020,25 +1519,42 @@ fn stdout_isatty() -> bool {
 pub enum TestEvent {
     TeFiltered(Vec<TestDesc>),
     TeWait(TestDesc, NamePadding),
-    TeResult(TestDesc, TestResult, Vec<u8>),
+    TeResult(TestDesc, TestResult, Duration, Vec<u8>),
+    // A failed run of a test which is going to be run again
+    TeRetry(TestDesc, TestResult, Duration, Vec<u8>),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
 }
 
-pub type MonitorMsg = (TestDesc, TestResult, Vec<u8>);
+pub type MonitorMsg = (TestDesc, TestResult, Duration, Vec<u8>);
 
 
 pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) -> io::Result<()>
     where F: FnMut(TestEvent) -> io::Result<()>
 {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     use std::sync::mpsc::RecvTimeoutError;
 
     let tests_len = tests.len();
 
     let mut filtered_tests = filter_tests(opts, tests);
     if !opts.bench_benchmarks {
-        filtered_tests = convert_benchmarks_to_tests(filtered_tests);
+        filtered_tests = if opts.isolate {
+            // Benchmarks with a static function are run once by the child
+            // process instead, as any other test.
+            filtered_tests.into_iter().flat_map(|test| {
+                match test.testfn {
+                    StaticBenchFn(_) => vec![test],
+                    _ => convert_benchmarks_to_tests(vec![test]),
+                }
+            }).collect()
+        } else {
+            convert_benchmarks_to_tests(filtered_tests)
+        };
+    }
+    if opts.shuffle {
+        let seed = opts.shuffle_seed.unwrap_or_else(random_shuffle_seed);
+        shuffle_tests(seed, &mut filtered_tests);
     }
 
     let filtered_out = tests_len - filtered_tests.len();
@@ -1053,7 +1569,8 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
     let (filtered_tests, filtered_benchs_and_metrics): (Vec<_>, _) =
         filtered_tests.into_iter().partition(|e| {
             match e.testfn {
-                StaticTestFn(_) | DynTestFn(_) => true,
+                StaticTestFn(_) | StaticFixtureTestFn(..) | DynTestFn(_) => true,
+                StaticBenchFn(_) => !opts.bench_benchmarks,
                 _ => false,
             }
         });
@@ -1070,6 +1587,18 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
     let (tx, rx) = channel::<MonitorMsg>();
 
     let mut running_tests: HashMap<TestDesc, Instant> = HashMap::new();
+    // Deadlines of the running tests when `--test-timeout` is given, and the
+    // tests which have already been reported as failed for exceeding them.
+    let mut deadlines: HashMap<TestDesc, Instant> = HashMap::new();
+    let mut abandoned: HashSet<TestDesc> = HashSet::new();
+    // Copies of the tests which can be run again when they fail, with the
+    // number of retries they have left. Tests in the child processes of
+    // `--isolate` are retried by the parent instead.
+    let mut retries: HashMap<TestDesc, (TestDescAndFn, usize)> = HashMap::new();
+    let can_retry = env::var_os(isolate::CHILD_ENV).is_none();
+    // Tests which should be retried but can't be, because they don't have a
+    // static function to run again.
+    let mut not_retryable: HashSet<TestDesc> = HashSet::new();
 
     fn get_timed_out_tests(running_tests: &mut HashMap<TestDesc, Instant>) -> Vec<TestDesc> {
         let now = Instant::now();
@@ -1082,8 +1611,9 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
         timed_out
     };
 
-    fn calc_timeout(running_tests: &HashMap<TestDesc, Instant>) -> Option<Duration> {
-        running_tests.values().min().map(|next_timeout| {
+    fn calc_timeout(running_tests: &HashMap<TestDesc, Instant>,
+                    deadlines: &HashMap<TestDesc, Instant>) -> Option<Duration> {
+        running_tests.values().chain(deadlines.values()).min().map(|next_timeout| {
             let now = Instant::now();
             if *next_timeout >= now {
                 *next_timeout - now
@@ -1095,6 +1625,17 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
     while pending > 0 || !remaining.is_empty() {
         while pending < concurrency && !remaining.is_empty() {
             let test = remaining.pop().unwrap();
+            let allowed_retries = test.desc.retries.unwrap_or(opts.retries);
+            if can_retry && allowed_retries > 0 && !retries.contains_key(&test.desc) {
+                match copy_static_test(&test) {
+                    Some(copy) => {
+                        retries.insert(test.desc.clone(), (copy, allowed_retries));
+                    }
+                    None => {
+                        not_retryable.insert(test.desc.clone());
+                    }
+                }
+            }
             if concurrency == 1 {
                 // We are doing one test at a time so we can print the name
                 // of the test before we run it. Useful for debugging tests
@@ -1103,18 +1644,39 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
             }
             let timeout = Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S);
             running_tests.insert(test.desc.clone(), timeout);
+            // Isolated tests are killed by the thread waiting for their
+            // process when they run for too long.
+            if let (Some(limit), false) = (opts.test_timeout, opts.isolate) {
+                deadlines.insert(test.desc.clone(), Instant::now() + limit);
+            }
             run_test(opts, !opts.run_tests, test, tx.clone());
             pending += 1;
         }
 
         let mut res;
         loop {
-            if let Some(timeout) = calc_timeout(&running_tests) {
+            if let Some(timeout) = calc_timeout(&running_tests, &deadlines) {
                 res = rx.recv_timeout(timeout);
                 for test in get_timed_out_tests(&mut running_tests) {
                     callback(TeTimeout(test))?;
                 }
-                if res != Err(RecvTimeoutError::Timeout) {
+                // Tests over their time limit are reported as failed right
+                // away. Their threads can't be stopped, so we just stop
+                // waiting for them and ignore whatever they send later.
+                let exceeded = get_timed_out_tests(&mut deadlines);
+                let any_exceeded = !exceeded.is_empty();
+                for test in exceeded {
+                    let limit = opts.test_timeout.unwrap();
+                    running_tests.remove(&test);
+                    abandoned.insert(test.clone());
+                    if concurrency != 1 {
+                        callback(TeWait(test.clone(), PadNone))?;
+                    }
+                    let msg = format!("test did not finish within {}", fmt_duration(limit));
+                    callback(TeResult(test, TrFailedMsg(msg), limit, Vec::new()))?;
+                    pending -= 1;
+                }
+                if res != Err(RecvTimeoutError::Timeout) || any_exceeded {
                     break;
                 }
             } else {
@@ -1123,13 +1685,51 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
             }
         }
 
-        let (desc, result, stdout) = res.unwrap();
+        let (desc, result, exec_time, mut stdout) = match res {
+            Err(RecvTimeoutError::Timeout) => continue,
+            res => res.unwrap(),
+        };
+        if abandoned.remove(&desc) {
+            continue;
+        }
         running_tests.remove(&desc);
+        deadlines.remove(&desc);
+
+        // Tests which failed by exceeding `--test-timeout` are not retried,
+        // since their first run may still be going on.
+        let retry = match result {
+            TrFailed | TrFailedMsg(_) => {
+                match retries.get_mut(&desc) {
+                    Some(&mut (ref test, ref mut left)) if *left > 0 => {
+                        *left -= 1;
+                        copy_static_test(test)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
 
         if concurrency != 1 {
             callback(TeWait(desc.clone(), PadNone))?;
         }
-        callback(TeResult(desc, result, stdout))?;
+        match retry {
+            Some(test) => {
+                callback(TeRetry(desc, result, exec_time, stdout))?;
+                remaining.push(test);
+            }
+            None => {
+                let failed = match result {
+                    TrFailed | TrFailedMsg(_) => true,
+                    _ => false,
+                };
+                if failed && not_retryable.contains(&desc) {
+                    stdout.extend_from_slice(b"note: the test was not retried, as only tests \
+                                               with a static function can be run again\n");
+                }
+                callback(TeResult(desc, result, exec_time, stdout))?
+            }
+        }
         pending -= 1;
     }
 
@@ -1139,13 +1739,28 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
         for b in filtered_benchs_and_metrics {
             callback(TeWait(b.desc.clone(), b.testfn.padding()))?;
             run_test(opts, false, b, tx.clone());
-            let (test, result, stdout) = rx.recv().unwrap();
-            callback(TeResult(test, result, stdout))?;
+            let (test, result, exec_time, stdout) = rx.recv().unwrap();
+            callback(TeResult(test, result, exec_time, stdout))?;
         }
     }
     Ok(())
 }
 
+// Copies a test for running it again, which is only possible for tests with a
+// static function.
+fn copy_static_test(test: &TestDescAndFn) -> Option<TestDescAndFn> {
+    let testfn = match test.testfn {
+        StaticTestFn(f) => StaticTestFn(f),
+        StaticFixtureTestFn(fixtures, f) => StaticFixtureTestFn(fixtures, f),
+        StaticBenchFn(f) => StaticBenchFn(f),
+        _ => return None,
+    };
+    Some(TestDescAndFn {
+        desc: test.desc.clone(),
+        testfn: testfn,
+    })
+}
+
 #[allow(deprecated)]
 fn get_concurrency() -> usize {
     return match env::var("RUST_TEST_THREADS") {
@@ -1313,12 +1928,46 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         filtered.into_iter().filter_map(filter).collect()
     };
 
+    // Only keep the tests of our shard
+    if let Some((index, count)) = opts.shard {
+        filtered.retain(|t| shard_of(&t.desc.name, count) == index);
+    }
+
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
     filtered
 }
 
+/// Returns the shard out of `count` which the test called `name` belongs to.
+///
+/// This only depends on the name of the test, using the 64-bit FNV-1a hash
+/// rather than `DefaultHasher` whose output may change between releases, so
+/// that test binaries built by different compilers agree on the shards.
+pub fn shard_of(name: &TestName, count: usize) -> usize {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in name.as_slice().as_bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash % count as u64) as usize
+}
+
+/// Permutes `tests` in an order determined only by `seed`, so that a run can
+/// be reproduced by passing the same seed again.
+pub fn shuffle_tests(seed: u64, tests: &mut [TestDescAndFn]) {
+    let lo = seed as u32;
+    let hi = (seed >> 32) as u32;
+    // XorShiftRng must not be seeded with all zeroes.
+    let mut rng: XorShiftRng = SeedableRng::from_seed([lo, hi, lo ^ 0x9e3779b9, hi ^ 0x7f4a7c15]);
+    rng.shuffle(tests);
+}
+
+fn random_shuffle_seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::new(0, 0));
+    now.as_secs().wrapping_mul(1_000_000_000).wrapping_add(now.subsec_nanos() as u64)
+}
+
 pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     // convert benchmarks to tests, if we're not benchmarking them
     tests.into_iter().map(|x| {
@@ -1354,10 +2003,43 @@ pub fn run_test(opts: &TestOpts,
     let TestDescAndFn {desc, testfn} = test;
 
     if force_ignore || desc.ignore {
-        monitor_ch.send((desc, TrIgnored, Vec::new())).unwrap();
+        monitor_ch.send((desc, TrIgnored, Duration::new(0, 0), Vec::new())).unwrap();
         return;
     }
 
+    fn run_test_isolated(desc: TestDesc,
+                         monitor_ch: Sender<MonitorMsg>,
+                         opts: isolate::IsolateOpts) {
+        let name = desc.name.to_string();
+        let runtest = move || {
+            let (result, exec_time, stdout) = isolate::run_test_in_process(&desc, opts);
+            monitor_ch.send((desc, result, exec_time, stdout)).unwrap();
+        };
+        thread::Builder::new().name(name).spawn(runtest).unwrap();
+    }
+
+    // Only a test with a static function can be found again by its name in
+    // the child process, the others can't be isolated.
+    if opts.isolate {
+        match testfn {
+            StaticTestFn(_) | StaticFixtureTestFn(..) | StaticBenchFn(_) => {
+                let isolate_opts = isolate::IsolateOpts {
+                    run_ignored: opts.run_ignored,
+                    nocapture: opts.nocapture,
+                    test_timeout: opts.test_timeout,
+                };
+                return run_test_isolated(desc, monitor_ch, isolate_opts);
+            }
+            _ => {
+                let msg = "test can't be run in its own process for --isolate, \
+                           as it doesn't have a static function".to_owned();
+                monitor_ch.send((desc, TrFailedMsg(msg), Duration::new(0, 0), Vec::new()))
+                          .unwrap();
+                return;
+            }
+        }
+    }
+
     fn run_test_inner(desc: TestDesc,
                       monitor_ch: Sender<MonitorMsg>,
                       nocapture: bool,
@@ -1387,9 +2069,11 @@ pub fn run_test(opts: &TestOpts,
                 None
             };
 
+            let start = Instant::now();
             let result = catch_unwind(AssertUnwindSafe(|| {
                 testfn.call_box(())
             }));
+            let exec_time = start.elapsed();
 
             if let Some((printio, panicio)) = oldio {
                 io::set_print(printio);
@@ -1398,7 +2082,7 @@ pub fn run_test(opts: &TestOpts,
 
             let test_result = calc_result(&desc, result);
             let stdout = data.lock().unwrap().to_vec();
-            monitor_ch.send((desc.clone(), test_result, stdout)).unwrap();
+            monitor_ch.send((desc.clone(), test_result, exec_time, stdout)).unwrap();
         };
 
 
@@ -1417,27 +2101,28 @@ pub fn run_test(opts: &TestOpts,
         }
     }
 
+    let start = Instant::now();
     match testfn {
         DynBenchFn(bencher) => {
             let bs = ::bench::benchmark(|harness| bencher.run(harness));
-            monitor_ch.send((desc, TrBench(bs), Vec::new())).unwrap();
+            monitor_ch.send((desc, TrBench(bs), start.elapsed(), Vec::new())).unwrap();
             return;
         }
         StaticBenchFn(benchfn) => {
             let bs = ::bench::benchmark(|harness| (benchfn.clone())(harness));
-            monitor_ch.send((desc, TrBench(bs), Vec::new())).unwrap();
+            monitor_ch.send((desc, TrBench(bs), start.elapsed(), Vec::new())).unwrap();
             return;
         }
         DynMetricFn(f) => {
             let mut mm = MetricMap::new();
             f.call_box(&mut mm);
-            monitor_ch.send((desc, TrMetrics(mm), Vec::new())).unwrap();
+            monitor_ch.send((desc, TrMetrics(mm), start.elapsed(), Vec::new())).unwrap();
             return;
         }
         StaticMetricFn(f) => {
             let mut mm = MetricMap::new();
             f(&mut mm);
-            monitor_ch.send((desc, TrMetrics(mm), Vec::new())).unwrap();
+            monitor_ch.send((desc, TrMetrics(mm), start.elapsed(), Vec::new())).unwrap();
             return;
         }
         DynTestFn(f) => {
@@ -1449,6 +2134,34 @@ pub fn run_test(opts: &TestOpts,
         StaticTestFn(f) =>
             run_test_inner(desc, monitor_ch, opts.nocapture,
                            Box::new(move |()| __rust_begin_short_backtrace(f))),
+        StaticFixtureTestFn(fixtures, f) => {
+            let cb = move |()| {
+                __rust_begin_short_backtrace(|| run_with_fixtures(fixtures, f))
+            };
+            run_test_inner(desc, monitor_ch, opts.nocapture, Box::new(cb))
+        }
+    }
+}
+
+/// Runs `test` inside of `fixtures`, the outermost fixture first.
+///
+/// The teardown function of a fixture is run whenever its setup function
+/// has returned, even if the test (or an inner fixture) panics. The panic is
+/// then resumed so that the test still fails.
+pub fn run_with_fixtures(fixtures: &[Fixture], test: fn()) {
+    let (fixture, inner) = match fixtures.split_first() {
+        Some(split) => split,
+        None => return test(),
+    };
+    if let Some(setup) = fixture.setup {
+        setup();
+    }
+    let result = catch_unwind(AssertUnwindSafe(|| run_with_fixtures(inner, test)));
+    if let Some(teardown) = fixture.teardown {
+        teardown();
+    }
+    if let Err(payload) = result {
+        resume_unwind(payload);
     }
 }
 
@@ -1546,7 +2259,9 @@ impl Bencher {
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summ, samples) = iter_samples(&mut inner);
+        self.summary = Some(summ);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Option<stats::Summary>
@@ -1574,6 +2289,13 @@ fn ns_iter_inner<T, F>(inner: &mut F, k: u64) -> u64
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
     where F: FnMut() -> T
+{
+    iter_samples(inner).0
+}
+
+// Like `iter`, but also returns the samples the summary was computed from.
+fn iter_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
+    where F: FnMut() -> T
 {
     // Initial bench run to get ballpark figure.
     let ns_single = ns_iter_inner(inner, 1);
@@ -1616,13 +2338,13 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         // stable median.
         if loop_run > Duration::from_millis(100) && summ.median_abs_dev_pct < 1.0 &&
            summ.median - summ5.median < summ5.median_abs_dev {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         total_run = total_run + loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         // If we overflow here just return the results so far. We check a
@@ -1632,7 +2354,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, samples.to_vec());
             }
         };
     }
@@ -1649,6 +2371,7 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Auto,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
         };
 
@@ -1659,6 +2382,7 @@ pub mod bench {
 
                 BenchSamples {
                     ns_iter_summ: ns_iter_summ,
+                    ns_iter_samples: bs.samples,
                     mb_s: mb_s as usize,
                 }
             }
@@ -1668,6 +2392,7 @@ pub mod bench {
                 let samples: &mut [f64] = &mut [0.0_f64; 1];
                 BenchSamples {
                     ns_iter_summ: stats::Summary::new(samples),
+                    ns_iter_samples: Vec::new(),
                     mb_s: 0,
                 }
             }
@@ -1680,6 +2405,7 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Single,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
         };
         bs.bench(f);
@@ -1690,10 +2416,24 @@ pub mod bench {
 mod tests {
     use test::{TrFailed, TrFailedMsg, TrIgnored, TrOk, filter_tests, parse_opts, TestDesc,
                TestDescAndFn, TestOpts, run_test, MetricMap, StaticTestName, DynTestName,
-               DynTestFn, ShouldPanic};
+               DynTestFn, ShouldPanic, OutputFormat};
+    use {run_tests, shuffle_tests, TestEvent};
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs;
     use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+    use json::ToJson;
+    use baseline::Baseline;
     use bench;
-    use Bencher;
+    use json;
+    use isolate;
+    use junit;
+    use stats;
+    use {Bencher, BenchSamples};
+    #[cfg(unix)]
+    use {StaticTestFn, TestResult};
 
     #[test]
     pub fn do_not_run_ignored_tests() {
@@ -1705,12 +2445,13 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: true,
                 should_panic: ShouldPanic::No,
+                retries: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res != TrOk);
     }
 
@@ -1722,12 +2463,13 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: true,
                 should_panic: ShouldPanic::No,
+                retries: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrIgnored);
     }
 
@@ -1741,13 +2483,55 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
+                retries: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
+        assert!(res == TrOk);
+    }
+
+    #[test]
+    fn test_fixtures_run_around_panicking_test() {
+        use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+        use Fixture;
+        use StaticFixtureTestFn;
+
+        // The steps in the order they ran, one decimal digit each.
+        static STEPS: AtomicUsize = ATOMIC_USIZE_INIT;
+        fn step(n: usize) {
+            let steps = STEPS.load(Ordering::SeqCst);
+            STEPS.store(steps * 10 + n, Ordering::SeqCst);
+        }
+        fn outer_setup() { step(1) }
+        fn inner_setup() { step(2) }
+        fn f() {
+            step(3);
+            panic!("an error message");
+        }
+        fn inner_teardown() { step(4) }
+        fn outer_teardown() { step(5) }
+
+        static FIXTURES: &'static [Fixture] = &[
+            Fixture { setup: Some(outer_setup), teardown: Some(outer_teardown) },
+            Fixture { setup: Some(inner_setup), teardown: Some(inner_teardown) },
+        ];
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_panic: ShouldPanic::YesWithMessage("error message"),
+                retries: None,
+            },
+            testfn: StaticFixtureTestFn(FIXTURES, f),
+        };
+        let (tx, rx) = channel();
+        run_test(&TestOpts::new(), false, desc, tx);
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrOk);
+        assert_eq!(STEPS.load(Ordering::SeqCst), 12345);
     }
 
     #[test]
@@ -1760,12 +2544,13 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage("error message"),
+                retries: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrOk);
     }
 
@@ -1781,12 +2566,13 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage(expected),
+                retries: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrFailedMsg(format!("{} '{}'", failed_msg, expected)));
     }
 
@@ -1798,12 +2584,13 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
+                retries: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrFailed);
     }
 
@@ -1817,6 +2604,321 @@ mod tests {
         assert!((opts.run_ignored));
     }
 
+    #[test]
+    fn parse_format_flag() {
+        let args = vec!["progname".to_string(), "--format".to_string(), "json".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_format_flag"),
+        };
+        assert_eq!(opts.format, OutputFormat::Json);
+
+        let args = vec!["progname".to_string(), "-q".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_format_flag"),
+        };
+        assert!(opts.quiet);
+        assert_eq!(opts.output_format(), OutputFormat::Terse);
+
+        let args = vec!["progname".to_string(), "-q".to_string(), "--format".to_string(),
+                        "json".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_format_flag"),
+        };
+        assert_eq!(opts.output_format(), OutputFormat::Json);
+    }
+
+    #[test]
+    fn parse_report_time_flag() {
+        let args = vec!["progname".to_string(), "--report-time".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_report_time_flag"),
+        };
+        assert_eq!(opts.report_time, Some(10));
+
+        let args = vec!["progname".to_string(), "--report-time=3".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_report_time_flag"),
+        };
+        assert_eq!(opts.report_time, Some(3));
+    }
+
+    #[test]
+    fn test_timeout_fails_slow_tests() {
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                retries: None,
+            },
+            testfn: DynTestFn(Box::new(move |()| thread::sleep(Duration::from_secs(5)))),
+        };
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(1),
+            test_timeout: Some(Duration::from_millis(100)),
+            ..TestOpts::new()
+        };
+        let mut results = Vec::new();
+        run_tests(&opts, vec![desc], |event| {
+            if let TestEvent::TeResult(_, result, _, _) = event {
+                results.push(result);
+            }
+            Ok(())
+        }).unwrap();
+        assert_eq!(results.len(), 1);
+        match results[0] {
+            TrFailedMsg(ref msg) => assert!(msg.contains("did not finish")),
+            _ => panic!("test exceeding its time limit did not fail"),
+        }
+    }
+
+    // The child processes of `--isolate` run the test binary again, so these
+    // tests abort or crash themselves when run as the child.
+    #[cfg(unix)]
+    fn run_isolated(name: &'static str) -> TestResult {
+        fn f() {
+            panic!("only run in the child process");
+        }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName(name),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                retries: None,
+            },
+            testfn: StaticTestFn(f),
+        };
+        let opts = TestOpts { isolate: true, ..TestOpts::new() };
+        let (tx, rx) = channel();
+        run_test(&opts, false, desc, tx);
+        let (_, res, _, _) = rx.recv().unwrap();
+        res
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn isolated_test_aborting_fails() {
+        if env::var_os(isolate::CHILD_ENV).is_some() {
+            ::std::process::abort();
+        }
+        let res = run_isolated("tests::isolated_test_aborting_fails");
+        assert!(res == TrFailedMsg("test process was terminated by signal SIGABRT".to_owned()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn isolated_test_crashing_fails() {
+        if env::var_os(isolate::CHILD_ENV).is_some() {
+            unsafe { ::std::ptr::write_volatile(8 as *mut u8, 1) };
+        }
+        let res = run_isolated("tests::isolated_test_crashing_fails");
+        assert!(res == TrFailedMsg("test process was terminated by signal SIGSEGV".to_owned()));
+    }
+
+    #[test]
+    fn isolate_rejects_dynamic_tests() {
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                retries: None,
+            },
+            testfn: DynTestFn(Box::new(move |()| {})),
+        };
+        let opts = TestOpts { isolate: true, ..TestOpts::new() };
+        let (tx, rx) = channel();
+        run_test(&opts, false, desc, tx);
+        let (_, res, _, _) = rx.recv().unwrap();
+        match res {
+            TrFailedMsg(ref msg) => assert!(msg.contains("--isolate")),
+            _ => panic!("dynamic test was not rejected by --isolate"),
+        }
+
+        let args = vec!["progname".to_string(), "--isolate".to_string(), "--bench".to_string()];
+        assert!(match parse_opts(&args) {
+            Some(Err(_)) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn parse_retries_flag() {
+        let args = vec!["progname".to_string(), "--retries".to_string(), "3".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_retries_flag"),
+        };
+        assert_eq!(opts.retries, 3);
+
+        let args = vec!["progname".to_string(), "--retries".to_string(), "x".to_string()];
+        assert!(match parse_opts(&args) {
+            Some(Err(_)) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn failed_tests_are_retried() {
+        use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+        use StaticTestFn;
+
+        static FLAKY_RUNS: AtomicUsize = ATOMIC_USIZE_INIT;
+        fn flaky() {
+            if FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("not yet");
+            }
+        }
+        fn broken() { panic!() }
+
+        let tests = vec![
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName("flaky"),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    retries: None,
+                },
+                testfn: StaticTestFn(flaky),
+            },
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName("broken"),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    retries: Some(1),
+                },
+                testfn: StaticTestFn(broken),
+            },
+        ];
+        let opts = TestOpts {
+            run_tests: true,
+            retries: 2,
+            ..TestOpts::new()
+        };
+        let mut retried = Vec::new();
+        let mut results = Vec::new();
+        run_tests(&opts, tests, |event| {
+            match event {
+                TestEvent::TeRetry(desc, _, _, _) => retried.push(desc.name.to_string()),
+                TestEvent::TeResult(desc, result, _, _) => {
+                    results.push((desc.name.to_string(), result))
+                }
+                _ => {}
+            }
+            Ok(())
+        }).unwrap();
+
+        retried.sort();
+        assert_eq!(retried, ["broken", "flaky", "flaky"]);
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        assert!(results == [("broken".to_string(), TrFailed), ("flaky".to_string(), TrOk)]);
+    }
+
+    #[test]
+    fn dynamic_tests_are_not_retried() {
+        let tests = vec![
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName("dynamic"),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    retries: Some(2),
+                },
+                testfn: DynTestFn(Box::new(move |()| panic!())),
+            },
+        ];
+        let opts = TestOpts { run_tests: true, ..TestOpts::new() };
+        let mut events = Vec::new();
+        run_tests(&opts, tests, |event| {
+            match event {
+                TestEvent::TeRetry(..) => events.push("retry".to_string()),
+                TestEvent::TeResult(_, TrFailed, _, stdout) => {
+                    events.push(String::from_utf8(stdout).unwrap())
+                }
+                _ => {}
+            }
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert!(events[0].contains("the test was not retried"));
+    }
+
+    #[test]
+    fn json_test_finished_event() {
+        let desc = TestDesc {
+            name: StaticTestName("whatever"),
+            ignore: false,
+            should_panic: ShouldPanic::No,
+            retries: None,
+        };
+        let result = TrFailedMsg("boom".to_string());
+        let event = json::TestFinished {
+            desc: &desc,
+            result: &result,
+            exec_time: Duration::from_millis(1500),
+            stdout: b"out",
+            comparison: None,
+        };
+        assert_eq!(event.to_json().to_string(),
+                   "{\"event\":\"failed\",\"exec_time\":1.5,\"message\":\"boom\",\
+                    \"name\":\"whatever\",\"stdout\":\"out\",\"type\":\"test\"}");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut obj = BTreeMap::new();
+        obj.insert("s".to_owned(), "a \"b\"\\\n\u{1}\u{1f600}".to_json());
+        obj.insert("n".to_owned(), vec![1usize, 2].to_json());
+        obj.insert("f".to_owned(), 2.0f64.to_json());
+        let value = json::Json::Object(obj);
+        let text = value.to_string();
+        assert_eq!(text,
+                   "{\"f\":2.0,\"n\":[1,2],\"s\":\"a \\\"b\\\"\\\\\\n\\u0001\u{1f600}\"}");
+        assert_eq!(json::from_str(&text), Ok(value));
+        assert_eq!(json::from_str(" {\"a\" : [true, null, -1.5e1, \"\\ud83d\\ude00\"]} ")
+                       .unwrap()
+                       .to_string(),
+                   "{\"a\":[true,null,-15.0,\"\u{1f600}\"]}");
+        assert!(json::from_str("{\"a\":1,}").is_err());
+        assert!(json::from_str("[1] 2").is_err());
+        assert!(json::from_str("\"unterminated").is_err());
+    }
+
+    #[test]
+    fn junit_report() {
+        fn desc(name: &'static str) -> TestDesc {
+            TestDesc {
+                name: StaticTestName(name),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                retries: None,
+            }
+        }
+        let mut report = junit::JunitReport::new(Vec::new(), "suite".to_string());
+        report.add_result(&desc("a::passes"), &TrOk, Duration::from_millis(5), b"");
+        report.add_result(&desc("a::b::fails"),
+                          &TrFailedMsg("boom".to_string()),
+                          Duration::from_millis(1250),
+                          b"<out>");
+        report.add_result(&desc("skipped"), &TrIgnored, Duration::new(0, 0), b"");
+        let xml = String::from_utf8(report.finish().unwrap()).unwrap();
+
+        assert!(xml.contains(r#"failures="1" skipped="1" tests="3""#));
+        assert!(xml.contains(r#"<testcase classname="a" name="passes" time="0.005"/>"#));
+        assert!(xml.contains(r#"<testcase classname="a::b" name="fails" time="1.250">"#));
+        assert!(xml.contains(r#"<failure message="boom">&lt;out&gt;</failure>"#));
+        assert!(xml.contains(r#"<testcase classname="suite" name="skipped" time="0.000">"#));
+        assert!(xml.contains("<skipped/>"));
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         // When we run ignored tests the test filter should filter out all the
@@ -1831,6 +2933,7 @@ mod tests {
                                  name: StaticTestName("1"),
                                  ignore: true,
                                  should_panic: ShouldPanic::No,
+                                 retries: None,
                              },
                              testfn: DynTestFn(Box::new(move |()| {})),
                          },
@@ -1839,6 +2942,7 @@ mod tests {
                                  name: StaticTestName("2"),
                                  ignore: false,
                                  should_panic: ShouldPanic::No,
+                                 retries: None,
                              },
                              testfn: DynTestFn(Box::new(move |()| {})),
                          }];
@@ -1862,6 +2966,7 @@ mod tests {
                     name: StaticTestName(name),
                     ignore: false,
                     should_panic: ShouldPanic::No,
+                    retries: None,
                 },
                 testfn: DynTestFn(Box::new(move |()| {}))
             })
@@ -1943,6 +3048,7 @@ mod tests {
                         name: DynTestName((*name).clone()),
                         ignore: false,
                         should_panic: ShouldPanic::No,
+                        retries: None,
                     },
                     testfn: DynTestFn(Box::new(move |()| testfn())),
                 };
@@ -1967,6 +3073,190 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_shard_flag() {
+        let args = vec!["progname".to_string(), "--shard".to_string(), "2/3".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shard_flag"),
+        };
+        assert_eq!(opts.shard, Some((1, 3)));
+
+        for shard in &["0/3", "4/3", "1/0", "1", "a/b", "1/2/3"] {
+            let args = vec!["progname".to_string(), "--shard".to_string(), shard.to_string()];
+            assert!(match parse_opts(&args) {
+                Some(Err(_)) => true,
+                _ => false,
+            });
+        }
+    }
+
+    #[test]
+    pub fn shards_partition_tests() {
+        fn tests() -> Vec<TestDescAndFn> {
+            (0..100).map(|i| {
+                TestDescAndFn {
+                    desc: TestDesc {
+                        name: DynTestName(format!("test_{}", i)),
+                        ignore: false,
+                        should_panic: ShouldPanic::No,
+                        retries: None,
+                    },
+                    testfn: DynTestFn(Box::new(move |()| {})),
+                }
+            }).collect()
+        }
+
+        let mut opts = TestOpts::new();
+        opts.filter = Some("test_1".to_string());
+        let filtered = filter_tests(&opts, tests()).len();
+
+        let mut seen = Vec::new();
+        for index in 0..4 {
+            opts.shard = Some((index, 4));
+            let shard = filter_tests(&opts, tests());
+            assert!(shard.len() < filtered);
+            seen.extend(shard.into_iter().map(|t| t.desc.name.to_string()));
+        }
+        // Every test is in exactly one shard.
+        assert_eq!(seen.len(), filtered);
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), filtered);
+    }
+
+    #[test]
+    fn parse_shuffle_seed_flag() {
+        let args = vec!["progname".to_string(), "--shuffle-seed".to_string(), "42".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shuffle_seed_flag"),
+        };
+        assert!(opts.shuffle);
+        assert_eq!(opts.shuffle_seed, Some(42));
+
+        let args = vec!["progname".to_string(), "--shuffle".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shuffle_seed_flag"),
+        };
+        assert!(opts.shuffle);
+        assert!(opts.shuffle_seed.is_some());
+    }
+
+    #[test]
+    fn parse_baseline_flags() {
+        let args = vec!["progname".to_string(), "--bench".to_string(),
+                        "--save-baseline".to_string(), "new".to_string(),
+                        "--baseline".to_string(), "old".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_baseline_flags"),
+        };
+        assert_eq!(opts.save_baseline, Some("new".to_string()));
+        assert_eq!(opts.baseline, Some("old".to_string()));
+
+        let args = vec!["progname".to_string(), "--baseline".to_string(), "../old".to_string()];
+        assert!(match parse_opts(&args) {
+            Some(Err(_)) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn baseline_comparison() {
+        fn samples(median: f64) -> BenchSamples {
+            let samples = (0..50).map(|i| median + (i % 10) as f64 - 4.5).collect::<Vec<_>>();
+            BenchSamples {
+                ns_iter_summ: stats::Summary::new(&samples),
+                ns_iter_samples: samples,
+                mb_s: 0,
+            }
+        }
+        let desc = TestDesc {
+            name: StaticTestName("bench"),
+            ignore: false,
+            should_panic: ShouldPanic::No,
+            retries: None,
+        };
+        let other = TestDesc { name: StaticTestName("other"), ..desc.clone() };
+
+        let mut baseline = Baseline::new();
+        baseline.insert(&desc, &samples(100.0));
+
+        let cmp = baseline.compare(&desc, &samples(100.0)).unwrap();
+        assert_eq!(cmp.change_pct, 0.0);
+        assert!(!cmp.is_regression() && !cmp.is_improvement());
+
+        let cmp = baseline.compare(&desc, &samples(120.0)).unwrap();
+        assert_eq!(cmp.change_pct, 20.0);
+        assert!(cmp.p_value < 0.001);
+        assert!(cmp.is_regression());
+
+        // Significant, but below the threshold.
+        let cmp = baseline.compare(&desc, &samples(98.0)).unwrap();
+        assert!(cmp.p_value < 0.05);
+        assert!(!cmp.is_regression() && !cmp.is_improvement());
+
+        assert!(baseline.compare(&desc, &samples(50.0)).unwrap().is_improvement());
+        assert!(baseline.compare(&other, &samples(100.0)).is_none());
+
+        // keep apart from test runs happening at the same time
+        let dir = env::temp_dir().join(format!("libtest-baseline-comparison-{}",
+                                               process_id()));
+        let path = baseline.save(&dir, "saved").unwrap();
+        assert!(path.starts_with(&dir));
+        let loaded = Baseline::load(&dir, "saved").unwrap();
+        assert_eq!(loaded.compare(&desc, &samples(120.0)).unwrap().change_pct, 20.0);
+        assert!(Baseline::load(&dir, "missing").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    fn process_id() -> u32 {
+        unsafe { ::libc::getpid() as u32 }
+    }
+    #[cfg(windows)]
+    fn process_id() -> u32 {
+        extern "system" {
+            fn GetCurrentProcessId() -> u32;
+        }
+        unsafe { GetCurrentProcessId() }
+    }
+
+    #[test]
+    pub fn shuffle_tests_is_reproducible() {
+        fn tests() -> Vec<TestDescAndFn> {
+            (0..20).map(|i| TestDescAndFn {
+                desc: TestDesc {
+                    name: DynTestName(format!("test{:02}", i)),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    retries: None,
+                },
+                testfn: DynTestFn(Box::new(move |()| {})),
+            }).collect()
+        }
+        fn names(tests: &[TestDescAndFn]) -> Vec<String> {
+            tests.iter().map(|t| t.desc.name.to_string()).collect()
+        }
+
+        let mut a = tests();
+        let mut b = tests();
+        shuffle_tests(42, &mut a);
+        shuffle_tests(42, &mut b);
+        assert_eq!(names(&a), names(&b));
+        assert!(names(&a) != names(&tests()));
+
+        let mut c = tests();
+        shuffle_tests(43, &mut c);
+        assert!(names(&a) != names(&c));
+
+        let mut sorted = names(&a);
+        sorted.sort();
+        assert_eq!(sorted, names(&tests()));
+    }
+
     #[test]
     pub fn test_metricmap_compare() {
         let mut m1 = MetricMap::new();
@@ -11,7 +11,7 @@
 // force-host
 // no-prefer-dynamic
 
-#![feature(proc_macro, proc_macro_lib)]
+#![feature(proc_macro)]
 #![crate_type = "proc-macro"]
 
 extern crate proc_macro;
@@ -8,8 +8,6 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(deprecated)]
-
 #[deprecated(since = "1.0.0", note = "text")]
 pub fn deprecated() {}
 #[deprecated(since = "1.0.0", note = "text")]
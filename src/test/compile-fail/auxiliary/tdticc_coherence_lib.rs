@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(optin_builtin_traits, core)]
+#![feature(optin_builtin_traits)]
 #![crate_type = "rlib"]
 
 pub trait DefaultedTrait { }
@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(question_mark, question_mark_carrier)]
+#![feature(question_mark_carrier)]
 
 // Test that type inference fails where there are multiple possible return types
 // for the `?` operator.
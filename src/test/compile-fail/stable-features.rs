@@ -11,6 +11,8 @@
 // Testing that the stable_features lint catches use of stable
 // language and lib features.
 
+// ignore-tidy-stable-feature
+
 #![deny(stable_features)]
 #![feature(test_accepted_feature)] //~ ERROR this feature has been stable since 1.0.0
 #![feature(rust1)] //~ ERROR this feature has been stable since 1.0.0
@@ -0,0 +1,32 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+#![feature(test_fixtures)]
+
+#[test_setup]
+fn setup() {}
+
+#[test_setup]
+fn setup_again() {} //~ ERROR a module can only have one `#[test_setup]` function
+
+#[test_teardown]
+fn teardown(_: u32) {} //~ ERROR functions used as `#[test_teardown]` must have signature
+
+mod a {
+    #[test_setup]
+    #[test_teardown]
+    fn both() {} //~ ERROR a function cannot be both `#[test_setup]` and `#[test_teardown]`
+
+    #[test]
+    #[test_setup]
+    fn test_and_setup() {} //~ ERROR `#[test_setup]` functions cannot be tests
+}
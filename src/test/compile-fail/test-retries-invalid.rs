@@ -0,0 +1,29 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+#![feature(test_retries)]
+
+#[test(retries)]
+//~^ ERROR argument must be of the form: `retries = N`
+fn no_value() {}
+
+#[test(retries = "1")]
+//~^ ERROR argument must be of the form: `retries = N`
+fn string_value() {}
+
+#[test(retries = 1, ignore)]
+//~^ ERROR argument must be of the form: `retries = N`
+fn extra_argument() {}
+
+#[test(retries = 18446744073709551617)]
+//~^ ERROR the number of retries can be at most 4294967295
+fn too_many() {}
@@ -13,7 +13,6 @@
 // ignore-cross-compile
 
 #![feature(rustc_private, path)]
-#![feature(core)]
 
 extern crate getopts;
 extern crate rustc;
@@ -13,7 +13,6 @@
 // ignore-cross-compile
 
 #![feature(plugin, custom_attribute)]
-#![feature(type_macros)]
 
 #![plugin(proc_macro_def)]
 
@@ -14,7 +14,6 @@
 
 #[foo(bar)]
 mod foo {
-  #![feature(globs)]
 }
 
 pub fn main() {}
@@ -9,8 +9,6 @@
 // except according to those terms.
 
 
-#![feature(core)]
-
 // Catch mistakes in the overflowing literals lint.
 #![deny(overflowing_literals)]
 
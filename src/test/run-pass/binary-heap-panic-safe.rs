@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(rand, std_panic)]
+#![feature(rand)]
 
 use std::__rand::{thread_rng, Rng};
 use std::panic::{self, AssertUnwindSafe};
@@ -14,7 +14,7 @@
 // pretty-expanded FIXME #23616
 
 #![allow(unknown_features)]
-#![feature(box_syntax, os)]
+#![feature(box_syntax)]
 
 use std::os;
 
@@ -11,7 +11,7 @@
 // ignore-windows - this is a unix-specific test
 // ignore-emscripten
 
-#![feature(process_exec, libc)]
+#![feature(libc)]
 
 extern crate libc;
 
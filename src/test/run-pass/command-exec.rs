@@ -11,7 +11,6 @@
 // ignore-windows - this is a unix-specific test
 // ignore-pretty issue #37199
 // ignore-emscripten
-#![feature(process_exec)]
 
 use std::env;
 use std::os::unix::process::CommandExt;
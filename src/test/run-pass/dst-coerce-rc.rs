@@ -10,8 +10,6 @@
 
 // Test a very simple custom DST coercion.
 
-#![feature(core, rc_weak)]
-
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 
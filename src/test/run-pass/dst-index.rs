@@ -12,8 +12,6 @@
 // work and don't ICE.
 
 
-#![feature(core)]
-
 use std::ops::Index;
 use std::fmt::Debug;
 
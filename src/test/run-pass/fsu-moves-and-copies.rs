@@ -13,7 +13,7 @@
 
 
 #![allow(unknown_features)]
-#![feature(box_syntax, core)]
+#![feature(box_syntax)]
 
 struct ncint { v: isize }
 fn ncint(v: isize) -> ncint { ncint { v: v } }
@@ -9,8 +9,6 @@
 // except according to those terms.
 
 
-#![feature(core)]
-
 use std::ops::Add;
 
 extern "C" fn foo<T: Add>(a: T, b: T) -> T::Output { a + b }
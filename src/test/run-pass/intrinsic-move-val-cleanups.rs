@@ -20,7 +20,7 @@
 // introduce temporaries that require cleanup, and SOURCE panics, then
 // make sure the cleanups still occur.
 
-#![feature(core_intrinsics, sync_poison)]
+#![feature(core_intrinsics)]
 
 use std::cell::RefCell;
 use std::intrinsics;
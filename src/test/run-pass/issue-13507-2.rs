@@ -11,8 +11,6 @@
 // aux-build:issue13507.rs
 
 
-#![feature(core)]
-
 extern crate issue13507;
 use issue13507::testtypes;
 
@@ -8,8 +8,6 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(iter_arith)]
-
 fn main() {
     let x: [u64; 3] = [1, 2, 3];
     assert_eq!(6, (0..3).map(|i| x[i]).sum::<u64>());
@@ -12,8 +12,6 @@
 // work more smoothly.
 
 
-#![feature(core)]
-
 use std::ops::Index;
 
 struct Mat<T> { data: Vec<T>, cols: usize, }
@@ -10,8 +10,6 @@
 
 // pretty-expanded FIXME #23616
 
-#![feature(core)]
-
 trait Str { fn foo(&self) {} }
 impl Str for str {}
 impl<'a, S: ?Sized> Str for &'a S where S: Str {}
@@ -10,7 +10,6 @@
 
 // pretty-expanded FIXME #23616
 
-#![feature(core)]
 #![feature(const_fn)]
 
 
@@ -11,7 +11,6 @@
 // aux-build:issue-17718-aux.rs
 
 
-#![feature(core)]
 #![feature(const_fn)]
 
 extern crate issue_17718_aux as other;
@@ -10,7 +10,7 @@
 
 // ignore-aarch64
 // ignore-emscripten
-#![feature(std_misc, os)]
+#![feature(std_misc)]
 
 #[cfg(unix)]
 fn main() {
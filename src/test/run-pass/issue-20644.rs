@@ -13,8 +13,6 @@
 
 // pretty-expanded FIXME #23616
 
-#![feature(os)]
-
 use std::iter;
 use std::os;
 use std::fs::File;
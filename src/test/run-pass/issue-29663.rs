@@ -12,7 +12,6 @@
 
 // ignore-emscripten See #41299: probably a bad optimization
 
-#![feature(volatile)]
 use std::ptr::{read_volatile, write_volatile};
 
 #[derive(Debug, Eq, PartialEq)]
@@ -12,6 +12,7 @@
 // rustc-env:RUSTC_BOOTSTRAP_KEY=
 #![cfg(any())] // This test should be configured away
 #![feature(rustc_attrs)] // Test that this is allowed on stable/beta
+// tidy-ignore(stable-feature)
 #![feature(iter_arith_traits)] // Test that this is not unused
 #![deny(unused_features)]
 
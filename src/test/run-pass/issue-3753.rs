@@ -12,8 +12,6 @@
 // Issue Name: pub method preceded by attribute can't be parsed
 // Abstract: Visibility parsing failed when compiler parsing
 
-#![feature(core)]
-
 use std::f64;
 
 #[derive(Copy, Clone)]
@@ -16,8 +16,6 @@
 
 // pretty-expanded FIXME #23616
 
-#![feature(associated_types)]
-
 trait Hasher {
     type Output;
     fn finish(&self) -> Self::Output;
@@ -13,8 +13,6 @@
 
 // pretty-expanded FIXME #23616
 
-#![feature(core)]
-
 trait A {
     fn foo(self);
 }
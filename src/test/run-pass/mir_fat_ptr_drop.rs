@@ -10,7 +10,6 @@
 
 // test that ordinary fat pointer operations work.
 
-#![feature(braced_empty_structs)]
 #![feature(rustc_attrs)]
 
 use std::sync::atomic;
@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(no_core, core)]
+#![feature(no_core)]
 #![no_core]
 
 extern crate std;
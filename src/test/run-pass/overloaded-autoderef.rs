@@ -9,7 +9,7 @@
 // except according to those terms.
 
 #![allow(unknown_features)]
-#![feature(box_syntax, collections, core)]
+#![feature(box_syntax, collections)]
 
 use std::cell::RefCell;
 use std::rc::Rc;
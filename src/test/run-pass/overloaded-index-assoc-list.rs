@@ -12,8 +12,6 @@
 // takes its argument *by reference*.
 
 
-#![feature(core)]
-
 use std::ops::Index;
 
 struct AssociationList<K,V> {
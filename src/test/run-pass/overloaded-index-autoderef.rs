@@ -12,7 +12,7 @@
 
 
 #![allow(unknown_features)]
-#![feature(box_syntax, core)]
+#![feature(box_syntax)]
 
 use std::ops::{Index, IndexMut};
 
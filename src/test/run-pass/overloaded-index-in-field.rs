@@ -12,8 +12,6 @@
 // field. This caused problems at some point.
 
 
-#![feature(core)]
-
 use std::ops::Index;
 
 struct Foo {
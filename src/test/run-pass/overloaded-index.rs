@@ -9,8 +9,6 @@
 // except according to those terms.
 
 
-#![feature(core)]
-
 use std::ops::{Index, IndexMut};
 
 struct Foo {
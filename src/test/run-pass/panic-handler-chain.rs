@@ -10,7 +10,7 @@
 
 // ignore-emscripten no threads support
 
-#![feature(panic_handler, const_fn, std_panic)]
+#![feature(panic_handler, const_fn)]
 
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::panic;
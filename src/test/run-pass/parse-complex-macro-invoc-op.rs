@@ -12,8 +12,6 @@
 
 // pretty-expanded FIXME #23616
 
-#![feature(macro_rules)]
-
 macro_rules! id {
     ($e: expr) => { $e }
 }
@@ -10,8 +10,6 @@
 
 // compile-flags: -C debug-assertions
 
-#![feature(iter_to_slice)]
-
 use std::slice;
 
 fn foo<T>(v: &[T]) -> Option<&[T]> {
@@ -10,7 +10,6 @@
 
 
 // this is for the wrapping_add call below.
-#![feature(core)]
 
 /*!
  * Tests the range assertion wraparound case in trans::middle::adt::load_discr.
@@ -10,7 +10,7 @@
 
 #![allow(warnings)]
 #![feature(collections)]
-#![feature(drain, collections_bound, btree_range, vecmap)]
+#![feature(vecmap)]
 
 extern crate collections;
 
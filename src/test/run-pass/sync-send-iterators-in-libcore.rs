@@ -11,8 +11,6 @@
 // pretty-expanded FIXME #23616
 
 #![allow(warnings)]
-#![feature(iter_empty)]
-#![feature(iter_once)]
 #![feature(iter_unfold)]
 #![feature(step_by)]
 #![feature(str_escape)]
@@ -0,0 +1,75 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+// ignore-emscripten
+
+#![feature(test_fixtures)]
+
+use std::cell::RefCell;
+
+// Fixtures run on the thread of the test they are run for.
+thread_local!(static LOG: RefCell<Vec<&'static str>> = RefCell::new(Vec::new()));
+
+fn log(event: &'static str) {
+    LOG.with(|log| log.borrow_mut().push(event));
+}
+
+fn logged() -> Vec<&'static str> {
+    LOG.with(|log| log.borrow().clone())
+}
+
+#[test_setup]
+fn setup() {
+    log("outer setup");
+}
+
+#[test_teardown]
+fn teardown() {
+    let log = logged();
+    assert_eq!(log[0], "outer setup");
+    assert_eq!(log[log.len() - 1], "test");
+}
+
+#[test]
+fn outer() {
+    assert_eq!(logged(), ["outer setup"]);
+    log("test");
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn outer_panics() {
+    log("test");
+    panic!("boom");
+}
+
+mod inner {
+    use super::{log, logged};
+
+    #[test_setup]
+    fn setup() {
+        log("inner setup");
+    }
+
+    #[test]
+    fn inner() {
+        assert_eq!(logged(), ["outer setup", "inner setup"]);
+        log("test");
+    }
+
+    mod without_fixtures {
+        #[test]
+        fn innermost() {
+            assert_eq!(::logged(), ["outer setup", "inner setup"]);
+            ::log("test");
+        }
+    }
+}
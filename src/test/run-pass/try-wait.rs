@@ -10,8 +10,6 @@
 
 // ignore-emscripten
 
-#![feature(process_try_wait)]
-
 use std::env;
 use std::process::Command;
 use std::thread;
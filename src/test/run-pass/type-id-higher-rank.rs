@@ -12,8 +12,6 @@
 // Also acts as a regression test for an ICE (issue #19791)
 
 
-#![feature(core)]
-
 use std::any::{Any, TypeId};
 
 struct Struct<'a>(&'a ());
@@ -11,7 +11,7 @@
 // pretty-expanded FIXME #23616
 
 #![allow(unused_imports)]
-#![feature(start, no_core, core)]
+#![feature(start, no_core)]
 #![no_core]
 
 extern crate std;
@@ -15,7 +15,6 @@
 
 
 #![allow(dead_code)]
-#![feature(core)]
 
 struct List<'l> {
     field1: &'l i32,
@@ -8,7 +8,6 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(volatile)]
 use std::ptr::{read_volatile, write_volatile};
 
 fn main() {
@@ -9,7 +9,6 @@
 // except according to those terms.
 
 #![feature(optin_builtin_traits)]
-#![feature(core)]
 
 pub mod bar {
     use std::marker;
@@ -11,8 +11,6 @@
 // aux-build:issue-20646.rs
 // ignore-cross-compile
 
-#![feature(associated_types)]
-
 extern crate issue_20646;
 
 // @has issue_20646/trait.Trait.html \
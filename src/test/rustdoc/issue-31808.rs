@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(associated_consts, associated_types)]
+#![feature(associated_consts)]
 
 // Test that associated item impls on primitive types don't crash rustdoc
 
@@ -0,0 +1,31 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z aggregate-diagnostics
+
+// Check that the warnings held back are reported when the compilation ends
+// with a fatal error.
+
+#![recursion_limit="4"]
+
+macro_rules! unused {
+    () => { let x = 1; }
+}
+
+#[allow(unconditional_recursion)]
+fn generic<T>() {
+    generic::<Option<T>>();
+}
+
+fn main() {
+    unused!();
+    unused!();
+    generic::<i32>();
+}
@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(core, fnbox)]
+#![feature(fnbox)]
 
 use std::boxed::FnBox;
 
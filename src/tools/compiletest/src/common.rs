@@ -13,7 +13,7 @@ use std::fmt;
 use std::str::FromStr;
 use std::path::PathBuf;
 
-use test::ColorConfig;
+use test::{ColorConfig, OutputFormat};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Mode {
@@ -101,7 +101,7 @@ pub struct Config {
     // The python executable to use for htmldocck
     pub docck_python: String,
 
-    // The llvm FileCheck binary path
+    // The llvm FileCheck binary path, used instead of the built-in FileCheck
     pub llvm_filecheck: Option<PathBuf>,
 
     // The valgrind path
@@ -132,9 +132,15 @@ pub struct Config {
     // Exactly match the filter, rather than a substring
     pub filter_exact: bool,
 
+    // Overwrite the expected output of UI and MIR-opt tests with the actual output
+    pub bless: bool,
+
     // Write out a parseable log of tests that were run
     pub logfile: Option<PathBuf>,
 
+    // Write a JSON report of every test to this directory
+    pub report_dir: Option<PathBuf>,
+
     // A command line to prefix program execution with,
     // for running under valgrind
     pub runtool: Option<String>,
@@ -184,8 +190,8 @@ pub struct Config {
     // Explain what's going on
     pub verbose: bool,
 
-    // Print one character per test instead of one line
-    pub quiet: bool,
+    // How libtest should report the progress of the test run
+    pub format: OutputFormat,
 
     // Whether to use colors in test.
     pub color: ColorConfig,
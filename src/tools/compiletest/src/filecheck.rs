@@ -0,0 +1,971 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An implementation of LLVM's `FileCheck`, used to check the IR emitted for
+//! codegen tests.
+//!
+//! The test file is searched for `CHECK:`, `CHECK-NEXT:`, `CHECK-SAME:`,
+//! `CHECK-NOT:`, `CHECK-DAG:` and `CHECK-LABEL:` directives, which have the
+//! same meaning as for `FileCheck` with its default options. Patterns are
+//! matched literally, except for regexes written as `{{regex}}`, variable
+//! definitions `[[NAME:regex]]`, uses of variables defined by an earlier
+//! directive `[[NAME]]`, and `[[@LINE]]`, `[[@LINE+N]]` or `[[@LINE-N]]`
+//! for the line number of the directive. Runs of spaces and tabs match any
+//! other such run, in both the patterns and the input. As with
+//! `FileCheck`, a directive only matches within a single line of the input,
+//! so negated classes like `{{[^%]*}}` and `{{\s*}}` don't match newlines.
+//!
+//! A failure is reported with the part of the input where matching stopped,
+//! and, when a pattern wasn't found, the line which comes closest to it.
+//!
+//! LLVM's own `FileCheck` is still used instead when it is given with
+//! `--llvm-filecheck`.
+
+use std::cmp;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+use regex::{self, Regex};
+
+const PREFIX: &'static str = "CHECK";
+
+/// How many lines of input are shown around each line of interest.
+const CONTEXT_LINES: usize = 2;
+
+/// The highest score for a line to be reported as a possible intended match.
+const MAX_FUZZY_SCORE: f64 = 50.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    Plain,
+    Next,
+    Same,
+    Not,
+    Dag,
+    Label,
+    /// Matches the end of the input, to check the trailing `CHECK-NOT` and
+    /// `CHECK-DAG` directives.
+    Eof,
+}
+
+impl Kind {
+    fn suffix(&self) -> &'static str {
+        match *self {
+            Kind::Plain | Kind::Eof => "",
+            Kind::Next => "-NEXT",
+            Kind::Same => "-SAME",
+            Kind::Not => "-NOT",
+            Kind::Dag => "-DAG",
+            Kind::Label => "-LABEL",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    Regex(String),
+    Define(String, String),
+    Use(String),
+    /// `[[@LINE]]` with an offset.
+    Line(isize),
+}
+
+struct Directive {
+    kind: Kind,
+    /// The line of the test file the directive is on, and its text.
+    line: usize,
+    source: String,
+    pieces: Vec<Piece>,
+}
+
+/// A directive matched in order, along with the `CHECK-NOT` and
+/// `CHECK-DAG` directives preceding it.
+struct CheckString {
+    directive: Directive,
+    dag_not: Vec<Directive>,
+}
+
+/// A directive which didn't match, or couldn't be used.
+pub struct Failure {
+    check: Option<(usize, String)>,
+    message: String,
+    notes: Vec<String>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.check {
+            Some((line, ref source)) => {
+                write!(f, "line {}: {}\n    {}", line, self.message, source)?
+            }
+            None => write!(f, "{}", self.message)?,
+        }
+        for note in &self.notes {
+            write!(f, "\n{}", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the file `input` against the directives in `check_file`,
+/// returning the directives which failed.
+pub fn check(check_file: &Path, input: &Path) -> Vec<Failure> {
+    let read = |path: &Path| {
+        let mut contents = String::new();
+        File::open(path).and_then(|mut f| f.read_to_string(&mut contents)).map(|_| contents)
+            .map_err(|e| {
+                Failure {
+                    check: None,
+                    message: format!("failed to read {}: {}", path.display(), e),
+                    notes: Vec::new(),
+                }
+            })
+    };
+    let (checks, input) = match (read(check_file), read(input)) {
+        (Ok(checks), Ok(input)) => (checks, input),
+        (Err(e), _) | (_, Err(e)) => return vec![e],
+    };
+    check_str(&checks, &input)
+}
+
+fn check_str(checks: &str, input: &str) -> Vec<Failure> {
+    let check_strings = match parse_checks(checks) {
+        Ok(check_strings) => check_strings,
+        Err(e) => return vec![e],
+    };
+    if check_strings.is_empty() {
+        return vec![Failure {
+            check: None,
+            message: format!("no check strings found with prefix '{}:'", PREFIX),
+            notes: Vec::new(),
+        }];
+    }
+    let mut checker = Checker::new(input);
+    checker.run(&check_strings);
+    checker.failures
+}
+
+/// Collapses runs of spaces and tabs into a single space, and removes the
+/// `\r` of `\r\n` line endings.
+fn canonicalize(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            ' ' | '\t' => {
+                out.push(' ');
+                while chars.peek() == Some(&' ') || chars.peek() == Some(&'\t') {
+                    chars.next();
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn parse_checks(checks: &str) -> Result<Vec<CheckString>, Failure> {
+    let mut check_strings = Vec::new();
+    let mut dag_not = Vec::new();
+    for (i, line) in canonicalize(checks).lines().enumerate() {
+        let (kind, pattern) = match find_directive(line) {
+            Some(directive) => directive,
+            None => continue,
+        };
+        let error = |message: String| {
+            Failure {
+                check: Some((i + 1, line.trim().to_owned())),
+                message: message,
+                notes: Vec::new(),
+            }
+        };
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(error(format!("found empty check string with prefix '{}{}:'",
+                                     PREFIX,
+                                     kind.suffix())));
+        }
+        let pieces = parse_pattern(pattern).map_err(&error)?;
+        let has_variable = pieces.iter().any(|p| {
+            match *p {
+                Piece::Define(..) | Piece::Use(_) => true,
+                _ => false,
+            }
+        });
+        if kind == Kind::Label && has_variable {
+            return Err(error(format!("found '{}-LABEL:' with variable definition or use",
+                                     PREFIX)));
+        }
+        if (kind == Kind::Next || kind == Kind::Same) && check_strings.is_empty() {
+            return Err(error(format!("found '{}{}' without previous '{}: line",
+                                     PREFIX,
+                                     kind.suffix(),
+                                     PREFIX)));
+        }
+
+        let directive = Directive {
+            kind: kind,
+            line: i + 1,
+            source: line.trim().to_owned(),
+            pieces: pieces,
+        };
+        match kind {
+            Kind::Not | Kind::Dag => dag_not.push(directive),
+            _ => {
+                check_strings.push(CheckString {
+                    directive: directive,
+                    dag_not: dag_not,
+                });
+                dag_not = Vec::new();
+            }
+        }
+    }
+    if !dag_not.is_empty() {
+        check_strings.push(CheckString {
+            directive: Directive {
+                kind: Kind::Eof,
+                line: 0,
+                source: String::new(),
+                pieces: Vec::new(),
+            },
+            dag_not: dag_not,
+        });
+    }
+    Ok(check_strings)
+}
+
+/// Finds a directive on a line of the test file, returning its kind and
+/// the text following its colon.
+fn find_directive(line: &str) -> Option<(Kind, &str)> {
+    for (i, _) in line.match_indices(PREFIX) {
+        // the prefix must not be part of a longer word
+        let preceded_by_word = line[..i].chars().next_back().map_or(false, |c| {
+            c.is_alphanumeric() || c == '-' || c == '_'
+        });
+        if preceded_by_word {
+            continue;
+        }
+        let rest = &line[i + PREFIX.len()..];
+        let kinds = [Kind::Plain, Kind::Next, Kind::Same, Kind::Not, Kind::Dag, Kind::Label];
+        for &kind in &kinds {
+            let suffix = format!("{}:", kind.suffix());
+            if rest.starts_with(&suffix[..]) {
+                return Some((kind, &rest[suffix.len()..]));
+            }
+        }
+    }
+    None
+}
+
+fn parse_pattern(mut pattern: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    while !pattern.is_empty() {
+        if pattern.starts_with("{{") {
+            let end = match pattern[2..].find("}}") {
+                Some(end) => end + 2,
+                None => return Err("found start of regex string with no end '}}'".to_owned()),
+            };
+            pieces.push(Piece::Regex(pattern[2..end].to_owned()));
+            pattern = &pattern[end + 2..];
+        } else if pattern.starts_with("[[") {
+            let end = match find_variable_end(&pattern[2..])? {
+                Some(end) => end + 2,
+                None => return Err("invalid named regex reference, no ]] found".to_owned()),
+            };
+            pieces.push(parse_variable(&pattern[2..end])?);
+            pattern = &pattern[end + 2..];
+        } else {
+            let end = match (pattern.find("{{"), pattern.find("[[")) {
+                (Some(a), Some(b)) => cmp::min(a, b),
+                (Some(a), None) | (None, Some(a)) => a,
+                (None, None) => pattern.len(),
+            };
+            pieces.push(Piece::Literal(pattern[..end].to_owned()));
+            pattern = &pattern[end..];
+        }
+    }
+    Ok(pieces)
+}
+
+/// Finds the `]]` closing a variable, skipping over the brackets of
+/// character classes in its regex.
+fn find_variable_end(s: &str) -> Result<Option<usize>, String> {
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if depth == 0 && s[i..].starts_with("]]") {
+            return Ok(Some(i));
+        }
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => depth += 1,
+            b']' if depth == 0 => {
+                return Err("missing closing \"]\" for regex variable".to_owned())
+            }
+            b']' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(None)
+}
+
+fn parse_variable(var: &str) -> Result<Piece, String> {
+    let (name, regex) = match var.find(':') {
+        Some(colon) => (&var[..colon], Some(&var[colon + 1..])),
+        None => (var, None),
+    };
+    if name.starts_with("@LINE") && regex.is_none() {
+        let offset = name["@LINE".len()..].trim();
+        let offset = if offset.is_empty() {
+            Some(0)
+        } else if offset.starts_with('+') {
+            offset[1..].trim().parse().ok()
+        } else if offset.starts_with('-') {
+            offset[1..].trim().parse::<isize>().ok().map(|n| -n)
+        } else {
+            None
+        };
+        return match offset {
+            Some(offset) => Ok(Piece::Line(offset)),
+            None => Err(format!("invalid expression in `[[{}]]`", var)),
+        };
+    }
+
+    let valid = !name.is_empty() &&
+                name.chars().enumerate().all(|(i, c)| {
+                    c == '_' || (c.is_alphanumeric() && (i > 0 || !c.is_numeric()))
+                });
+    if !valid {
+        return Err(format!("invalid name in named regex: `{}`", name));
+    }
+    Ok(match regex {
+        Some(regex) => Piece::Define(name.to_owned(), regex.to_owned()),
+        None => Piece::Use(name.to_owned()),
+    })
+}
+
+/// A match of a directive: its byte range in the input and the variables
+/// it defined.
+struct Match {
+    start: usize,
+    end: usize,
+    defines: Vec<(String, String)>,
+}
+
+impl Directive {
+    /// Builds the regex for this directive with the current values of the
+    /// variables, returning the names of the variables it defines in the
+    /// order of their capture groups.
+    fn regex(&self, vars: &HashMap<String, String>) -> Result<(Regex, Vec<String>), String> {
+        let mut re = String::new();
+        let mut defines = Vec::new();
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Literal(ref s) => re.push_str(&regex::escape(s)),
+                Piece::Regex(ref r) => re.push_str(&format!("(?:{})", r)),
+                Piece::Define(ref name, ref r) => {
+                    re.push_str(&format!("({})", r));
+                    defines.push(name.clone());
+                }
+                Piece::Use(ref name) => {
+                    if defines.contains(name) {
+                        return Err(format!("variable `{}` is used on the line defining it, \
+                                            which isn't supported",
+                                           name));
+                    }
+                    match vars.get(name) {
+                        Some(value) => re.push_str(&regex::escape(value)),
+                        None => return Err(format!("uses undefined variable `{}`", name)),
+                    }
+                }
+                Piece::Line(offset) => {
+                    let line = self.line as isize + offset;
+                    re.push_str(&line.to_string());
+                }
+            }
+        }
+        match Regex::new(&re) {
+            Ok(re) => Ok((re, defines)),
+            Err(e) => Err(format!("invalid regex: {}", e)),
+        }
+    }
+
+    /// Returns the first match of this directive in `input[start..end]`.
+    fn find(&self,
+            input: &str,
+            start: usize,
+            end: usize,
+            vars: &HashMap<String, String>)
+            -> Result<Option<Match>, String> {
+        if self.kind == Kind::Eof {
+            return Ok(Some(Match {
+                start: end,
+                end: end,
+                defines: Vec::new(),
+            }));
+        }
+        let (re, names) = self.regex(vars)?;
+        // As with `FileCheck`, the search starts right after the previous
+        // match, where `^` matches too, as it does at the start of each line.
+        let mut line_start = start;
+        for line in input[start..end].split('\n') {
+            if let Some(caps) = re.captures(line) {
+                let whole = caps.get(0).unwrap();
+                let defines = names.iter().enumerate().map(|(i, name)| {
+                    let value = caps.get(i + 1).map_or("", |m| m.as_str());
+                    (name.clone(), value.to_owned())
+                }).collect();
+                return Ok(Some(Match {
+                    start: line_start + whole.start(),
+                    end: line_start + whole.end(),
+                    defines: defines,
+                }));
+            }
+            line_start += line.len() + 1;
+        }
+        Ok(None)
+    }
+
+    /// The pattern of this directive with the values of the variables it
+    /// uses filled in, to show next to the line which came closest to it.
+    fn expected(&self, vars: &HashMap<String, String>) -> String {
+        let mut expected = String::new();
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Literal(ref s) => expected.push_str(s),
+                Piece::Regex(ref r) => expected.push_str(&format!("{{{{{}}}}}", r)),
+                Piece::Define(ref name, ref r) => {
+                    expected.push_str(&format!("[[{}:{}]]", name, r))
+                }
+                Piece::Use(ref name) => {
+                    match vars.get(name) {
+                        Some(value) => expected.push_str(value),
+                        None => expected.push_str(&format!("[[{}]]", name)),
+                    }
+                }
+                Piece::Line(offset) => {
+                    expected.push_str(&(self.line as isize + offset).to_string())
+                }
+            }
+        }
+        expected
+    }
+
+    /// The literal text this directive is expected to match, for finding
+    /// the line which comes closest to it.
+    fn example(&self, vars: &HashMap<String, String>) -> String {
+        let mut example = String::new();
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Literal(ref s) => example.push_str(s),
+                Piece::Use(ref name) => {
+                    example.push_str(vars.get(name).map_or("", |s| &s[..]))
+                }
+                Piece::Line(offset) => {
+                    example.push_str(&(self.line as isize + offset).to_string())
+                }
+                Piece::Regex(_) | Piece::Define(..) => {}
+            }
+        }
+        example
+    }
+}
+
+struct Checker<'a> {
+    /// The input with its whitespace canonicalized, and its original lines.
+    input: String,
+    lines: Vec<&'a str>,
+    /// The byte offset in `input` of the start of each line.
+    line_starts: Vec<usize>,
+    vars: HashMap<String, String>,
+    /// Variables defined by directives which failed, so that their uses
+    /// aren't reported as errors too.
+    lost_vars: HashSet<String>,
+    failures: Vec<Failure>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(input: &'a str) -> Checker<'a> {
+        let canonical = canonicalize(input);
+        let mut line_starts = vec![0];
+        line_starts.extend(canonical.match_indices('\n').map(|(i, _)| i + 1));
+        Checker {
+            input: canonical,
+            lines: input.lines().collect(),
+            line_starts: line_starts,
+            vars: HashMap::new(),
+            lost_vars: HashSet::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// Returns the line of the input (starting at 1) containing an offset.
+    fn line_of(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        }
+    }
+
+    fn run(&mut self, check_strings: &[CheckString]) {
+        let len = self.input.len();
+        let mut pos = 0;
+        let mut i = 0;
+        let mut j = 0;
+        loop {
+            // Find the next label; the region to check ends with it.
+            let mut region_end = len;
+            while j < check_strings.len() && check_strings[j].directive.kind != Kind::Label {
+                j += 1;
+            }
+            if j < check_strings.len() {
+                match self.check(&check_strings[j], pos, len, true) {
+                    Some(m) => region_end = m.end,
+                    None => return,
+                }
+                j += 1;
+            }
+
+            let mut region_start = pos;
+            while i < j {
+                match self.check(&check_strings[i], region_start, region_end, false) {
+                    Some(m) => region_start = m.end,
+                    None => break,
+                }
+                i += 1;
+            }
+            i = j;
+            pos = region_end;
+            if j == check_strings.len() {
+                return;
+            }
+        }
+    }
+
+    fn record(&mut self, m: &Match) {
+        for &(ref name, ref value) in &m.defines {
+            self.vars.insert(name.clone(), value.clone());
+        }
+    }
+
+    fn find(&mut self,
+            directive: &Directive,
+            start: usize,
+            end: usize)
+            -> Result<Option<Match>, ()> {
+        let uses_lost_var = directive.pieces.iter().any(|piece| match *piece {
+            Piece::Use(ref name) => {
+                !self.vars.contains_key(name) && self.lost_vars.contains(name)
+            }
+            _ => false,
+        });
+        if uses_lost_var {
+            return Err(());
+        }
+        match directive.find(&self.input, start, end, &self.vars) {
+            Ok(m) => Ok(m),
+            Err(e) => {
+                self.fail(directive, e, Vec::new());
+                Err(())
+            }
+        }
+    }
+
+    /// Matches a check string in `input[start..end]`, along with the
+    /// `CHECK-DAG` and `CHECK-NOT` directives before it unless scanning for
+    /// a label. Returns its match, or `None` after recording a failure.
+    fn check(&mut self,
+             check_string: &CheckString,
+             start: usize,
+             end: usize,
+             label_scan: bool)
+             -> Option<Match> {
+        let mut pos = start;
+        let mut nots = Vec::new();
+        if !label_scan {
+            pos = match self.check_dag(&check_string.dag_not, start, end, &mut nots) {
+                Some(pos) => pos,
+                None => return None,
+            };
+        }
+
+        let directive = &check_string.directive;
+        let m = match self.find(directive, pos, end) {
+            Ok(Some(m)) => m,
+            Ok(None) => {
+                self.not_found(directive, pos, end);
+                return None;
+            }
+            Err(()) => return None,
+        };
+        self.record(&m);
+        if label_scan {
+            return Some(m);
+        }
+
+        let newlines = self.input[pos..m.start].matches('\n').count();
+        let error = match directive.kind {
+            Kind::Next if newlines == 0 => Some("is on the same line as the previous match"),
+            Kind::Next if newlines > 1 => Some("is not on the line after the previous match"),
+            Kind::Same if newlines > 0 => Some("is not on the same line as the previous match"),
+            _ => None,
+        };
+        if let Some(error) = error {
+            let message = format!("{}{}: {}", PREFIX, directive.kind.suffix(), error);
+            let prev = self.line_of(pos.saturating_sub(1));
+            let this = self.line_of(m.start);
+            let notes = self.excerpt(&[(prev, '>', "previous match"), (this, '-', "match")],
+                                     None);
+            self.fail(directive, message, notes);
+            return None;
+        }
+        if !self.check_not(&nots, pos, m.start) {
+            return None;
+        }
+        Some(m)
+    }
+
+    /// Matches the `CHECK-DAG` directives in any order, checking that the
+    /// `CHECK-NOT` directives between them don't match in between. The
+    /// directives of a group between two `CHECK-NOT`s must match distinct,
+    /// non-overlapping text. Returns the end of the last match, or `None`
+    /// after recording a failure.
+    fn check_dag<'b>(&mut self,
+                     dag_not: &'b [Directive],
+                     start: usize,
+                     end: usize,
+                     nots: &mut Vec<&'b Directive>)
+                     -> Option<usize> {
+        let mut last = start;
+        let mut search_start = start;
+        // the ranges matched by the directives of the current group
+        let mut matched: Vec<(usize, usize)> = Vec::new();
+        for directive in dag_not {
+            if directive.kind == Kind::Not {
+                nots.push(directive);
+                continue;
+            }
+            if !nots.is_empty() {
+                matched.clear();
+            }
+            let mut from = search_start;
+            let m = loop {
+                let m = match self.find(directive, from, end) {
+                    Ok(Some(m)) => m,
+                    Ok(None) => {
+                        self.not_found(directive, search_start, end);
+                        return None;
+                    }
+                    Err(()) => return None,
+                };
+                // look for another match after the text already matched
+                match matched.iter().find(|&&(lo, hi)| m.start < hi && lo < m.end) {
+                    Some(&(_, hi)) => from = hi,
+                    None => break m,
+                }
+            };
+            matched.push((m.start, m.end));
+            self.record(&m);
+            if !nots.is_empty() {
+                if m.start < last {
+                    let message = format!("found a {}-DAG reordering across a {}-NOT",
+                                          PREFIX,
+                                          PREFIX);
+                    let notes = self.excerpt(&[(self.line_of(m.start), '-', "match")], None);
+                    self.fail(directive, message, notes);
+                    return None;
+                }
+                search_start = last;
+                if !self.check_not(nots, last, m.start) {
+                    return None;
+                }
+                nots.clear();
+            }
+            last = cmp::max(last, m.end);
+        }
+        Some(last)
+    }
+
+    /// Checks that none of `nots` match in `input[start..end]`.
+    fn check_not(&mut self, nots: &[&Directive], start: usize, end: usize) -> bool {
+        for directive in nots {
+            let m = match self.find(directive, start, end) {
+                Ok(Some(m)) => m,
+                Ok(None) => continue,
+                Err(()) => return false,
+            };
+            let message = format!("{}-NOT: excluded string found in input", PREFIX);
+            let marks = [(self.line_of(start), '>', "scanning from here"),
+                         (self.line_of(m.start), '-', "excluded string")];
+            let notes = self.excerpt(&marks, None);
+            self.fail(directive, message, notes);
+            return false;
+        }
+        true
+    }
+
+    fn not_found(&mut self, directive: &Directive, start: usize, end: usize) {
+        let scan_line = self.line_of(start);
+        let mut marks = vec![(scan_line, '>', "scanning from here")];
+        let mut expected = None;
+        if let Some(line) = self.closest_line(directive, start, end) {
+            if line != scan_line {
+                marks.push((line, '-', "possible intended match"));
+            } else {
+                marks[0].1 = '-';
+            }
+            expected = Some((line, directive.expected(&self.vars)));
+        }
+        let notes = self.excerpt(&marks, expected);
+        self.fail(directive, "expected string not found in input".to_owned(), notes);
+    }
+
+    /// Finds the line in `input[start..end]` most similar to the text the
+    /// directive expects, preferring the lines closest to `start`.
+    fn closest_line(&self, directive: &Directive, start: usize, end: usize) -> Option<usize> {
+        let example = directive.example(&self.vars);
+        let example: Vec<char> = example.trim().chars().collect();
+        if example.is_empty() {
+            return None;
+        }
+        let first = self.line_of(start);
+        let last = self.line_of(end.saturating_sub(1));
+        let mut best = None;
+        for line in first..last + 1 {
+            let text = &self.input[self.line_starts[line - 1]..];
+            let text: Vec<char> = text.trim_left().chars()
+                                      .take_while(|&c| c != '\n')
+                                      .take(example.len())
+                                      .collect();
+            let score = edit_distance(&example, &text) as f64 + (line - first) as f64 / 100.0;
+            if best.map_or(true, |(_, best)| score < best) {
+                best = Some((line, score));
+            }
+        }
+        match best {
+            Some((line, score)) if score < MAX_FUZZY_SCORE => Some(line),
+            _ => None,
+        }
+    }
+
+    /// Shows the input around the given lines, each marked with a character
+    /// and labeled. The expected text is shown below its line, like a diff.
+    fn excerpt(&self,
+               marks: &[(usize, char, &str)],
+               expected: Option<(usize, String)>)
+               -> Vec<String> {
+        let mut shown = BTreeSet::new();
+        for &(line, _, _) in marks {
+            let first = cmp::max(line.saturating_sub(CONTEXT_LINES), 1);
+            let last = cmp::min(line + CONTEXT_LINES, self.lines.len());
+            shown.extend(first..last + 1);
+        }
+        let width = shown.iter().next_back().map_or(1, |n| n.to_string().len());
+
+        let mut notes = Vec::new();
+        let mut prev = None;
+        for &line in &shown {
+            if prev.map_or(false, |prev| prev + 1 != line) {
+                notes.push(format!("  {:>1$} |", "...", width));
+            }
+            prev = Some(line);
+            let text = self.lines[line - 1];
+            let here: Vec<_> = marks.iter().filter(|&&(l, _, _)| l == line).collect();
+            match here.last() {
+                Some(&&(_, mark, _)) => {
+                    let labels: Vec<_> = here.iter().map(|&&(_, _, label)| label).collect();
+                    notes.push(format!("{0} {1:>2$} | {3}    <- {4}",
+                                       mark,
+                                       line,
+                                       width,
+                                       text,
+                                       labels.join(", ")));
+                }
+                None => notes.push(format!("  {:>2$} | {}", line, text, width)),
+            }
+            if let Some(&(expected_line, ref expected)) = expected.as_ref() {
+                if expected_line == line {
+                    let indent = &text[..text.len() - text.trim_left().len()];
+                    notes.push(format!("+ {:>3$} | {}{}", "", indent, expected, width));
+                }
+            }
+        }
+        notes
+    }
+
+    fn fail(&mut self, directive: &Directive, message: String, notes: Vec<String>) {
+        let check = if directive.kind == Kind::Eof {
+            None
+        } else {
+            Some((directive.line, directive.source.clone()))
+        };
+        for piece in &directive.pieces {
+            if let Piece::Define(ref name, _) = *piece {
+                self.lost_vars.insert(name.clone());
+            }
+        }
+        self.failures.push(Failure {
+            check: check,
+            message: message,
+            notes: notes,
+        });
+    }
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..b.len() + 1).collect();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if ca == cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cmp::min(cost, cmp::min(row[j], row[j + 1]) + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::ffi::OsString;
+    use std::fs::{self, File};
+    use std::io::prelude::*;
+    use std::process::{Command, Stdio};
+
+    use super::{check_str, parse_pattern, Piece};
+
+    fn failures(checks: &str, input: &str) -> Vec<String> {
+        check_str(checks, input).into_iter().map(|f| f.message).collect()
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(parse_pattern("store i32 %{{[0-9]+}}, [[PTR:i32\\*[[:space:]]]]").unwrap(),
+                   vec![Piece::Literal("store i32 %".to_owned()),
+                        Piece::Regex("[0-9]+".to_owned()),
+                        Piece::Literal(", ".to_owned()),
+                        Piece::Define("PTR".to_owned(), "i32\\*[[:space:]]".to_owned())]);
+        assert_eq!(parse_pattern("[[@LINE+1]] [[X]]").unwrap(),
+                   vec![Piece::Line(1),
+                        Piece::Literal(" ".to_owned()),
+                        Piece::Use("X".to_owned())]);
+        assert!(parse_pattern("{{a").is_err());
+        assert!(parse_pattern("[[1X]]").is_err());
+    }
+
+    #[test]
+    fn directives() {
+        let input = "define void @f() {\n  %x = alloca i32\n  store i32 1, i32* %x\n}\n\
+                     define void @g() {\n  ret void\n}\n";
+        let ok = |checks: &str| assert!(failures(checks, input).is_empty(), "{}", checks);
+        let err = |checks: &str, msg: &str| {
+            assert_eq!(failures(checks, input), vec![msg.to_owned()], "{}", checks)
+        };
+
+        ok("// CHECK: @f\n// CHECK-NEXT: alloca\n// CHECK-SAME: i32\n// CHECK: }");
+        ok("// CHECK: [[X:%[a-z]+]] = alloca\n// CHECK: store i32 {{[0-9]}}, i32* [[X]]");
+        ok("// CHECK-LABEL: @f\n// CHECK-NOT: ret\n// CHECK: }\n// CHECK-LABEL: @g\n// CHECK: ret");
+        ok("// CHECK-DAG: store\n// CHECK-DAG: alloca\n// CHECK: }");
+        ok("// CHECK-DAG: define void\n// CHECK-DAG: define void\n// CHECK: ret");
+        ok("// CHECK:    store   i32 1,\ti32*");
+        ok("// CHECK: store {{[^%]*}}%x\n// CHECK: {{^}}}{{$}}");
+        err("// CHECK: @f\n// CHECK-NEXT: store", "CHECK-NEXT: is not on the line after the \
+                                                   previous match");
+        err("// CHECK: alloca\n// CHECK-SAME: store", "CHECK-SAME: is not on the same line as \
+                                                       the previous match");
+        err("// CHECK: @f\n// CHECK-NOT: store\n// CHECK: }",
+            "CHECK-NOT: excluded string found in input");
+        err("// CHECK: @g\n// CHECK-NOT: ret", "CHECK-NOT: excluded string found in input");
+        err("// CHECK: store i32 2", "expected string not found in input");
+        err("// CHECK: @g{{[^%]*}}ret", "expected string not found in input");
+        err("// CHECK: }{{\\s*}}define void @g", "expected string not found in input");
+        err("// CHECK: alloca\n// CHECK-SAME: {{^}}i32", "expected string not found in input");
+        err("// CHECK-DAG: store\n// CHECK-DAG: store", "expected string not found in input");
+        err("// CHECK: [[Y]]", "uses undefined variable `Y`");
+        err("// CHECK-LABEL: @f\n// CHECK: [[Y:%[0-9]+]] = alloca\n// CHECK-LABEL: @g\n\
+             // CHECK: [[Y]]",
+            "expected string not found in input");
+        err("// CHECK-LABEL: @h\n// CHECK: ret", "expected string not found in input");
+        err("// CHECK-NEXT: @f", "found 'CHECK-NEXT' without previous 'CHECK: line");
+        err("// no directives", "no check strings found with prefix 'CHECK:'");
+    }
+
+    #[test]
+    fn line_start() {
+        let not_found = vec!["expected string not found in input".to_owned()];
+        // `^` matches where the search starts, right after the previous match
+        assert!(failures("// CHECK: a\n// CHECK-SAME: {{^}}b", "ab\n").is_empty());
+        assert_eq!(failures("// CHECK: a\n// CHECK-SAME: {{^}}c", "abc\n"), not_found);
+        assert_eq!(failures("// CHECK: a\n// CHECK-NEXT: {{^}}c", "a\nbc\n"), not_found);
+        assert!(failures("// CHECK: a\n// CHECK-NEXT: {{^}}b", "a\nb\n").is_empty());
+    }
+
+    /// Checks that LLVM's FileCheck, when it is given in `FILECHECK` or found
+    /// in the `PATH`, agrees on which of the directives match.
+    #[test]
+    fn same_as_llvm() {
+        let input = "define void @f() {\n  %x = alloca i32\n  store i32 1, i32* %x\n}\n\
+                     define void @g() {\n  ret void\n}\n";
+        let checks = [
+            "CHECK: @f\nCHECK-NEXT: alloca\nCHECK-SAME: i32\nCHECK: }",
+            "CHECK: @f\nCHECK-NEXT: store",
+            "CHECK: alloca\nCHECK-SAME: store",
+            "CHECK: alloca\nCHECK-SAME: {{^}} i32",
+            "CHECK: alloca\nCHECK-SAME: {{^}}i32",
+            "CHECK: @f\nCHECK: {{^}}define",
+            "CHECK: @f\nCHECK: {{^}}store",
+            "CHECK: [[X:%[a-z]+]] = alloca\nCHECK: store i32 {{[0-9]}}, i32* [[X]]",
+            "CHECK: [[X:%[a-z]+]] = alloca\nCHECK: store i32 [[X]]",
+            "CHECK-LABEL: @f\nCHECK-NOT: ret\nCHECK: }\nCHECK-LABEL: @g\nCHECK: ret",
+            "CHECK-LABEL: @f\nCHECK: ret\nCHECK-LABEL: @g",
+            "CHECK: @f\nCHECK-NOT: store\nCHECK: }",
+            "CHECK: @g\nCHECK-NOT: ret",
+            "CHECK-DAG: store\nCHECK-DAG: alloca\nCHECK: }",
+            "CHECK-DAG: store\nCHECK-DAG: store",
+            "CHECK-DAG: define void\nCHECK-DAG: define void\nCHECK: ret",
+            "CHECK-DAG: store\nCHECK-NOT: ret\nCHECK-DAG: alloca",
+            "CHECK:    store   i32 1,\ti32*",
+            "CHECK: store {{[^%]*}}%x\nCHECK: {{^}}}{{$}}",
+            "CHECK: @g{{[^%]*}}ret",
+            "CHECK: store i32 [[@LINE]]",
+        ];
+        let prog = env::var_os("FILECHECK").unwrap_or(OsString::from("FileCheck"));
+        let check_file = env::current_exe().unwrap().with_file_name("filecheck-checks.txt");
+        for checks in &checks {
+            File::create(&check_file).and_then(|mut f| f.write_all(checks.as_bytes())).unwrap();
+            let child = Command::new(&prog)
+                                .arg(&check_file)
+                                .stdin(Stdio::piped())
+                                .stdout(Stdio::null())
+                                .stderr(Stdio::null())
+                                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                // LLVM's FileCheck isn't available
+                Err(_) => break,
+            };
+            // it may exit without reading the input, on an invalid directive
+            let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+            let llvm = child.wait().unwrap().success();
+            assert_eq!(failures(checks, input).is_empty(), llvm, "{}", checks);
+        }
+        let _ = fs::remove_file(&check_file);
+    }
+}
@@ -236,6 +236,10 @@ pub struct TestProps {
     // The test must be compiled and run successfully. Only used in UI tests for
     // now.
     pub run_pass: bool,
+    // Rules (regex, replacement) applied to the stdout and stderr of UI tests
+    // before they are compared with the expected output.
+    pub normalize_stdout: Vec<(String, String)>,
+    pub normalize_stderr: Vec<(String, String)>,
 }
 
 impl TestProps {
@@ -262,6 +266,8 @@ impl TestProps {
             must_compile_successfully: false,
             check_test_line_numbers_match: false,
             run_pass: false,
+            normalize_stdout: vec![],
+            normalize_stderr: vec![],
         }
     }
 
@@ -376,6 +382,14 @@ impl TestProps {
             if !self.run_pass {
                 self.run_pass = config.parse_run_pass(ln);
             }
+
+            if let Some(rule) = config.parse_custom_normalization(ln, "normalize-stdout") {
+                self.normalize_stdout.push(rule);
+            }
+
+            if let Some(rule) = config.parse_custom_normalization(ln, "normalize-stderr") {
+                self.normalize_stderr.push(rule);
+            }
         });
 
         for key in vec!["RUST_TEST_NOCAPTURE", "RUST_TEST_THREADS"] {
@@ -497,6 +511,28 @@ impl Config {
         self.parse_name_directive(line, "run-pass")
     }
 
+    /// Parses `<prefix>-test: "REGEX" -> "REPLACEMENT"`, or the same with
+    /// `32bit` or `64bit` in place of `test` for rules which only apply to
+    /// targets with that pointer width.
+    fn parse_custom_normalization(&self, line: &str, prefix: &str) -> Option<(String, String)> {
+        let pointer_width = util::get_pointer_width(&self.target);
+        let applies = ["test", pointer_width].iter().any(|suffix| {
+            self.parse_name_directive(line, &format!("{}-{}", prefix, suffix))
+        });
+        if !applies {
+            return None;
+        }
+        match parse_normalization_rule(line) {
+            Some(rule) => Some(rule),
+            None => {
+                panic!("malformed normalization directive: expected \
+                        `{}-test: \"REGEX\" -> \"REPLACEMENT\"`, found `{}`",
+                       prefix,
+                       line)
+            }
+        }
+    }
+
     fn parse_env(&self, line: &str, name: &str) -> Option<(String, String)> {
         self.parse_name_value_directive(line, name).map(|nv| {
             // nv is either FOO or FOO=BAR
@@ -548,6 +584,37 @@ impl Config {
     }
 }
 
+/// Parses the `"REGEX" -> "REPLACEMENT"` following the colon of a
+/// normalization directive. Neither string can contain a `"`.
+fn parse_normalization_rule(line: &str) -> Option<(String, String)> {
+    fn quoted(s: &str) -> Option<(&str, &str)> {
+        let s = s.trim_left();
+        if !s.starts_with('"') {
+            return None;
+        }
+        s[1..].find('"').map(|end| (&s[1..end + 1], &s[end + 2..]))
+    }
+
+    let colon = match line.find(':') {
+        Some(colon) => colon,
+        None => return None,
+    };
+    let (regex, rest) = match quoted(&line[colon + 1..]) {
+        Some(parts) => parts,
+        None => return None,
+    };
+    let rest = rest.trim_left();
+    if !rest.starts_with("->") {
+        return None;
+    }
+    match quoted(&rest[2..]) {
+        Some((replacement, rest)) if rest.trim().is_empty() => {
+            Some((regex.to_owned(), replacement.to_owned()))
+        }
+        _ => None,
+    }
+}
+
 pub fn lldb_version_to_int(version_string: &str) -> isize {
     let error_string = format!("Encountered LLDB version string with unexpected format: {}",
                                version_string);
@@ -576,3 +643,19 @@ fn expand_variables(mut value: String, config: &Config) -> String {
 
     value
 }
+
+#[test]
+fn test_parse_normalization_rule() {
+    let rule = |a: &str, b: &str| Some((a.to_owned(), b.to_owned()));
+
+    assert_eq!(parse_normalization_rule(r#"normalize-stderr-test: "a\d+" -> "b""#),
+               rule(r"a\d+", "b"));
+    assert_eq!(parse_normalization_rule(r#"normalize-stderr-32bit:"0x[0-9a-f]+"->"$$PTR""#),
+               rule("0x[0-9a-f]+", "$$PTR"));
+    assert_eq!(parse_normalization_rule(r#"normalize-stdout-test: "x" -> "" "#),
+               rule("x", ""));
+    assert_eq!(parse_normalization_rule(r#"normalize-stdout-test: "a" "b""#), None);
+    assert_eq!(parse_normalization_rule(r#"normalize-stdout-test: "a" -> "b" c"#), None);
+    assert_eq!(parse_normalization_rule(r#"normalize-stdout-test: "a -> b"#), None);
+    assert_eq!(parse_normalization_rule("normalize-stdout-test"), None);
+}
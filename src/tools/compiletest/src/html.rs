@@ -0,0 +1,630 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A small HTML parser and the subset of XPath used by rustdoc tests.
+//!
+//! The parser only has to deal with the fairly regular HTML rustdoc emits,
+//! so it doesn't implement the HTML5 tree construction rules: void elements
+//! and `<foo/>` never have children, an end tag closes the innermost open
+//! element of that name, and stray end tags are ignored.
+//!
+//! The supported XPath expressions are made of steps separated by `/` or
+//! `//`, and must start with one of them. A step is `tag`, `*`, `.` or `..`,
+//! optionally followed by predicates: `[@attr]`, `[@attr="value"]`, `[tag]`
+//! (has a child element `tag`), `[N]` (the Nth of the matching siblings),
+//! `[last()]` and `[last()-N]`. The last step may also be `@attr` to select
+//! the values of an attribute, or `text()`.
+
+use std::fmt::Write;
+
+pub type NodeId = usize;
+
+/// The document node, which is the parent of the top-level elements.
+pub const ROOT: NodeId = 0;
+
+// "void elements" (no closing tag) from the HTML Standard section 12.1.2
+const VOID_ELEMENTS: &'static [&'static str] = &["area", "base", "br", "col", "embed", "hr",
+                                                  "img", "input", "keygen", "link", "menuitem",
+                                                  "meta", "param", "source", "track", "wbr"];
+
+// Elements whose contents are not parsed as HTML.
+const RAW_TEXT_ELEMENTS: &'static [&'static str] = &["script", "style"];
+
+const ENTITIES: &'static [(&'static str, &'static str)] = &[
+    ("amp", "&"), ("lt", "<"), ("gt", ">"), ("quot", "\""), ("apos", "'"),
+    // tests match against a plain space
+    ("nbsp", " "),
+    ("larrb", "\u{21e4}"), ("rarrb", "\u{21e5}"), ("larr", "\u{2190}"), ("rarr", "\u{2192}"),
+    ("uarr", "\u{2191}"), ("darr", "\u{2193}"), ("ndash", "\u{2013}"), ("mdash", "\u{2014}"),
+    ("hellip", "\u{2026}"), ("middot", "\u{b7}"), ("bull", "\u{2022}"), ("laquo", "\u{ab}"),
+    ("raquo", "\u{bb}"), ("lsquo", "\u{2018}"), ("rsquo", "\u{2019}"), ("ldquo", "\u{201c}"),
+    ("rdquo", "\u{201d}"), ("copy", "\u{a9}"), ("reg", "\u{ae}"), ("trade", "\u{2122}"),
+    ("times", "\u{d7}"), ("sect", "\u{a7}"), ("para", "\u{b6}"), ("dagger", "\u{2020}"),
+];
+
+pub enum NodeData {
+    Document,
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    Text(String),
+}
+
+pub struct Node {
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+    pub data: NodeData,
+    /// The line of the HTML file this node starts on.
+    pub line: usize,
+}
+
+/// A parsed HTML file. Nodes are stored in document order, so sorting a
+/// list of `NodeId`s puts it in document order as well.
+pub struct Document {
+    nodes: Vec<Node>,
+}
+
+impl Document {
+    pub fn parse(html: &str) -> Document {
+        Parser::new(html).parse()
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn name(&self, id: NodeId) -> Option<&str> {
+        match self.nodes[id].data {
+            NodeData::Element { ref name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn attr(&self, id: NodeId, attr: &str) -> Option<&str> {
+        match self.nodes[id].data {
+            NodeData::Element { ref attrs, .. } => {
+                attrs.iter().find(|&&(ref name, _)| name == attr).map(|&(_, ref value)| &value[..])
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the text of a node and all of its descendants.
+    pub fn text(&self, id: NodeId) -> String {
+        let mut text = String::new();
+        self.collect_text(id, &mut text);
+        text
+    }
+
+    fn collect_text(&self, id: NodeId, text: &mut String) {
+        match self.nodes[id].data {
+            NodeData::Text(ref s) => text.push_str(s),
+            _ => {
+                for &child in &self.nodes[id].children {
+                    self.collect_text(child, text);
+                }
+            }
+        }
+    }
+
+    /// Describes a node for error messages, as its start tag and line.
+    pub fn describe(&self, id: NodeId) -> String {
+        let node = &self.nodes[id];
+        match node.data {
+            NodeData::Document => "the document".to_owned(),
+            NodeData::Text(_) => format!("text on line {}", node.line),
+            NodeData::Element { ref name, ref attrs } => {
+                let mut tag = format!("<{}", name);
+                for &(ref name, ref value) in attrs {
+                    write!(tag, " {}=\"{}\"", name, value).unwrap();
+                }
+                write!(tag, "> on line {}", node.line).unwrap();
+                tag
+            }
+        }
+    }
+
+    fn element_children<'a>(&'a self, id: NodeId) -> Box<Iterator<Item = NodeId> + 'a> {
+        Box::new(self.nodes[id].children.iter().cloned().filter(move |&c| {
+            self.name(c).is_some()
+        }))
+    }
+
+    fn descendants_or_self(&self, id: NodeId, out: &mut Vec<NodeId>) {
+        out.push(id);
+        for &child in &self.nodes[id].children {
+            if self.name(child).is_some() {
+                self.descendants_or_self(child, out);
+            }
+        }
+    }
+
+    fn push(&mut self, parent: NodeId, data: NodeData, line: usize) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            parent: Some(parent),
+            children: Vec::new(),
+            data: data,
+            line: line,
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+}
+
+struct Parser<'a> {
+    html: &'a str,
+    pos: usize,
+    line: usize,
+    line_pos: usize,
+    doc: Document,
+    open: Vec<NodeId>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(html: &'a str) -> Parser<'a> {
+        Parser {
+            html: html,
+            pos: 0,
+            line: 1,
+            line_pos: 0,
+            doc: Document {
+                nodes: vec![Node {
+                    parent: None,
+                    children: Vec::new(),
+                    data: NodeData::Document,
+                    line: 1,
+                }],
+            },
+            open: vec![ROOT],
+        }
+    }
+
+    fn parse(mut self) -> Document {
+        while self.pos < self.html.len() {
+            let rest = &self.html[self.pos..];
+            if rest.starts_with("<!--") {
+                self.skip_past("-->");
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                self.skip_past(">");
+            } else if rest.starts_with("</") {
+                self.end_tag();
+            } else if rest.len() > 1 && rest.as_bytes()[0] == b'<' &&
+                      is_name_start(rest.as_bytes()[1]) {
+                self.start_tag();
+            } else {
+                self.text();
+            }
+        }
+        self.doc
+    }
+
+    fn current_line(&mut self) -> usize {
+        self.line += self.html[self.line_pos..self.pos].matches('\n').count();
+        self.line_pos = self.pos;
+        self.line
+    }
+
+    fn skip_past(&mut self, end: &str) {
+        self.pos = match self.html[self.pos..].find(end) {
+            Some(i) => self.pos + i + end.len(),
+            None => self.html.len(),
+        };
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.html[self.pos..];
+        self.pos += rest.len() - rest.trim_left().len();
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.html.as_bytes().get(self.pos).cloned()
+    }
+
+    fn read_while<F: Fn(u8) -> bool>(&mut self, f: F) -> &'a str {
+        let start = self.pos;
+        while self.peek().map_or(false, &f) {
+            self.pos += 1;
+        }
+        &self.html[start..self.pos]
+    }
+
+    fn text(&mut self) {
+        let line = self.current_line();
+        // A `<` which doesn't start markup is kept as text.
+        let start = self.pos;
+        let skip = if self.html[start..].starts_with('<') { 1 } else { 0 };
+        self.pos = match self.html[start + skip..].find('<') {
+            Some(i) => start + skip + i,
+            None => self.html.len(),
+        };
+        let text = decode_entities(&self.html[start..self.pos]);
+        let parent = *self.open.last().unwrap();
+        self.doc.push(parent, NodeData::Text(text), line);
+    }
+
+    fn end_tag(&mut self) {
+        self.pos += 2;
+        let name = self.read_while(|b| b != b'>' && !is_space(b)).to_lowercase();
+        self.skip_past(">");
+        let open = self.open.iter().rposition(|&id| self.doc.name(id) == Some(&name[..]));
+        if let Some(i) = open {
+            self.open.truncate(i);
+        }
+    }
+
+    fn start_tag(&mut self) {
+        let line = self.current_line();
+        self.pos += 1;
+        let name = self.read_while(|b| b != b'>' && b != b'/' && !is_space(b)).to_lowercase();
+        let mut attrs = Vec::new();
+        let mut self_closing = false;
+        loop {
+            self.skip_whitespace();
+            let rest = &self.html[self.pos..];
+            if rest.is_empty() {
+                break;
+            } else if rest.starts_with(">") {
+                self.pos += 1;
+                break;
+            } else if rest.starts_with("/>") {
+                self.pos += 2;
+                self_closing = true;
+                break;
+            } else if rest.starts_with("/") {
+                self.pos += 1;
+                continue;
+            }
+
+            let attr = self.read_while(|b| b != b'>' && b != b'=' && b != b'/' && !is_space(b))
+                           .to_lowercase();
+            self.skip_whitespace();
+            let mut value = String::new();
+            if self.peek() == Some(b'=') {
+                self.pos += 1;
+                self.skip_whitespace();
+                let raw = match self.peek() {
+                    Some(quote @ b'"') | Some(quote @ b'\'') => {
+                        self.pos += 1;
+                        let raw = self.read_while(|b| b != quote);
+                        if self.pos < self.html.len() {
+                            self.pos += 1;
+                        }
+                        raw
+                    }
+                    _ => self.read_while(|b| b != b'>' && !is_space(b)),
+                };
+                value = decode_entities(raw);
+            }
+            attrs.push((attr, value));
+        }
+
+        let parent = *self.open.last().unwrap();
+        let element = NodeData::Element {
+            name: name.clone(),
+            attrs: attrs,
+        };
+        let id = self.doc.push(parent, element, line);
+        if RAW_TEXT_ELEMENTS.contains(&&name[..]) {
+            let start = self.pos;
+            let end = format!("</{}", name);
+            let len = self.html[start..].find(&end[..]).unwrap_or(self.html.len() - start);
+            if len > 0 {
+                let text = self.html[start..start + len].to_owned();
+                self.doc.push(id, NodeData::Text(text), line);
+            }
+            self.pos = start + len;
+        } else if !self_closing && !VOID_ELEMENTS.contains(&&name[..]) {
+            self.open.push(id);
+        }
+    }
+}
+
+fn is_space(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\n' || b == b'\r' || b == b'\x0c'
+}
+
+fn is_name_start(b: u8) -> bool {
+    (b >= b'a' && b <= b'z') || (b >= b'A' && b <= b'Z')
+}
+
+/// Replaces character references. Unknown ones are left as they are.
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let name = &rest[1..semi];
+            let c = if name.starts_with("#x") || name.starts_with("#X") {
+                u32::from_str_radix(&name[2..], 16).ok().and_then(::std::char::from_u32)
+                                                      .map(|c| c.to_string())
+            } else if name.starts_with('#') {
+                name[1..].parse().ok().and_then(::std::char::from_u32).map(|c| c.to_string())
+            } else {
+                ENTITIES.iter().find(|&&(n, _)| n == name).map(|&(_, c)| c.to_owned())
+            };
+            c.map(|c| (c, semi + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push_str(&c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Axis {
+    Child,
+    Descendant,
+    SelfNode,
+    Parent,
+}
+
+enum Predicate {
+    HasAttr(String),
+    AttrEquals(String, String),
+    HasChild(String),
+    Position(usize),
+    /// `last()-N`
+    Last(usize),
+}
+
+struct Step {
+    /// The text of this step in the XPath, including its leading slashes.
+    source: String,
+    axis: Axis,
+    /// `None` matches any element.
+    name: Option<String>,
+    predicates: Vec<Predicate>,
+}
+
+/// What an XPath selects from the nodes matched by its steps.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Select {
+    Nodes,
+    Text,
+    Attr(String),
+}
+
+pub struct XPath {
+    steps: Vec<Step>,
+    pub select: Select,
+}
+
+impl XPath {
+    pub fn parse(path: &str) -> Result<XPath, String> {
+        let mut rest = if path.starts_with(".//") { &path[1..] } else { path };
+        if !rest.starts_with('/') {
+            return Err(format!("XPath `{}` must start with `/` or `//`", path));
+        }
+
+        let mut steps = Vec::new();
+        let mut select = Select::Nodes;
+        while !rest.is_empty() {
+            if select != Select::Nodes {
+                return Err(format!("`@attr` and `text()` must be at the end of `{}`", path));
+            }
+            let slashes = if rest.starts_with("//") { 2 } else { 1 };
+            let end = slashes + step_len(&rest[slashes..]);
+            let source = &rest[..end];
+            let step = &rest[slashes..end];
+            rest = &rest[end..];
+
+            if step.starts_with('@') && slashes == 1 && !steps.is_empty() {
+                select = Select::Attr(step[1..].to_owned());
+            } else if step == "text()" && slashes == 1 && !steps.is_empty() {
+                select = Select::Text;
+            } else {
+                steps.push(parse_step(step, slashes == 2, source).map_err(|e| {
+                    format!("invalid step `{}` in XPath `{}`: {}", step, path, e)
+                })?);
+            }
+        }
+        Ok(XPath { steps: steps, select: select })
+    }
+
+    /// Returns the nodes matched by the steps, in document order.
+    pub fn find(&self, doc: &Document) -> Vec<NodeId> {
+        self.steps.iter().fold(vec![ROOT], |nodes, step| step.apply(doc, &nodes))
+    }
+
+    /// For an XPath matching nothing, returns the longest leading part of it
+    /// which does match some nodes, along with those nodes.
+    pub fn longest_matching_prefix(&self, doc: &Document) -> Option<(String, Vec<NodeId>)> {
+        let mut nodes = vec![ROOT];
+        let mut prefix = String::new();
+        let mut longest = None;
+        for step in &self.steps {
+            nodes = step.apply(doc, &nodes);
+            if nodes.is_empty() {
+                break;
+            }
+            prefix.push_str(&step.source);
+            longest = Some((prefix.clone(), nodes.clone()));
+        }
+        longest
+    }
+}
+
+/// Returns the length of the step at the start of `s`, which ends at the next
+/// `/` outside of brackets and quotes.
+fn step_len(s: &str) -> usize {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            (None, '/') if depth == 0 => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn parse_step(step: &str, descendant: bool, source: &str) -> Result<Step, String> {
+    let name_end = step.find('[').unwrap_or(step.len());
+    let (name, mut rest) = step.split_at(name_end);
+    let (axis, name) = match name {
+        "." => (Axis::SelfNode, None),
+        ".." => (Axis::Parent, None),
+        // `a/[@href]` applies the predicate to `a` itself
+        "" if !descendant && !rest.is_empty() => (Axis::SelfNode, None),
+        "" => return Err("expected a tag name".to_owned()),
+        "*" => (if descendant { Axis::Descendant } else { Axis::Child }, None),
+        name if name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') => {
+            (if descendant { Axis::Descendant } else { Axis::Child },
+             Some(name.to_lowercase()))
+        }
+        _ => return Err("expected a tag name, `*`, `.` or `..`".to_owned()),
+    };
+    if descendant && (axis == Axis::SelfNode || axis == Axis::Parent) {
+        return Err("`.` and `..` cannot follow `//`".to_owned());
+    }
+
+    let mut predicates = Vec::new();
+    while !rest.is_empty() {
+        let len = predicate_len(rest).ok_or_else(|| "unclosed `[`".to_owned())?;
+        predicates.push(parse_predicate(&rest[1..len - 1])?);
+        rest = &rest[len..];
+    }
+    Ok(Step {
+        source: source.to_owned(),
+        axis: axis,
+        name: name,
+        predicates: predicates,
+    })
+}
+
+/// Returns the length of the `[...]` predicate at the start of `s`.
+fn predicate_len(s: &str) -> Option<usize> {
+    if !s.starts_with('[') {
+        return None;
+    }
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, ']') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_predicate(pred: &str) -> Result<Predicate, String> {
+    let pred = pred.trim();
+    if pred.starts_with('@') {
+        let (name, value) = match pred.find('=') {
+            Some(eq) => (pred[1..eq].trim(), Some(pred[eq + 1..].trim())),
+            None => (&pred[1..], None),
+        };
+        let value = match value {
+            Some(v) if v.len() >= 2 && ((v.starts_with('"') && v.ends_with('"')) ||
+                                        (v.starts_with('\'') && v.ends_with('\''))) => {
+                Some(v[1..v.len() - 1].to_owned())
+            }
+            Some(_) => return Err(format!("expected a quoted value in `[{}]`", pred)),
+            None => None,
+        };
+        Ok(match value {
+            Some(value) => Predicate::AttrEquals(name.to_owned(), value),
+            None => Predicate::HasAttr(name.to_owned()),
+        })
+    } else if pred == "last()" {
+        Ok(Predicate::Last(0))
+    } else if pred.starts_with("last()") && pred[6..].trim_left().starts_with('-') {
+        match pred[6..].trim_left()[1..].trim().parse() {
+            Ok(n) => Ok(Predicate::Last(n)),
+            Err(_) => Err(format!("invalid position in `[{}]`", pred)),
+        }
+    } else if let Ok(n) = pred.parse::<usize>() {
+        if n == 0 {
+            return Err("positions start at 1".to_owned());
+        }
+        Ok(Predicate::Position(n))
+    } else if !pred.is_empty() && pred.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        Ok(Predicate::HasChild(pred.to_lowercase()))
+    } else {
+        Err(format!("unsupported predicate `[{}]`", pred))
+    }
+}
+
+impl Step {
+    fn apply(&self, doc: &Document, nodes: &[NodeId]) -> Vec<NodeId> {
+        let mut out = Vec::new();
+        for &node in nodes {
+            match self.axis {
+                Axis::Child => self.filter(doc, doc.element_children(node).collect(), &mut out),
+                Axis::Descendant => {
+                    let mut parents = Vec::new();
+                    doc.descendants_or_self(node, &mut parents);
+                    for parent in parents {
+                        self.filter(doc, doc.element_children(parent).collect(), &mut out);
+                    }
+                }
+                Axis::SelfNode => self.filter(doc, vec![node], &mut out),
+                Axis::Parent => {
+                    if let Some(parent) = doc.node(node).parent {
+                        self.filter(doc, vec![parent], &mut out);
+                    }
+                }
+            }
+        }
+        out.sort();
+        out.dedup();
+        out
+    }
+
+    /// Filters a group of siblings by the name test and the predicates, in
+    /// order, so that positions are relative to what the previous
+    /// predicates kept.
+    fn filter(&self, doc: &Document, siblings: Vec<NodeId>, out: &mut Vec<NodeId>) {
+        let mut nodes: Vec<NodeId> = match self.name {
+            Some(ref name) => {
+                siblings.into_iter().filter(|&n| doc.name(n) == Some(&name[..])).collect()
+            }
+            None => siblings,
+        };
+        for pred in &self.predicates {
+            let len = nodes.len();
+            nodes = nodes.into_iter().enumerate().filter(|&(i, node)| {
+                match *pred {
+                    Predicate::HasAttr(ref attr) => doc.attr(node, attr).is_some(),
+                    Predicate::AttrEquals(ref attr, ref value) => {
+                        doc.attr(node, attr) == Some(&value[..])
+                    }
+                    Predicate::HasChild(ref name) => {
+                        doc.element_children(node).any(|c| doc.name(c) == Some(&name[..]))
+                    }
+                    Predicate::Position(n) => i + 1 == n,
+                    Predicate::Last(n) => i + n + 1 == len,
+                }
+            }).map(|(_, node)| node).collect();
+        }
+        out.extend(nodes);
+    }
+}
@@ -0,0 +1,616 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checks the HTML generated for rustdoc tests against the commands in the
+//! test file. This follows `src/etc/htmldocck.py`, which describes the
+//! commands in more detail:
+//!
+//! * `@has PATH` checks that the file `PATH` (relative to the output
+//!   directory, or `-` for the previous one) exists.
+//! * `@has PATH PATTERN` checks that the file contains `PATTERN`, with all
+//!   whitespace normalized, and `@matches PATH REGEX` that the regex matches
+//!   somewhere in it.
+//! * `@has PATH XPATH PATTERN` and `@matches PATH XPATH REGEX` check that
+//!   the text (or attribute value) of one of the nodes selected by `XPATH`
+//!   matches. An empty pattern only checks that some node is selected.
+//! * `@count PATH XPATH COUNT` checks how many nodes `XPATH` selects.
+//!
+//! Any command can be negated with `!`, as in `@!has`, and arguments can be
+//! quoted like in a shell. A line ending in `\` is continued on the next
+//! line, without the prefix it shares with the first one.
+//!
+//! When an XPath check fails, the nodes that came closest to matching are
+//! listed: the ones the XPath selected, or if it selected nothing, the ones
+//! selected by the longest part of it which does match.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+
+use html::{Document, NodeId, Select, XPath};
+
+/// How many of the closest nodes are listed for a failed check.
+const MAX_CLOSEST_NODES: usize = 5;
+
+/// How much of the text of a node is shown when listing it.
+const MAX_TEXT_LEN: usize = 80;
+
+struct Command {
+    negated: bool,
+    name: String,
+    args: Vec<String>,
+    line: usize,
+    context: String,
+}
+
+/// A command which failed or couldn't be run.
+pub struct CheckError {
+    line: usize,
+    context: String,
+    message: String,
+    notes: Vec<String>,
+}
+
+impl CheckError {
+    fn new(command: &Command, message: String) -> CheckError {
+        CheckError {
+            line: command.line,
+            context: command.context.clone(),
+            message: message,
+            notes: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}\n    {}", self.line, self.message, self.context.trim())?;
+        for note in &self.notes {
+            write!(f, "\n    {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the commands in `template` against the documentation in `doc_dir`,
+/// returning the ones which failed.
+pub fn check(doc_dir: &Path, template: &Path) -> Vec<CheckError> {
+    let mut contents = String::new();
+    if let Err(e) = File::open(template).and_then(|mut f| f.read_to_string(&mut contents)) {
+        return vec![CheckError {
+            line: 0,
+            context: String::new(),
+            message: format!("failed to read {}: {}", template.display(), e),
+            notes: Vec::new(),
+        }];
+    }
+
+    let mut errors = Vec::new();
+    let mut cache = CachedFiles {
+        root: doc_dir.to_path_buf(),
+        files: HashMap::new(),
+        trees: HashMap::new(),
+        last_path: None,
+    };
+    for command in get_commands(&contents, &mut errors) {
+        if let Err(e) = check_command(&command, &mut cache) {
+            errors.push(e);
+        }
+    }
+    errors
+}
+
+/// Joins lines ending in `\` with the following ones, returning each
+/// resulting line with the number of its first line.
+fn concat_multi_lines(contents: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut first: Option<(usize, &str)> = None;
+    let mut catenated = String::new();
+    for (i, mut line) in contents.lines().enumerate() {
+        // strip the prefix shared with the first line of the continuation
+        if let Some((_, first_line)) = first {
+            let prefix = line.char_indices()
+                             .zip(first_line.chars())
+                             .find(|&((_, a), b)| a != b)
+                             .map(|((i, _), _)| i)
+                             .unwrap_or(cmp::min(line.len(), first_line.len()));
+            line = line[prefix..].trim_left();
+        }
+        if line.ends_with('\\') {
+            let line = &line[..line.len() - 1];
+            if first.is_none() {
+                first = Some((i + 1, line));
+            }
+            catenated.push_str(line);
+        } else {
+            catenated.push_str(line);
+            let lineno = first.map_or(i + 1, |(lineno, _)| lineno);
+            lines.push((lineno, catenated));
+            first = None;
+            catenated = String::new();
+        }
+    }
+    if let Some((lineno, _)) = first {
+        lines.push((lineno, catenated));
+    }
+    lines
+}
+
+fn get_commands(contents: &str, errors: &mut Vec<CheckError>) -> Vec<Command> {
+    let mut commands = Vec::new();
+    for (lineno, line) in concat_multi_lines(contents) {
+        let (negated, name, args) = match find_command(&line) {
+            Some(command) => command,
+            None => continue,
+        };
+        let mut command = Command {
+            negated: negated,
+            name: name.to_owned(),
+            args: Vec::new(),
+            line: lineno,
+            context: line.clone(),
+        };
+        if !args.is_empty() && !args.starts_with(char::is_whitespace) {
+            errors.push(CheckError::new(&command, "invalid template syntax".to_owned()));
+            continue;
+        }
+        match split_args(args) {
+            Ok(args) => command.args = args,
+            Err(e) => {
+                errors.push(CheckError::new(&command, e));
+                continue;
+            }
+        }
+        commands.push(command);
+    }
+    commands
+}
+
+/// Finds an `@` which isn't preceded by something other than whitespace
+/// and is followed by a command name, returning whether the command is
+/// negated, its name and the rest of the line.
+fn find_command(line: &str) -> Option<(bool, &str, &str)> {
+    for (at, _) in line.match_indices('@') {
+        if !line[..at].chars().next_back().map_or(true, char::is_whitespace) {
+            continue;
+        }
+        let rest = &line[at + 1..];
+        let (negated, rest) = if rest.starts_with('!') {
+            (true, &rest[1..])
+        } else {
+            (false, rest)
+        };
+
+        // letters, optionally separated by single hyphens
+        let bytes = rest.as_bytes();
+        let is_letter = |i: usize| {
+            i < bytes.len() && ((bytes[i] >= b'a' && bytes[i] <= b'z') ||
+                                (bytes[i] >= b'A' && bytes[i] <= b'Z'))
+        };
+        let mut len = 0;
+        while is_letter(len) {
+            len += 1;
+            if !is_letter(len) && bytes.get(len) == Some(&b'-') && is_letter(len + 1) {
+                len += 1;
+            }
+        }
+        if len > 0 {
+            return Some((negated, &rest[..len], &rest[len..]));
+        }
+    }
+    None
+}
+
+/// Splits arguments like a POSIX shell: they are separated by whitespace
+/// and may be quoted with `'` or `"`. A backslash escapes the next character
+/// outside of quotes, and `"` or `\` inside double quotes.
+fn split_args(args: &str) -> Result<Vec<String>, String> {
+    let mut result = Vec::new();
+    let mut arg = String::new();
+    let mut in_arg = false;
+    let mut chars = args.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_arg {
+                    result.push(arg);
+                    arg = String::new();
+                    in_arg = false;
+                }
+            }
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err("no closing quotation".to_owned()),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            match chars.next() {
+                                Some(c) if c == '"' || c == '\\' => arg.push(c),
+                                Some(c) => {
+                                    arg.push('\\');
+                                    arg.push(c);
+                                }
+                                None => return Err("no closing quotation".to_owned()),
+                            }
+                        }
+                        Some(c) => arg.push(c),
+                        None => return Err("no closing quotation".to_owned()),
+                    }
+                }
+            }
+            '\\' => {
+                in_arg = true;
+                match chars.next() {
+                    Some(c) => arg.push(c),
+                    None => return Err("no escaped character".to_owned()),
+                }
+            }
+            c => {
+                in_arg = true;
+                arg.push(c);
+            }
+        }
+    }
+    if in_arg {
+        result.push(arg);
+    }
+    Ok(result)
+}
+
+struct CachedFiles {
+    root: PathBuf,
+    files: HashMap<PathBuf, String>,
+    trees: HashMap<PathBuf, Document>,
+    last_path: Option<PathBuf>,
+}
+
+impl CachedFiles {
+    fn resolve_path(&mut self, path: &str) -> Result<PathBuf, String> {
+        if path != "-" {
+            let path = normalize_path(path);
+            self.last_path = Some(path.clone());
+            Ok(path)
+        } else {
+            self.last_path.clone().ok_or_else(|| {
+                "tried to use the previous path in the first command".to_owned()
+            })
+        }
+    }
+
+    /// Returns the contents of a file, or `None` if it doesn't exist.
+    fn get_file(&mut self, path: &str) -> Result<Option<&str>, String> {
+        let path = self.resolve_path(path)?;
+        if !self.files.contains_key(&path) {
+            let abspath = self.root.join(&path);
+            if !abspath.is_file() {
+                return Ok(None);
+            }
+            let mut contents = String::new();
+            File::open(&abspath).and_then(|mut f| f.read_to_string(&mut contents)).map_err(|e| {
+                format!("failed to read {}: {}", abspath.display(), e)
+            })?;
+            self.files.insert(path.clone(), contents);
+        }
+        Ok(Some(&self.files[&path]))
+    }
+
+    fn get_tree(&mut self, path: &str) -> Result<&Document, String> {
+        let resolved = self.resolve_path(path)?;
+        if !self.trees.contains_key(&resolved) {
+            let tree = match self.get_file(path)? {
+                Some(contents) => Document::parse(contents),
+                None => return Err(format!("file `{}` does not exist", resolved.display())),
+            };
+            self.trees.insert(resolved.clone(), tree);
+        }
+        Ok(&self.trees[&resolved])
+    }
+}
+
+fn normalize_path(path: &str) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            c => normalized.push(c.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Runs a single command, returning an error if it failed.
+fn check_command(command: &Command, cache: &mut CachedFiles) -> Result<(), CheckError> {
+    let args = &command.args;
+    let invalid = |msg: String| CheckError::new(command, msg);
+    let regex = command.name == "matches";
+    let outcome = match (&command.name[..], args.len()) {
+        ("has", 1) => {
+            let exists = cache.get_file(&args[0]).map_err(&invalid)?.is_some();
+            Outcome::file(exists, &args[0])
+        }
+        ("has", 2) | ("matches", 2) => {
+            let pattern = Pattern::new(&args[1], regex).map_err(&invalid)?;
+            // A missing file is an error even for negated commands, which
+            // would otherwise pass without checking anything.
+            match cache.get_file(&args[0]).map_err(&invalid)? {
+                Some(contents) => Outcome::string(pattern.is_match(contents)),
+                None => return Err(invalid(format!("file `{}` does not exist", args[0]))),
+            }
+        }
+        ("has", 3) | ("matches", 3) => {
+            let pattern = Pattern::new(&args[2], regex).map_err(&invalid)?;
+            let xpath = XPath::parse(&args[1]).map_err(&invalid)?;
+            let tree = cache.get_tree(&args[0]).map_err(&invalid)?;
+            check_tree(tree, &xpath, &args[1], &pattern)
+        }
+        ("count", 3) => {
+            let expected = args[2].parse::<usize>().map_err(|_| {
+                invalid(format!("invalid count `{}`", args[2]))
+            })?;
+            let xpath = XPath::parse(&args[1]).map_err(&invalid)?;
+            let tree = cache.get_tree(&args[0]).map_err(&invalid)?;
+            let found = select(tree, &xpath).len();
+            let mut outcome = Outcome {
+                matched: expected == found,
+                message: format!("expected {} occurrences but found {}", expected, found),
+                notes: Vec::new(),
+            };
+            if found == 0 {
+                outcome.notes = closest_nodes(tree, &xpath, &args[1], &[]);
+            }
+            outcome
+        }
+        ("has", _) | ("matches", _) | ("count", _) => {
+            return Err(invalid(format!("invalid number of @{} arguments", command.name)));
+        }
+        ("valid-html", _) | ("valid-links", _) => {
+            return Err(invalid(format!("unimplemented @{}", command.name)));
+        }
+        _ => return Err(invalid(format!("unrecognized @{}", command.name))),
+    };
+
+    if outcome.matched != command.negated {
+        return Ok(());
+    }
+    let mut error = CheckError::new(command,
+                                    format!("@{}{} check failed: {}",
+                                            if command.negated { "!" } else { "" },
+                                            command.name,
+                                            outcome.message));
+    error.notes = outcome.notes;
+    Err(error)
+}
+
+/// The result of a command before negation, with a description of what
+/// went wrong if the command wasn't negated.
+struct Outcome {
+    matched: bool,
+    message: String,
+    notes: Vec<String>,
+}
+
+impl Outcome {
+    fn file(exists: bool, path: &str) -> Outcome {
+        Outcome {
+            matched: exists,
+            message: format!("file `{}` {}",
+                             path,
+                             if exists { "exists" } else { "does not exist" }),
+            notes: Vec::new(),
+        }
+    }
+
+    fn string(matched: bool) -> Outcome {
+        Outcome {
+            matched: matched,
+            message: format!("`PATTERN` {}", if matched { "matched" } else { "did not match" }),
+            notes: Vec::new(),
+        }
+    }
+}
+
+enum Pattern {
+    /// A string matched with all whitespace normalized; empty strings
+    /// match anything.
+    Text(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn new(pattern: &str, regex: bool) -> Result<Pattern, String> {
+        if regex {
+            Regex::new(pattern).map(Pattern::Regex).map_err(|e| {
+                format!("invalid regex `{}`: {}", pattern, e)
+            })
+        } else {
+            Ok(Pattern::Text(normalize_whitespace(pattern)))
+        }
+    }
+
+    fn is_match(&self, s: &str) -> bool {
+        match *self {
+            Pattern::Text(ref pattern) => {
+                pattern.is_empty() || normalize_whitespace(s).contains(&pattern[..])
+            }
+            Pattern::Regex(ref regex) => regex.is_match(s),
+        }
+    }
+}
+
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns the nodes selected by an XPath along with their value: their
+/// text, or the value of the selected attribute for the nodes which have it.
+fn select(tree: &Document, xpath: &XPath) -> Vec<(NodeId, String)> {
+    xpath.find(tree).into_iter().filter_map(|node| {
+        match xpath.select {
+            Select::Nodes | Select::Text => Some((node, tree.text(node))),
+            Select::Attr(ref attr) => tree.attr(node, attr).map(|v| (node, v.to_owned())),
+        }
+    }).collect()
+}
+
+fn check_tree(tree: &Document, xpath: &XPath, source: &str, pattern: &Pattern) -> Outcome {
+    let selected = select(tree, xpath);
+    if let Some(&(node, ref value)) = selected.iter().find(|&&(_, ref v)| pattern.is_match(v)) {
+        return Outcome {
+            matched: true,
+            message: "`XPATH PATTERN` matched".to_owned(),
+            notes: vec![format!("matched {}: {}", tree.describe(node), excerpt(value))],
+        };
+    }
+    let notes = closest_nodes(tree, xpath, source, &selected);
+    Outcome {
+        matched: false,
+        message: "`XPATH PATTERN` did not match".to_owned(),
+        notes: notes,
+    }
+}
+
+/// Describes the nodes closest to matching: the ones that were selected,
+/// or the ones matched by the longest part of the XPath that matches.
+fn closest_nodes(tree: &Document,
+                 xpath: &XPath,
+                 source: &str,
+                 selected: &[(NodeId, String)])
+                 -> Vec<String> {
+    let mut notes = Vec::new();
+    let nodes: Vec<(NodeId, String)> = if !selected.is_empty() {
+        notes.push(format!("`{}` selected {} node(s), none of which matched:",
+                           source,
+                           selected.len()));
+        selected.to_vec()
+    } else {
+        match xpath.longest_matching_prefix(tree) {
+            Some((prefix, nodes)) => {
+                notes.push(format!("`{}` selected nothing, but `{}` selected {} node(s):",
+                                   source,
+                                   prefix,
+                                   nodes.len()));
+                nodes.into_iter().map(|n| (n, tree.text(n))).collect()
+            }
+            None => {
+                notes.push(format!("no part of `{}` selected anything", source));
+                Vec::new()
+            }
+        }
+    };
+    for &(node, ref value) in nodes.iter().take(MAX_CLOSEST_NODES) {
+        notes.push(format!("    {}: {}", tree.describe(node), excerpt(value)));
+    }
+    if nodes.len() > MAX_CLOSEST_NODES {
+        notes.push(format!("    and {} more", nodes.len() - MAX_CLOSEST_NODES));
+    }
+    notes
+}
+
+fn excerpt(value: &str) -> String {
+    let value = normalize_whitespace(value);
+    match value.char_indices().nth(MAX_TEXT_LEN) {
+        Some((end, _)) => format!("`{}...`", &value[..end]),
+        None => format!("`{}`", value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+
+    use super::{check, concat_multi_lines, find_command, split_args};
+    use html::{Document, XPath};
+
+    fn select(html: &str, xpath: &str) -> Vec<String> {
+        let doc = Document::parse(html);
+        let xpath = XPath::parse(xpath).unwrap();
+        super::select(&doc, &xpath).into_iter().map(|(_, value)| value).collect()
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(find_command("// @has foo/index.html"),
+                   Some((false, "has", " foo/index.html")));
+        assert_eq!(find_command("// @!matches - 'a'"), Some((true, "matches", " - 'a'")));
+        assert_eq!(find_command("// @valid-html"), Some((false, "valid-html", "")));
+        assert_eq!(find_command("//@has bar"), None);
+        assert_eq!(find_command("// foo@has bar"), None);
+        assert_eq!(find_command("// email@example.com"), None);
+
+        assert_eq!(split_args(r#" foo '//*[@class="a b"]' "x \"y\"" '' "#).unwrap(),
+                   vec!["foo", r#"//*[@class="a b"]"#, r#"x "y""#, ""]);
+        assert!(split_args(" 'foo").is_err());
+
+        let lines = concat_multi_lines("a\n// @has x \\\n//     'y' \\\n//     'z'\nb");
+        assert_eq!(lines[1], (2, "// @has x 'y' 'z'".to_owned()));
+        assert_eq!(lines[2], (5, "b".to_owned()));
+    }
+
+    #[test]
+    fn xpath() {
+        let html = r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>t</title></head>
+<body><div class="impl" id="a"><code>impl <a href="x.html">X</a></code><br></div>
+<div class="impl"><code>A &amp; B&nbsp;&#60;&#x3e;</code><p>one</p><p>two</p></div>
+<ul><li>1</li><li>2</li><li>3</li></ul></body></html>"#;
+        assert_eq!(select(html, "//title"), vec!["t"]);
+        assert_eq!(select(html, "//*[@class=\"impl\"]//code"), vec!["impl X", "A & B <>"]);
+        assert_eq!(select(html, "//div[@id='a']/code/a/@href"), vec!["x.html"]);
+        assert_eq!(select(html, "//a/[@href='x.html']"), vec!["X"]);
+        assert_eq!(select(html, "//div[p]/p[last()]/text()"), vec!["two"]);
+        assert_eq!(select(html, "//li[1]"), vec!["1"]);
+        assert_eq!(select(html, "//li[last()-1]"), vec!["2"]);
+        assert_eq!(select(html, "//a/../.."), vec!["impl X"]);
+        assert_eq!(select(html, "//div[@class='impl'][2]/p"), vec!["one", "two"]);
+        assert!(select(html, "//div[@class='trait']").is_empty());
+        assert!(XPath::parse("div").is_err());
+        assert!(XPath::parse("//div[").is_err());
+        assert!(XPath::parse("//@class/div").is_err());
+    }
+
+    #[test]
+    fn missing_files() {
+        let dir = env::temp_dir().join("htmldocck-missing-files");
+        fs::create_dir_all(&dir).unwrap();
+        let template = dir.join("template.rs");
+        File::create(&template).unwrap().write_all(b"\
+// @!has missing.html
+// @!has missing.html 'pattern'
+// @!matches missing.html 'pattern'
+").unwrap();
+
+        let errors = check(&dir, &template);
+        fs::remove_dir_all(&dir).unwrap();
+        let lines = errors.iter().map(|e| e.line).collect::<Vec<_>>();
+        assert_eq!(lines, [2, 3]);
+        assert!(errors.iter().all(|e| e.message == "file `missing.html` does not exist"));
+    }
+}
@@ -36,6 +36,7 @@ struct DiagnosticSpan {
     column_end: usize,
     is_primary: bool,
     label: Option<String>,
+    suggested_replacement: Option<String>,
     expansion: Option<Box<DiagnosticSpanMacroExpansion>>,
 }
 
@@ -163,8 +164,10 @@ fn push_expected_errors(expected_errors: &mut Vec<Error>,
         }
     }
 
-    // If the message has a suggestion, register that.
-    if let Some(ref rendered) = diagnostic.rendered {
+    // If the message has a suggestion, register that. The other messages are
+    // rendered too, but as a whole rather than as the suggested code.
+    let has_suggestion = diagnostic.spans.iter().any(|s| s.suggested_replacement.is_some());
+    if let (true, Some(rendered)) = (has_suggestion, diagnostic.rendered.as_ref()) {
         let start_line = primary_spans.iter().map(|s| s.line_start).min().expect("\
             every suggestion should have at least one span");
         for (index, line) in rendered.lines().enumerate() {
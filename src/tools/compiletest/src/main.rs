@@ -26,6 +26,7 @@ extern crate log;
 extern crate env_logger;
 extern crate filetime;
 extern crate diff;
+extern crate regex;
 
 use std::env;
 use std::ffi::OsString;
@@ -37,7 +38,7 @@ use filetime::FileTime;
 use getopts::{optopt, optflag, reqopt};
 use common::Config;
 use common::{Pretty, DebugInfoGdb, DebugInfoLldb, Mode};
-use test::{TestPaths, ColorConfig};
+use test::{TestPaths, ColorConfig, OutputFormat};
 use util::logv;
 
 use self::header::EarlyProps;
@@ -49,7 +50,11 @@ pub mod header;
 pub mod runtest;
 pub mod common;
 pub mod errors;
+mod filecheck;
+mod html;
+mod htmldocck;
 mod raise_fd_limit;
+mod report;
 
 fn main() {
     env_logger::init().unwrap();
@@ -75,7 +80,8 @@ pub fn parse_config(args: Vec<String> ) -> Config {
           reqopt("", "docck-python", "path to python to use for doc tests", "PATH"),
           optopt("", "valgrind-path", "path to Valgrind executable for Valgrind tests", "PROGRAM"),
           optflag("", "force-valgrind", "fail if Valgrind tests cannot be run under Valgrind"),
-          optopt("", "llvm-filecheck", "path to LLVM's FileCheck binary", "DIR"),
+          optopt("", "llvm-filecheck", "path to LLVM's FileCheck binary, used for codegen tests \
+                                        instead of the built-in FileCheck", "DIR"),
           reqopt("", "src-base", "directory to scan for test files", "PATH"),
           reqopt("", "build-base", "directory to deposit test outputs", "PATH"),
           reqopt("", "stage-id", "the target-stage identifier", "stageN-TARGET"),
@@ -84,14 +90,19 @@ pub fn parse_config(args: Vec<String> ) -> Config {
                   run-pass-valgrind|pretty|debug-info|incremental|mir-opt)"),
           optflag("", "ignored", "run tests marked as ignored"),
           optflag("", "exact", "filters match exactly"),
+          optflag("", "bless", "overwrite the expected output of ui and mir-opt tests \
+                                with the actual output"),
           optopt("", "runtool", "supervisor program to run tests under \
                                  (eg. emulator, valgrind)", "PROGRAM"),
           optopt("", "host-rustcflags", "flags to pass to rustc for host", "FLAGS"),
           optopt("", "target-rustcflags", "flags to pass to rustc for target", "FLAGS"),
           optflag("", "verbose", "run tests verbosely, showing all output"),
           optflag("", "quiet", "print one character per test instead of one line"),
+          optopt("", "format", "output format: pretty, terse, json", "FORMAT"),
           optopt("", "color", "coloring: auto, always, never", "WHEN"),
           optopt("", "logfile", "file to log test execution to", "FILE"),
+          optopt("", "report-dir", "directory to write a JSON report of every test to",
+                 "PATH"),
           optopt("", "target", "the target to build for", "TARGET"),
           optopt("", "host", "the host to build for", "HOST"),
           optopt("", "gdb", "path to GDB to use for GDB debuginfo tests", "PATH"),
@@ -155,6 +166,15 @@ pub fn parse_config(args: Vec<String> ) -> Config {
         Some(x) => panic!("argument for --color must be auto, always, or never, but found `{}`", x),
     };
 
+    let format = match matches.opt_str("format").as_ref().map(|x| &**x) {
+        None if matches.opt_present("quiet") => OutputFormat::Terse,
+        Some("pretty") | None => OutputFormat::Pretty,
+        Some("terse") => OutputFormat::Terse,
+        Some("json") => OutputFormat::Json,
+        Some(x) => panic!("argument for --format must be pretty, terse, or json, but found `{}`",
+                          x),
+    };
+
     Config {
         compile_lib_path: make_absolute(opt_path(matches, "compile-lib-path")),
         run_lib_path: make_absolute(opt_path(matches, "run-lib-path")),
@@ -172,7 +192,9 @@ pub fn parse_config(args: Vec<String> ) -> Config {
         run_ignored: matches.opt_present("ignored"),
         filter: matches.free.first().cloned(),
         filter_exact: matches.opt_present("exact"),
+        bless: matches.opt_present("bless"),
         logfile: matches.opt_str("logfile").map(|s| PathBuf::from(&s)),
+        report_dir: matches.opt_str("report-dir").map(|s| make_absolute(PathBuf::from(&s))),
         runtool: matches.opt_str("runtool"),
         host_rustcflags: matches.opt_str("host-rustcflags"),
         target_rustcflags: matches.opt_str("target-rustcflags"),
@@ -192,7 +214,7 @@ pub fn parse_config(args: Vec<String> ) -> Config {
             !opt_str2(matches.opt_str("adb-test-dir")).is_empty(),
         lldb_python_dir: matches.opt_str("lldb-python-dir"),
         verbose: matches.opt_present("verbose"),
-        quiet: matches.opt_present("quiet"),
+        format: format,
         color: color,
         remote_test_client: matches.opt_str("remote-test-client").map(PathBuf::from),
 
@@ -222,7 +244,9 @@ pub fn log_config(config: &Config) {
                                    .as_ref()
                                    .map(|re| re.to_owned()))));
     logv(c, format!("filter_exact: {}", config.filter_exact));
+    logv(c, format!("bless: {}", config.bless));
     logv(c, format!("runtool: {}", opt_str(&config.runtool)));
+    logv(c, format!("report_dir: {:?}", config.report_dir));
     logv(c, format!("host-rustcflags: {}",
                     opt_str(&config.host_rustcflags)));
     logv(c, format!("target-rustcflags: {}",
@@ -236,7 +260,7 @@ pub fn log_config(config: &Config) {
     logv(c, format!("adb_device_status: {}",
                     config.adb_device_status));
     logv(c, format!("verbose: {}", config.verbose));
-    logv(c, format!("quiet: {}", config.quiet));
+    logv(c, format!("format: {:?}", config.format));
     logv(c, format!("\n"));
 }
 
@@ -333,8 +357,10 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         filter: config.filter.clone(),
         filter_exact: config.filter_exact,
         run_ignored: config.run_ignored,
-        quiet: config.quiet,
+        quiet: false,
+        format: config.format,
         logfile: config.logfile.clone(),
+        junit: None,
         run_tests: true,
         bench_benchmarks: true,
         nocapture: match env::var("RUST_TEST_NOCAPTURE") {
@@ -343,6 +369,15 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         },
         color: config.color,
         test_threads: None,
+        report_time: None,
+        test_timeout: None,
+        isolate: false,
+        shuffle: false,
+        shuffle_seed: None,
+        save_baseline: None,
+        baseline: None,
+        shard: None,
+        retries: 0,
         skip: vec![],
         list: false,
         options: test::Options::new(),
@@ -475,6 +510,7 @@ pub fn make_test(config: &Config, testpaths: &TestPaths) -> test::TestDescAndFn
             name: make_test_name(config, testpaths),
             ignore: ignore,
             should_panic: should_panic,
+            retries: None,
         },
         testfn: make_test_closure(config, testpaths),
     }
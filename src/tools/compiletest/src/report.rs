@@ -0,0 +1,231 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The JSON reports written for every test with `--report-dir`.
+//!
+//! A report records, for each revision of a test, the command lines that were
+//! run, the expected diagnostics along with the unexpected and missing ones,
+//! the normalized diffs of UI test output and the error messages printed. This
+//! lets tools triage failures without running the tests again.
+//!
+//! The report of `ui/foo/bar.rs` is written to `<report-dir>/ui/foo/bar.rs.json`
+//! once the test has finished, whether it passed or failed.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::thread;
+
+use rustc_serialize::json::{Json, ToJson};
+use test::TestPaths;
+
+use common::Config;
+use errors::Error;
+
+struct Report {
+    test: String,
+    mode: String,
+    passed: bool,
+    revisions: Vec<Revision>,
+}
+
+struct Revision {
+    revision: Option<String>,
+    commands: Vec<Command>,
+    expected_errors: Vec<Diagnostic>,
+    unexpected_errors: Vec<Diagnostic>,
+    missing_errors: Vec<Diagnostic>,
+    output_diffs: Vec<OutputDiff>,
+    errors: Vec<String>,
+}
+
+struct Command {
+    command: String,
+    status: String,
+}
+
+struct Diagnostic {
+    line: usize,
+    kind: Option<String>,
+    message: String,
+}
+
+struct OutputDiff {
+    kind: String,
+    diff: String,
+}
+
+macro_rules! json_object {
+    ($($field:ident: $value:expr),*) => {{
+        let mut obj = BTreeMap::new();
+        $(obj.insert(stringify!($field).to_owned(), $value.to_json());)*
+        Json::Object(obj)
+    }}
+}
+
+impl ToJson for Report {
+    fn to_json(&self) -> Json {
+        json_object!(test: self.test,
+                     mode: self.mode,
+                     passed: self.passed,
+                     revisions: self.revisions)
+    }
+}
+
+impl ToJson for Revision {
+    fn to_json(&self) -> Json {
+        json_object!(revision: self.revision,
+                     commands: self.commands,
+                     expected_errors: self.expected_errors,
+                     unexpected_errors: self.unexpected_errors,
+                     missing_errors: self.missing_errors,
+                     output_diffs: self.output_diffs,
+                     errors: self.errors)
+    }
+}
+
+impl ToJson for Command {
+    fn to_json(&self) -> Json {
+        json_object!(command: self.command, status: self.status)
+    }
+}
+
+impl ToJson for Diagnostic {
+    fn to_json(&self) -> Json {
+        json_object!(line: self.line, kind: self.kind, message: self.message)
+    }
+}
+
+impl ToJson for OutputDiff {
+    fn to_json(&self) -> Json {
+        json_object!(kind: self.kind, diff: self.diff)
+    }
+}
+
+impl<'a> From<&'a Error> for Diagnostic {
+    fn from(error: &'a Error) -> Diagnostic {
+        Diagnostic {
+            line: error.line_num,
+            kind: error.kind.as_ref().map(|k| k.to_string()),
+            message: error.msg.clone(),
+        }
+    }
+}
+
+/// Collects the report of a single test, which is written out when the
+/// reporter is dropped. Nothing is recorded without `--report-dir`.
+pub struct Reporter {
+    path: Option<PathBuf>,
+    report: RefCell<Report>,
+}
+
+impl Reporter {
+    pub fn new(config: &Config, testpaths: &TestPaths) -> Reporter {
+        let test = PathBuf::from(config.mode.to_string())
+            .join(&testpaths.relative_dir)
+            .join(testpaths.file.file_name().unwrap());
+        let path = config.report_dir.as_ref().map(|dir| {
+            let mut name = test.file_name().unwrap().to_os_string();
+            name.push(".json");
+            dir.join(&test).with_file_name(name)
+        });
+        Reporter {
+            path: path,
+            report: RefCell::new(Report {
+                test: test.display().to_string().replace(r"\", "/"),
+                mode: config.mode.to_string(),
+                passed: false,
+                revisions: Vec::new(),
+            }),
+        }
+    }
+
+    fn record<F: FnOnce(&mut Revision)>(&self, revision: Option<&str>, f: F) {
+        if self.path.is_none() {
+            return;
+        }
+        let mut report = self.report.borrow_mut();
+        let revisions = &mut report.revisions;
+        let existing = revisions.iter()
+                                .position(|r| r.revision.as_ref().map(|s| &s[..]) == revision);
+        let index = match existing {
+            Some(index) => index,
+            None => {
+                revisions.push(Revision {
+                    revision: revision.map(str::to_owned),
+                    commands: Vec::new(),
+                    expected_errors: Vec::new(),
+                    unexpected_errors: Vec::new(),
+                    missing_errors: Vec::new(),
+                    output_diffs: Vec::new(),
+                    errors: Vec::new(),
+                });
+                revisions.len() - 1
+            }
+        };
+        f(&mut revisions[index]);
+    }
+
+    pub fn command(&self, revision: Option<&str>, cmdline: &str, status: &ExitStatus) {
+        self.record(revision, |r| {
+            r.commands.push(Command {
+                command: cmdline.to_owned(),
+                status: status.to_string(),
+            })
+        });
+    }
+
+    pub fn diagnostics(&self,
+                       revision: Option<&str>,
+                       expected: &[Error],
+                       unexpected: &[&Error],
+                       missing: &[&Error]) {
+        self.record(revision, |r| {
+            r.expected_errors.extend(expected.iter().map(Diagnostic::from));
+            r.unexpected_errors.extend(unexpected.iter().map(|&e| Diagnostic::from(e)));
+            r.missing_errors.extend(missing.iter().map(|&e| Diagnostic::from(e)));
+        });
+    }
+
+    pub fn output_diff(&self, revision: Option<&str>, kind: &str, diff: &str) {
+        self.record(revision, |r| {
+            r.output_diffs.push(OutputDiff {
+                kind: kind.to_owned(),
+                diff: diff.to_owned(),
+            })
+        });
+    }
+
+    pub fn error(&self, revision: Option<&str>, err: &str) {
+        self.record(revision, |r| r.errors.push(err.to_owned()));
+    }
+}
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return,
+        };
+        // Failing tests panic, so this is the only way to notice them here.
+        let mut report = self.report.borrow_mut();
+        report.passed = !thread::panicking();
+
+        let result = fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| File::create(path))
+            .and_then(|mut f| writeln!(f, "{}", report.to_json().pretty()));
+        if let Err(e) = result {
+            println!("failed to write test report to {}: {}", path.display(), e);
+        }
+    }
+}
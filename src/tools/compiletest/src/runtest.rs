@@ -14,10 +14,14 @@ use common::{Codegen, DebugInfoLldb, DebugInfoGdb, Rustdoc, CodegenUnits};
 use common::{Incremental, RunMake, Ui, MirOpt};
 use diff;
 use errors::{self, ErrorKind, Error};
+use filecheck;
 use filetime::FileTime;
 use json;
 use header::TestProps;
+use htmldocck;
 use procsrv;
+use regex::Regex;
+use report::Reporter;
 use test::TestPaths;
 use util::logv;
 
@@ -56,11 +60,13 @@ pub fn run(config: Config, testpaths: &TestPaths) {
     }
     debug!("running {:?}", testpaths.file.display());
     let base_props = TestProps::from_file(&testpaths.file, &config);
+    let reporter = Reporter::new(&config, testpaths);
 
     let base_cx = TestCx { config: &config,
                            props: &base_props,
                            testpaths: testpaths,
-                           revision: None };
+                           revision: None,
+                           report: &reporter };
     base_cx.init_all();
 
     if base_props.revisions.is_empty() {
@@ -73,7 +79,8 @@ pub fn run(config: Config, testpaths: &TestPaths) {
                 config: &config,
                 props: &revision_props,
                 testpaths: testpaths,
-                revision: Some(revision)
+                revision: Some(revision),
+                report: &reporter,
             };
             rev_cx.run_revision();
         }
@@ -88,7 +95,8 @@ struct TestCx<'test> {
     config: &'test Config,
     props: &'test TestProps,
     testpaths: &'test TestPaths,
-    revision: Option<&'test str>
+    revision: Option<&'test str>,
+    report: &'test Reporter,
 }
 
 struct DebuggerCommands {
@@ -846,11 +854,13 @@ actual:\n\
         };
 
         self.dump_output(&out, &err);
+        let cmdline = format!("{:?}", cmd);
+        self.report.command(self.revision, &cmdline, &status);
         ProcRes {
             status: status,
             stdout: out,
             stderr: err,
-            cmdline: format!("{:?}", cmd)
+            cmdline: cmdline,
         }
     }
 
@@ -1114,6 +1124,8 @@ actual:\n\
             }
         }
 
+        self.report.diagnostics(self.revision, &expected_errors, &unexpected, &not_found);
+
         if unexpected.len() > 0 || not_found.len() > 0 {
             self.error(
                 &format!("{} unexpected errors found, {} expected errors not found",
@@ -1171,7 +1183,8 @@ actual:\n\
                     config: self.config,
                     props: &aux_props,
                     testpaths: &aux_testpaths,
-                    revision: self.revision
+                    revision: self.revision,
+                    report: self.report,
                 };
                 let auxres = aux_cx.document(out_dir);
                 if !auxres.status.success() {
@@ -1315,7 +1328,8 @@ actual:\n\
                 config: self.config,
                 props: &aux_props,
                 testpaths: &aux_testpaths,
-                revision: self.revision
+                revision: self.revision,
+                report: self.report,
             };
             let aux_args = aux_cx.make_compile_args(crate_type, &aux_testpaths.file, aux_output);
             let auxres = aux_cx.compose_and_run(aux_args,
@@ -1559,6 +1573,7 @@ actual:\n\
                          env,
                          input).expect(&format!("failed to exec `{}`", prog));
         self.dump_output(&out, &err);
+        self.report.command(self.revision, &cmdline, &status);
         return ProcRes {
             status: status,
             stdout: out,
@@ -1641,6 +1656,7 @@ actual:\n\
     }
 
     fn error(&self, err: &str) {
+        self.report.error(self.revision, err);
         match self.revision {
             Some(rev) => println!("\nerror in revision `{}`: {}", rev, err),
             None => println!("\nerror: {}", err)
@@ -1712,9 +1728,8 @@ actual:\n\
         self.compose_and_run_compiler(args, None)
     }
 
-    fn check_ir_with_filecheck(&self) -> ProcRes {
+    fn check_ir_with_llvm_filecheck(&self, prog: &Path) -> ProcRes {
         let irfile = self.output_base_name().with_extension("ll");
-        let prog = self.config.llvm_filecheck.as_ref().unwrap();
         let proc_args = ProcArgs {
             // FIXME (#9639): This needs to handle non-utf8 paths
             prog: prog.to_str().unwrap().to_owned(),
@@ -1724,21 +1739,33 @@ actual:\n\
         self.compose_and_run(proc_args, Vec::new(), "", None, None)
     }
 
+    fn check_ir_with_filecheck(&self) {
+        let irfile = self.output_base_name().with_extension("ll");
+        let failures = filecheck::check(&self.testpaths.file, &irfile);
+        if !failures.is_empty() {
+            for failure in &failures {
+                self.error(&failure.to_string());
+            }
+            self.fatal(&format!("verification of {} failed", irfile.display()));
+        }
+    }
+
     fn run_codegen_test(&self) {
         assert!(self.revision.is_none(), "revisions not relevant here");
 
-        if self.config.llvm_filecheck.is_none() {
-            self.fatal("missing --llvm-filecheck");
-        }
-
-        let mut proc_res = self.compile_test_and_save_ir();
+        let proc_res = self.compile_test_and_save_ir();
         if !proc_res.status.success() {
             self.fatal_proc_rec("compilation failed!", &proc_res);
         }
 
-        proc_res = self.check_ir_with_filecheck();
-        if !proc_res.status.success() {
-            self.fatal_proc_rec("verification with 'FileCheck' failed", &proc_res);
+        match self.config.llvm_filecheck {
+            Some(ref prog) => {
+                let proc_res = self.check_ir_with_llvm_filecheck(prog);
+                if !proc_res.status.success() {
+                    self.fatal_proc_rec("verification with 'FileCheck' failed", &proc_res);
+                }
+            }
+            None => self.check_ir_with_filecheck(),
         }
     }
 
@@ -1768,13 +1795,12 @@ actual:\n\
         if self.props.check_test_line_numbers_match == true {
             self.check_rustdoc_test_option(proc_res);
         } else {
-            let root = self.find_rust_src_root().unwrap();
-            let res = self.cmd2procres(Command::new(&self.config.docck_python)
-                                       .arg(root.join("src/etc/htmldocck.py"))
-                                       .arg(out_dir)
-                                       .arg(&self.testpaths.file));
-            if !res.status.success() {
-                self.fatal_proc_rec("htmldocck failed!", &res);
+            let errors = htmldocck::check(&out_dir, &self.testpaths.file);
+            if !errors.is_empty() {
+                for error in &errors {
+                    self.error(&error.to_string());
+                }
+                self.fatal(&format!("{} rustdoc check(s) failed", errors.len()));
             }
         }
     }
@@ -2095,6 +2121,7 @@ actual:\n\
             props: &revision_props,
             testpaths: self.testpaths,
             revision: self.revision,
+            report: self.report,
         };
 
         if self.config.verbose {
@@ -2235,15 +2262,23 @@ actual:\n\
         let expected_stdout_path = self.expected_output_path("stdout");
         let expected_stdout = self.load_expected_output(&expected_stdout_path);
 
-        let normalized_stdout = self.normalize_output(&proc_res.stdout);
-        let normalized_stderr = self.normalize_output(&proc_res.stderr);
+        let normalized_stdout =
+            self.normalize_output(&proc_res.stdout, &self.props.normalize_stdout);
+        let normalized_stderr =
+            self.normalize_output(&proc_res.stderr, &self.props.normalize_stderr);
 
         let mut errors = 0;
-        errors += self.compare_output("stdout", &normalized_stdout, &expected_stdout);
-        errors += self.compare_output("stderr", &normalized_stderr, &expected_stderr);
+        if self.config.bless {
+            self.bless_output("stdout", &normalized_stdout, &expected_stdout);
+            self.bless_output("stderr", &normalized_stderr, &expected_stderr);
+        } else {
+            errors += self.compare_output("stdout", &normalized_stdout, &expected_stdout);
+            errors += self.compare_output("stderr", &normalized_stderr, &expected_stderr);
+        }
 
         if errors > 0 {
-            println!("To update references, run this command from build directory:");
+            println!("To update references, rerun the tests with `--bless` or run this \
+                      command from build directory:");
             let relative_path_to_file =
                 self.testpaths.relative_dir
                               .join(self.testpaths.file.file_name().unwrap());
@@ -2285,10 +2320,16 @@ actual:\n\
                                                    .read_to_string(&mut test_file_contents)
                                                    .unwrap();
         if let Some(idx) =  test_file_contents.find("// END RUST SOURCE") {
-            let (_, tests_text) = test_file_contents.split_at(idx + "// END_RUST SOURCE".len());
+            let (source, tests_text) =
+                test_file_contents.split_at(idx + "// END_RUST SOURCE".len());
             let tests_text_str = String::from(tests_text);
             let mut curr_test : Option<&str> = None;
             let mut curr_test_contents = Vec::new();
+            // With `--bless`, the test file with the blocks which didn't match
+            // replaced by the actual MIR.
+            let mut blessed = String::from(source);
+            let mut curr_block = String::new();
+            let mut changed = false;
             for l in tests_text_str.lines() {
                 debug!("line: {:?}", l);
                 if l.starts_with("// START ") {
@@ -2299,15 +2340,50 @@ actual:\n\
                     if Some(t) != curr_test {
                         panic!("mismatched START END test name");
                     }
-                    self.compare_mir_test_output(curr_test.unwrap(), &curr_test_contents);
+                    let test_name = curr_test.unwrap();
+                    match self.compare_mir_test_output(test_name, &curr_test_contents) {
+                        Ok(()) => blessed.push_str(&curr_block),
+                        Err(_) if self.config.bless => {
+                            blessed.push_str(&format!("// START {}\n", test_name));
+                            for dumped_line in self.read_mir_dump(test_name).lines() {
+                                let dumped_line = nocomment_mir_line(dumped_line);
+                                if !dumped_line.is_empty() {
+                                    blessed.push_str(&format!("// {}\n", dumped_line));
+                                }
+                            }
+                            changed = true;
+                        }
+                        Err(msg) => panic!("{}", msg),
+                    }
                     curr_test = None;
                     curr_test_contents.clear();
+                    curr_block.clear();
                 } else if l.is_empty() {
                     // ignore
                 } else if l.starts_with("// ") {
                     let (_, test_content) = l.split_at("// ".len());
                     curr_test_contents.push(test_content);
                 }
+                // Lines outside of the blocks are kept as they are
+                if curr_test.is_some() {
+                    curr_block.push_str(l);
+                    curr_block.push('\n');
+                } else {
+                    blessed.push_str(l);
+                    blessed.push('\n');
+                }
+            }
+            blessed.push_str(&curr_block);
+
+            if changed {
+                let path = &self.testpaths.file;
+                match File::create(path).and_then(|mut f| f.write_all(blessed.as_bytes())) {
+                    Ok(()) => println!("blessed {}", path.display()),
+                    Err(e) => {
+                        self.fatal(&format!("failed to bless MIR in `{}`: {}",
+                                            path.display(), e))
+                    }
+                }
             }
         }
     }
@@ -2324,17 +2400,27 @@ actual:\n\
         }
     }
 
-    fn compare_mir_test_output(&self, test_name: &str, expected_content: &Vec<&str>) {
+    fn read_mir_dump(&self, test_name: &str) -> String {
         let mut output_file = PathBuf::new();
         output_file.push(self.get_mir_dump_dir());
         output_file.push(test_name);
-        debug!("comparing the contests of: {:?}", output_file);
-        debug!("with: {:?}", expected_content);
         self.check_mir_test_timestamp(test_name, &output_file);
 
         let mut dumped_file = fs::File::open(output_file.clone()).unwrap();
         let mut dumped_string = String::new();
         dumped_file.read_to_string(&mut dumped_string).unwrap();
+        dumped_string
+    }
+
+    /// Checks that the expected lines appear in the MIR dumped for
+    /// `test_name`, returning a description of the mismatch if they don't.
+    fn compare_mir_test_output(&self,
+                               test_name: &str,
+                               expected_content: &Vec<&str>)
+                               -> Result<(), String> {
+        debug!("comparing the contents of: {:?}", test_name);
+        debug!("with: {:?}", expected_content);
+        let dumped_string = self.read_mir_dump(test_name);
         let mut dumped_lines = dumped_string.lines().filter(|l| !l.is_empty());
         let mut expected_lines = expected_content.iter().filter(|l| !l.is_empty());
 
@@ -2361,15 +2447,16 @@ actual:\n\
                                                  .filter(|l| !l.is_empty())
                                                  .collect::<Vec<_>>()
                                                  .join("\n");
-                panic!("ran out of mir dump output to match against.\n\
-                        Did not find expected line: {:?}\n\
-                        Expected:\n{}\n\
-                        Actual:\n{}",
-                        expected_line,
-                        expected_content.join("\n"),
-                        normalize_all);
+                return Err(format!("ran out of mir dump output to match against.\n\
+                                    Did not find expected line: {:?}\n\
+                                    Expected:\n{}\n\
+                                    Actual:\n{}",
+                                   expected_line,
+                                   expected_content.join("\n"),
+                                   normalize_all));
             }
         }
+        Ok(())
     }
 
     fn get_mir_dump_dir(&self) -> PathBuf {
@@ -2382,13 +2469,26 @@ actual:\n\
         mir_dump_dir
     }
 
-    fn normalize_output(&self, output: &str) -> String {
+    fn normalize_output(&self, output: &str, custom_rules: &[(String, String)]) -> String {
         let parent_dir = self.testpaths.file.parent().unwrap();
         let parent_dir_str = parent_dir.display().to_string();
-        output.replace(&parent_dir_str, "$DIR")
-              .replace("\\", "/") // normalize for paths on windows
-              .replace("\r\n", "\n") // normalize for linebreaks on windows
-              .replace("\t", "\\t") // makes tabs visible
+        let mut normalized = output.replace(&parent_dir_str, "$DIR")
+                                   .replace("\\", "/") // normalize for paths on windows
+                                   .replace("\r\n", "\n") // normalize for linebreaks on windows
+                                   .replace("\t", "\\t"); // makes tabs visible
+
+        // Rules from `normalize-stdout`/`normalize-stderr` directives
+        for &(ref pattern, ref replacement) in custom_rules {
+            let re = match Regex::new(pattern) {
+                Ok(re) => re,
+                Err(e) => {
+                    self.fatal(&format!("invalid regex `{}` in normalization directive: {}",
+                                        pattern, e))
+                }
+            };
+            normalized = re.replace_all(&normalized, &replacement[..]).into_owned();
+        }
+        normalized
     }
 
     fn expected_output_path(&self, kind: &str) -> PathBuf {
@@ -2423,13 +2523,17 @@ actual:\n\
         println!("expected {}:\n{}\n", kind, expected);
         println!("diff of {}:\n", kind);
 
+        let mut diff_lines = String::new();
         for diff in diff::lines(expected, actual) {
-            match diff {
-                diff::Result::Left(l)    => println!("-{}", l),
-                diff::Result::Both(l, _) => println!(" {}", l),
-                diff::Result::Right(r)   => println!("+{}", r),
-            }
+            let line = match diff {
+                diff::Result::Left(l)    => format!("-{}\n", l),
+                diff::Result::Both(l, _) => format!(" {}\n", l),
+                diff::Result::Right(r)   => format!("+{}\n", r),
+            };
+            diff_lines.push_str(&line);
         }
+        print!("{}", diff_lines);
+        self.report.output_diff(self.revision, kind, &diff_lines);
 
         let output_file = self.output_base_name().with_extension(kind);
         match File::create(&output_file).and_then(|mut f| f.write_all(actual.as_bytes())) {
@@ -2444,6 +2548,29 @@ actual:\n\
         println!("Actual {} saved to {}", kind, output_file.display());
         1
     }
+
+    /// Makes `actual` the expected output of this test, removing the file
+    /// with the expected output if there is no output at all.
+    fn bless_output(&self, kind: &str, actual: &str, expected: &str) {
+        let path = self.expected_output_path(kind);
+        let result = if actual.is_empty() {
+            if !path.exists() {
+                return;
+            }
+            fs::remove_file(&path)
+        } else if actual != expected {
+            File::create(&path).and_then(|mut f| f.write_all(actual.as_bytes()))
+        } else {
+            return;
+        };
+        match result {
+            Ok(()) => println!("blessed {}", path.display()),
+            Err(e) => {
+                self.fatal(&format!("failed to bless {} in `{}`: {}",
+                                    kind, path.display(), e))
+            }
+        }
+    }
 }
 
 struct ProcArgs {
@@ -10,6 +10,8 @@
 
 #![feature(rustc_private)]
 
+extern crate rustc;
+extern crate rustc_lint;
 extern crate syntax;
 extern crate rustdoc;
 extern crate serialize as rustc_serialize;
@@ -22,6 +24,7 @@ use std::io::{Read, Write};
 use std::path::Path;
 use std::path::PathBuf;
 
+use rustc::lint::{Lint, LintStore};
 use syntax::diagnostics::metadata::{get_metadata_dir, ErrorMetadataMap, ErrorMetadata};
 
 use rustdoc::html::markdown::{Markdown, PLAYGROUND, RenderType};
@@ -44,10 +47,11 @@ impl OutputFormat {
 }
 
 trait Formatter {
-    fn header(&self, output: &mut Write) -> Result<(), Box<Error>>;
-    fn title(&self, output: &mut Write) -> Result<(), Box<Error>>;
+    fn header(&self, output: &mut Write, title: &str) -> Result<(), Box<Error>>;
+    fn title(&self, output: &mut Write, title: &str) -> Result<(), Box<Error>>;
     fn error_code_block(&self, output: &mut Write, info: &ErrorMetadata,
                         err_code: &str) -> Result<(), Box<Error>>;
+    fn lint_block(&self, output: &mut Write, lint: &Lint) -> Result<(), Box<Error>>;
     fn footer(&self, output: &mut Write) -> Result<(), Box<Error>>;
 }
 
@@ -55,11 +59,11 @@ struct HTMLFormatter;
 struct MarkdownFormatter;
 
 impl Formatter for HTMLFormatter {
-    fn header(&self, output: &mut Write) -> Result<(), Box<Error>> {
+    fn header(&self, output: &mut Write, title: &str) -> Result<(), Box<Error>> {
         write!(output, r##"<!DOCTYPE html>
 <html>
 <head>
-<title>Rust Compiler Error Index</title>
+<title>{}</title>
 <meta charset="utf-8">
 <!-- Include rust.css after main.css so its rules take priority. -->
 <link rel="stylesheet" type="text/css" href="main.css"/>
@@ -71,12 +75,12 @@ impl Formatter for HTMLFormatter {
 </style>
 </head>
 <body>
-"##)?;
+"##, title)?;
         Ok(())
     }
 
-    fn title(&self, output: &mut Write) -> Result<(), Box<Error>> {
-        write!(output, "<h1>Rust Compiler Error Index</h1>\n")?;
+    fn title(&self, output: &mut Write, title: &str) -> Result<(), Box<Error>> {
+        write!(output, "<h1>{}</h1>\n", title)?;
         Ok(())
     }
 
@@ -108,6 +112,24 @@ impl Formatter for HTMLFormatter {
         Ok(())
     }
 
+    fn lint_block(&self, output: &mut Write, lint: &Lint) -> Result<(), Box<Error>> {
+        let name = lint.name_lower();
+
+        // Lint name (with self-link), default level and short description.
+        write!(output,
+               "<h2 id=\"{0}\" class=\"section-header\"><a href=\"#{0}\">{0}</a></h2>\n",
+               name)?;
+        let summary = format!("Default level: `{}`. {}.",
+                              lint.default_level.as_str(), capitalize(lint.desc));
+        write!(output, "{}", Markdown(&summary, RenderType::Hoedown))?;
+
+        // Explanation rendered as markdown.
+        if let Some(explanation) = lint.explanation {
+            write!(output, "{}", Markdown(explanation, RenderType::Hoedown))?;
+        }
+        Ok(())
+    }
+
     fn footer(&self, output: &mut Write) -> Result<(), Box<Error>> {
         write!(output, "</body>\n</html>")?;
         Ok(())
@@ -116,12 +138,12 @@ impl Formatter for HTMLFormatter {
 
 impl Formatter for MarkdownFormatter {
     #[allow(unused_variables)]
-    fn header(&self, output: &mut Write) -> Result<(), Box<Error>> {
+    fn header(&self, output: &mut Write, title: &str) -> Result<(), Box<Error>> {
         Ok(())
     }
 
-    fn title(&self, output: &mut Write) -> Result<(), Box<Error>> {
-        write!(output, "# Rust Compiler Error Index\n")?;
+    fn title(&self, output: &mut Write, title: &str) -> Result<(), Box<Error>> {
+        write!(output, "# {}\n", title)?;
         Ok(())
     }
 
@@ -133,6 +155,13 @@ impl Formatter for MarkdownFormatter {
         })
     }
 
+    fn lint_block(&self, output: &mut Write, lint: &Lint) -> Result<(), Box<Error>> {
+        Ok(match lint.explanation {
+            Some(explanation) => write!(output, "## {}\n{}\n", lint.name_lower(), explanation)?,
+            None => (),
+        })
+    }
+
     #[allow(unused_variables)]
     fn footer(&self, output: &mut Write) -> Result<(), Box<Error>> {
         Ok(())
@@ -159,13 +188,31 @@ fn load_all_errors(metadata_dir: &Path) -> Result<ErrorMetadataMap, Box<Error>>
     Ok(all_errors)
 }
 
+/// Returns the builtin lints, sorted by name.
+fn load_all_lints() -> Vec<&'static Lint> {
+    let mut store = LintStore::new();
+    rustc_lint::register_builtins(&mut store, None);
+    let mut lints = store.get_lints().iter().map(|&(lint, _)| lint).collect::<Vec<_>>();
+    lints.sort_by_key(|lint| lint.name_lower());
+    lints
+}
+
+/// Upper-cases the first letter of a lint description, to use it as a sentence.
+fn capitalize(desc: &str) -> String {
+    let mut chars = desc.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 /// Output an HTML page for the errors in `err_map` to `output_path`.
 fn render_error_page<T: Formatter>(err_map: &ErrorMetadataMap, output_path: &Path,
-                                   formatter: T) -> Result<(), Box<Error>> {
+                                   formatter: &T) -> Result<(), Box<Error>> {
     let mut output_file = File::create(output_path)?;
 
-    formatter.header(&mut output_file)?;
-    formatter.title(&mut output_file)?;
+    formatter.header(&mut output_file, "Rust Compiler Error Index")?;
+    formatter.title(&mut output_file, "Rust Compiler Error Index")?;
 
     for (err_code, info) in err_map {
         formatter.error_code_block(&mut output_file, info, err_code)?;
@@ -174,19 +221,43 @@ fn render_error_page<T: Formatter>(err_map: &ErrorMetadataMap, output_path: &Pat
     formatter.footer(&mut output_file)
 }
 
-fn main_with_result(format: OutputFormat, dst: &Path) -> Result<(), Box<Error>> {
+/// Output a page for the lints in `lints` to `output_path`.
+fn render_lint_page<T: Formatter>(lints: &[&'static Lint], output_path: &Path,
+                                  formatter: &T) -> Result<(), Box<Error>> {
+    let mut output_file = File::create(output_path)?;
+
+    formatter.header(&mut output_file, "Rust Compiler Lint Index")?;
+    formatter.title(&mut output_file, "Rust Compiler Lint Index")?;
+
+    for lint in lints {
+        formatter.lint_block(&mut output_file, lint)?;
+    }
+
+    formatter.footer(&mut output_file)
+}
+
+fn render_pages<T: Formatter>(err_map: &ErrorMetadataMap, dst: &Path, lint_dst: &Path,
+                              formatter: T) -> Result<(), Box<Error>> {
+    render_error_page(err_map, dst, &formatter)?;
+    render_lint_page(&load_all_lints(), lint_dst, &formatter)
+}
+
+fn main_with_result(format: OutputFormat, dst: &Path, lint_dst: &Path)
+                    -> Result<(), Box<Error>> {
     let build_arch = env::var("CFG_BUILD")?;
     let metadata_dir = get_metadata_dir(&build_arch);
     let err_map = load_all_errors(&metadata_dir)?;
     match format {
         OutputFormat::Unknown(s)  => panic!("Unknown output format: {}", s),
-        OutputFormat::HTML(h)     => render_error_page(&err_map, dst, h)?,
-        OutputFormat::Markdown(m) => render_error_page(&err_map, dst, m)?,
+        OutputFormat::HTML(h)     => render_pages(&err_map, dst, lint_dst, h)?,
+        OutputFormat::Markdown(m) => render_pages(&err_map, dst, lint_dst, m)?,
     }
     Ok(())
 }
 
-fn parse_args() -> (OutputFormat, PathBuf) {
+/// Parses `[FORMAT [ERROR_INDEX [LINT_INDEX]]]`, the lint index being written
+/// next to the error index by default.
+fn parse_args() -> (OutputFormat, PathBuf, PathBuf) {
     let mut args = env::args().skip(1);
     let format = args.next().map(|a| OutputFormat::from(&a))
                             .unwrap_or(OutputFormat::from("html"));
@@ -197,15 +268,22 @@ fn parse_args() -> (OutputFormat, PathBuf) {
             OutputFormat::Unknown(..) => PathBuf::from("<nul>"),
         }
     });
-    (format, dst)
+    let lint_dst = args.next().map(PathBuf::from).unwrap_or_else(|| {
+        match format {
+            OutputFormat::HTML(..) => dst.with_file_name("lint-index.html"),
+            OutputFormat::Markdown(..) => dst.with_file_name("lint-index.md"),
+            OutputFormat::Unknown(..) => PathBuf::from("<nul>"),
+        }
+    });
+    (format, dst, lint_dst)
 }
 
 fn main() {
     PLAYGROUND.with(|slot| {
         *slot.borrow_mut() = Some((None, String::from("https://play.rust-lang.org/")));
     });
-    let (format, dst) = parse_args();
-    if let Err(e) = main_with_result(format, &dst) {
+    let (format, dst, lint_dst) = parse_args();
+    if let Err(e) = main_with_result(format, &dst, &lint_dst) {
         panic!("{}", e.description());
     }
 }
@@ -17,12 +17,14 @@
 
 use std::path::Path;
 
+use Report;
+
 // All files are executable on Windows, so just check on Unix
 #[cfg(windows)]
-pub fn check(_path: &Path, _bad: &mut bool) {}
+pub fn check(_path: &Path, _bad: &mut Report) {}
 
 #[cfg(unix)]
-pub fn check(path: &Path, bad: &mut bool) {
+pub fn check(path: &Path, bad: &mut Report) {
     use std::fs;
     use std::io::Read;
     use std::process::{Command, Stdio};
@@ -62,7 +64,7 @@ pub fn check(path: &Path, bad: &mut bool) {
                 });
             let path_bytes = rel_path.as_os_str().as_bytes();
             if output.status.success() && output.stdout.starts_with(path_bytes) {
-                tidy_error!(bad, "binary checked into source: {}", file.display());
+                bad.error_at(file, None, "binary checked into source".to_string());
             }
         }
     })
@@ -19,7 +19,9 @@ use std::io::prelude::*;
 use std::fs::File;
 use std::path::Path;
 
-pub fn check(path: &Path, bad: &mut bool) {
+use Report;
+
+pub fn check(path: &Path, bad: &mut Report) {
     if !super::filter_dirs(path) {
         return
     }
@@ -40,7 +42,7 @@ pub fn check(path: &Path, bad: &mut bool) {
 
 // Verify that the dependencies in Cargo.toml at `tomlfile` are sync'd with the
 // `extern crate` annotations in the lib.rs at `libfile`.
-fn verify(tomlfile: &Path, libfile: &Path, bad: &mut bool) {
+fn verify(tomlfile: &Path, libfile: &Path, bad: &mut Report) {
     let mut toml = String::new();
     let mut librs = String::new();
     t!(t!(File::open(tomlfile)).read_to_string(&mut toml));
@@ -100,8 +102,8 @@ fn verify(tomlfile: &Path, libfile: &Path, bad: &mut bool) {
         }
 
         if !librs.contains(&format!("extern crate {}", krate)) {
-            tidy_error!(bad, "{} doesn't have `extern crate {}`, but Cargo.toml \
-                              depends on it", libfile.display(), krate);
+            bad.error_at(libfile, None, format!("doesn't have `extern crate {}`, but \
+                                                 Cargo.toml depends on it", krate));
         }
     }
 }
@@ -14,6 +14,8 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+use Report;
+
 static LICENSES: &'static [&'static str] = &[
     "MIT/Apache-2.0",
     "MIT / Apache-2.0",
@@ -35,7 +37,7 @@ static EXCEPTIONS: &'static [&'static str] = &[
     "strings", // this is actually MIT/Apache-2.0 but it's not in the manifest yet
 ];
 
-pub fn check(path: &Path, bad: &mut bool) {
+pub fn check(path: &Path, bad: &mut Report) {
     let path = path.join("vendor");
     assert!(path.exists(), "vendor directory missing");
     let mut saw_dir = false;
@@ -54,14 +56,14 @@ pub fn check(path: &Path, bad: &mut bool) {
         }
 
         let toml = dir.path().join("Cargo.toml");
-        if !check_license(&toml) {
-            *bad = true;
+        if let Err(msg) = check_license(&toml) {
+            bad.error_at(&toml, None, msg);
         }
     }
     assert!(saw_dir, "no vendored source");
 }
 
-fn check_license(path: &Path) -> bool {
+fn check_license(path: &Path) -> Result<(), String> {
     if !path.exists() {
         panic!("{} does not exist", path.display());
     }
@@ -75,18 +77,16 @@ fn check_license(path: &Path) -> bool {
         }
         let license = extract_license(line);
         if !LICENSES.contains(&&*license) {
-            println!("invalid license {} in {}", license, path.display());
-            return false;
+            return Err(format!("invalid license {}", license));
         }
         found_license = true;
         break;
     }
     if !found_license {
-        println!("no license in {}", path.display());
-        return false;
+        return Err("no license".to_string());
     }
 
-    true
+    Ok(())
 }
 
 fn extract_license(line: &str) -> String {
@@ -18,7 +18,9 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 
-pub fn check(path: &Path, bad: &mut bool) {
+use Report;
+
+pub fn check(path: &Path, bad: &mut Report) {
     let mut contents = String::new();
     let mut map = HashMap::new();
     super::walk(path,
@@ -79,13 +81,13 @@ pub fn check(path: &Path, bad: &mut bool) {
             continue
         }
 
-        tidy_error!(bad, "duplicate error code: {}", code);
         for &(ref file, line_num, ref line) in entries.iter() {
-            tidy_error!(bad, "{}:{}: {}", file.display(), line_num, line);
+            bad.error_at(file, Some(line_num),
+                         format!("duplicate error code E{:04}: {}", code, line.trim()));
         }
     }
 
-    if !*bad {
+    if !bad.failed() {
         println!("* {} error codes", map.len());
         println!("* highest error code: E{:04}", max);
     }
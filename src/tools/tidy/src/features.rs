@@ -17,6 +17,13 @@
 //! * Library features have at most one stability level
 //! * Library features have at most one `since` value
 //! * All unstable lang features have tests to ensure they are actually unstable
+//! * Features enabled with `#![feature]` outside of the tests are still
+//!   declared, either as a lang feature or with an `#[unstable]` attribute
+//!   (`unknown-feature`)
+//! * Features aren't enabled with `#![feature]` once they're stable
+//!   (`stable-feature`)
+//! * Stable features no longer have a section in The Unstable Book
+//!   (`stale-unstable-book`)
 
 use std::collections::HashMap;
 use std::fmt;
@@ -24,6 +31,11 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 
+use Report;
+
+/// Library features declared by crates which tidy doesn't look into.
+const EXTERNAL_FEATURES: &'static [&'static str] = &["libc"];
+
 #[derive(Debug, PartialEq)]
 pub enum Status {
     Stable,
@@ -49,7 +61,7 @@ pub struct Feature {
     pub has_gate_test: bool,
 }
 
-pub fn check(path: &Path, bad: &mut bool, quiet: bool) {
+pub fn check(path: &Path, bad: &mut Report) {
     let mut features = collect_lang_features(path);
     assert!(!features.is_empty());
 
@@ -77,7 +89,7 @@ pub fn check(path: &Path, bad: &mut bool, quiet: bool) {
 
         for (i, line) in contents.lines().enumerate() {
             let mut err = |msg: &str| {
-                tidy_error!(bad, "{}:{}: {}", file.display(), i + 1, msg);
+                bad.error_at(file, Some(i + 1), msg.to_string());
             };
 
             let gate_test_str = "gate-test-";
@@ -131,10 +143,13 @@ pub fn check(path: &Path, bad: &mut bool, quiet: bool) {
         tidy_error!(bad, "Found {} features without a gate test.", gate_untested.len());
     }
 
-    if *bad {
+    check_enabled_features(path, bad, &features, &lib_features);
+    check_unstable_book(path, bad, &features, &lib_features);
+
+    if bad.failed() {
         return;
     }
-    if quiet {
+    if bad.quiet {
         println!("* {} features", features.len());
         return;
     }
@@ -161,6 +176,108 @@ pub fn check(path: &Path, bad: &mut bool, quiet: bool) {
     }
 }
 
+/// Returns the `since` version of the feature `name` if it's stable, either
+/// as a lang or a library feature.
+fn stable_since<'a>(name: &str,
+                    features: &'a HashMap<String, Feature>,
+                    lib_features: &'a HashMap<String, Feature>) -> Option<&'a str> {
+    features.get(name).into_iter().chain(lib_features.get(name))
+            .find(|f| f.level == Status::Stable)
+            .map(|f| &f.since[..])
+}
+
+/// Cross-references the features enabled with `#![feature]` throughout the
+/// tree with the declared ones.
+fn check_enabled_features(path: &Path,
+                          bad: &mut Report,
+                          features: &HashMap<String, Feature>,
+                          lib_features: &HashMap<String, Feature>) {
+    let mut contents = String::new();
+    super::walk(path, &mut super::filter_dirs, &mut |file| {
+        let filename = file.file_name().unwrap().to_string_lossy();
+        if !filename.ends_with(".rs") {
+            return;
+        }
+        // Tests can enable features which don't exist on purpose, to test
+        // how unknown features are dealt with.
+        let is_test = file.starts_with(path.join("test"));
+
+        contents.truncate(0);
+        t!(t!(File::open(&file), &file).read_to_string(&mut contents));
+
+        for (line, name) in enabled_features(&contents) {
+            if let Some(since) = stable_since(&name, features, lib_features) {
+                bad.lint_at("stable-feature", file, Some(line),
+                            format!("the `{}` feature has been stable since {} and no \
+                                     longer needs to be enabled", name, since));
+            } else if !is_test && !features.contains_key(&name) &&
+                      !lib_features.contains_key(&name) &&
+                      !EXTERNAL_FEATURES.contains(&&name[..]) {
+                bad.lint_at("unknown-feature", file, Some(line),
+                            format!("the `{}` feature is enabled, but no longer declared \
+                                     by any `#[unstable]` attribute", name));
+            }
+        }
+    });
+}
+
+/// Returns the features enabled by `#![feature(...)]` attributes in
+/// `contents`, along with the line each of them is on.
+fn enabled_features(contents: &str) -> Vec<(usize, String)> {
+    let mut enabled = Vec::new();
+    let mut inside = false;
+    for (i, line) in contents.lines().enumerate() {
+        let mut rest = match line.find("//") {
+            Some(i) => line[..i].trim(),
+            None => line.trim(),
+        };
+        if !inside {
+            if !rest.starts_with("#![feature(") {
+                continue;
+            }
+            rest = &rest["#![feature(".len()..];
+            inside = true;
+        }
+        let end = match rest.find(')') {
+            Some(end) => {
+                inside = false;
+                end
+            }
+            None => rest.len(),
+        };
+        for name in rest[..end].split(',').map(|name| name.trim()) {
+            if !name.is_empty() {
+                enabled.push((i + 1, name.to_owned()));
+            }
+        }
+    }
+    enabled
+}
+
+/// Checks that The Unstable Book has no section left for stable features.
+fn check_unstable_book(path: &Path,
+                       bad: &mut Report,
+                       features: &HashMap<String, Feature>,
+                       lib_features: &HashMap<String, Feature>) {
+    let book = path.join("doc/unstable-book/src");
+    for dir in &["language-features", "library-features"] {
+        let dir = book.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in t!(dir.read_dir(), dir) {
+            let file = t!(entry).path();
+            let name = file.file_stem().unwrap().to_string_lossy().replace('-', "_");
+            if let Some(since) = stable_since(&name, features, lib_features) {
+                bad.lint_at("stale-unstable-book", &file, None,
+                            format!("the `{}` feature has been stable since {}, its \
+                                     section in The Unstable Book should be removed",
+                                    name, since));
+            }
+        }
+    }
+}
+
 fn find_attr_val<'a>(line: &'a str, attr: &str) -> Option<&'a str> {
     line.find(attr)
         .and_then(|i| line[i..].find('"').map(|j| i + j + 1))
@@ -208,7 +325,7 @@ pub fn collect_lang_features(base_src_path: &Path) -> HashMap<String, Feature> {
 }
 
 pub fn collect_lib_features(base_src_path: &Path,
-                            bad: &mut bool,
+                            bad: &mut Report,
                             features: &HashMap<String, Feature>) -> HashMap<String, Feature> {
     let mut lib_features = HashMap::<String, Feature>::new();
     let mut contents = String::new();
@@ -226,7 +343,7 @@ pub fn collect_lib_features(base_src_path: &Path,
 
         for (i, line) in contents.lines().enumerate() {
             let mut err = |msg: &str| {
-                tidy_error!(bad, "{}:{}: {}", file.display(), i + 1, msg);
+                bad.error_at(file, Some(i + 1), msg.to_string());
             };
             let level = if line.contains("[unstable(") {
                 Status::Unstable
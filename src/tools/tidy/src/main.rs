@@ -13,6 +13,11 @@
 //! This program runs all of the various tidy checks for style, cleanliness,
 //! etc. This is run by default on `make check` and as part of the auto
 //! builders.
+//!
+//! Each check has a name, and `--check NAME` runs only the named checks. With
+//! `--json` the findings are printed to stderr as JSON objects, one per line,
+//! with the check, file and line they are about. See the `report` module for
+//! how to suppress a finding.
 
 use std::env;
 use std::fs;
@@ -34,10 +39,7 @@ macro_rules! t {
 
 macro_rules! tidy_error {
     ($bad:expr, $fmt:expr, $($arg:tt)*) => ({
-        use std::io::Write;
-        *$bad = true;
-        write!(::std::io::stderr(), "tidy error: ").expect("could not write to stderr");
-        writeln!(::std::io::stderr(), $fmt, $($arg)*).expect("could not write to stderr");
+        $bad.error(format!($fmt, $($arg)*));
     });
 }
 
@@ -49,6 +51,29 @@ mod cargo;
 mod pal;
 mod deps;
 mod unstable_book;
+mod report;
+
+use report::Report;
+
+/// A tidy check, which can be run on its own with `--check NAME`.
+struct Check {
+    name: &'static str,
+    run: fn(&Path, &mut Report),
+    /// Whether the check looks at the vendored sources, skipped with
+    /// `--no-vendor`.
+    vendor: bool,
+}
+
+static CHECKS: &'static [Check] = &[
+    Check { name: "bins", run: bins::check, vendor: false },
+    Check { name: "style", run: style::check, vendor: false },
+    Check { name: "errors", run: errors::check, vendor: false },
+    Check { name: "cargo", run: cargo::check, vendor: false },
+    Check { name: "features", run: features::check, vendor: false },
+    Check { name: "pal", run: pal::check, vendor: false },
+    Check { name: "unstable_book", run: unstable_book::check, vendor: false },
+    Check { name: "deps", run: deps::check, vendor: true },
+];
 
 fn main() {
     let path = env::args_os().skip(1).next().expect("need an argument");
@@ -56,21 +81,38 @@ fn main() {
 
     let args: Vec<String> = env::args().skip(1).collect();
 
-    let mut bad = false;
     let quiet = args.iter().any(|s| *s == "--quiet");
-    bins::check(&path, &mut bad);
-    style::check(&path, &mut bad);
-    errors::check(&path, &mut bad);
-    cargo::check(&path, &mut bad);
-    features::check(&path, &mut bad, quiet);
-    pal::check(&path, &mut bad);
-    unstable_book::check(&path, &mut bad);
-    if !args.iter().any(|s| *s == "--no-vendor") {
-        deps::check(&path, &mut bad);
+    let json = args.iter().any(|s| *s == "--json");
+    let no_vendor = args.iter().any(|s| *s == "--no-vendor");
+    let mut selected = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--check" {
+            let name = iter.next().expect("--check needs the name of a check");
+            if !CHECKS.iter().any(|c| c.name == name) {
+                let names = CHECKS.iter().map(|c| c.name).collect::<Vec<_>>();
+                panic!("unknown check `{}`, expected one of: {}", name, names.join(", "));
+            }
+            selected.push(&name[..]);
+        }
+    }
+
+    let mut bad = Report::new(json, quiet);
+    for check in CHECKS {
+        if !selected.is_empty() && !selected.contains(&check.name) {
+            continue
+        }
+        if check.vendor && no_vendor {
+            continue
+        }
+        bad.start(check.name);
+        (check.run)(&path, &mut bad);
     }
 
-    if bad {
-        writeln!(io::stderr(), "some tidy checks failed").expect("could not write to stderr");
+    if bad.findings() > 0 {
+        if !json {
+            writeln!(io::stderr(), "some tidy checks failed").expect("could not write to stderr");
+        }
         process::exit(1);
     }
 }
@@ -49,6 +49,8 @@ use std::io::Read;
 use std::path::Path;
 use std::iter::Iterator;
 
+use Report;
+
 // Paths that may contain platform-specific code
 const EXCEPTION_PATHS: &'static [&'static str] = &[
     // std crates
@@ -86,7 +88,7 @@ const EXCEPTION_PATHS: &'static [&'static str] = &[
     "src/bootstrap",
 ];
 
-pub fn check(path: &Path, bad: &mut bool) {
+pub fn check(path: &Path, bad: &mut Report) {
     let ref mut contents = String::new();
     // Sanity check that the complex parsing here works
     let ref mut saw_target_arch = false;
@@ -106,7 +108,7 @@ pub fn check(path: &Path, bad: &mut bool) {
 }
 
 fn check_cfgs(contents: &mut String, file: &Path,
-              bad: &mut bool, saw_target_arch: &mut bool, saw_cfg_bang: &mut bool) {
+              bad: &mut Report, saw_target_arch: &mut bool, saw_cfg_bang: &mut bool) {
     contents.truncate(0);
     t!(t!(File::open(file), file).read_to_string(contents));
 
@@ -126,7 +128,7 @@ fn check_cfgs(contents: &mut String, file: &Path,
             Ok(_) => unreachable!(),
             Err(i) => i + 1
         };
-        tidy_error!(bad, "{}:{}: platform-specific cfg: {}", file.display(), line, cfg);
+        bad.error_at(file, Some(line), format!("platform-specific cfg: {}", cfg));
     };
 
     for (idx, cfg) in cfgs.into_iter() {
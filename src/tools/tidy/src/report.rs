@@ -0,0 +1,189 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reporting of the findings of tidy checks.
+//!
+//! Every finding has the id of the check it comes from, and checks with
+//! several kinds of findings can give each kind an id of its own, like
+//! `linelength` for the `style` check. Findings about a file can be
+//! suppressed by naming either id:
+//!
+//! * for the whole file, with an `ignore-tidy-<id>` directive anywhere in it,
+//! * for a single line, with a `tidy-ignore(<id>)` comment at the end of that
+//!   line, or on a line of its own just before it. Several ids can be given,
+//!   separated by commas.
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub struct Report {
+    /// Whether findings are printed as JSON, one object per line.
+    pub json: bool,
+    /// Whether checks should only print a summary of what they looked at.
+    pub quiet: bool,
+    /// Whether findings are dropped rather than printed.
+    silent: bool,
+    check: &'static str,
+    failed: bool,
+    findings: usize,
+    /// The last file a finding was about, to look for suppressions in.
+    contents: Option<(PathBuf, String)>,
+}
+
+impl Report {
+    pub fn new(json: bool, quiet: bool) -> Report {
+        Report {
+            json: json,
+            quiet: quiet,
+            silent: false,
+            check: "",
+            failed: false,
+            findings: 0,
+            contents: None,
+        }
+    }
+
+    /// A report which drops all findings, for checks reusing parts of others.
+    pub fn silent() -> Report {
+        let mut report = Report::new(false, true);
+        report.silent = true;
+        report
+    }
+
+    /// Starts reporting the findings of the check named `check`.
+    pub fn start(&mut self, check: &'static str) {
+        self.check = check;
+        self.failed = false;
+    }
+
+    /// Whether the current check has reported any finding.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+
+    /// The number of findings reported so far by all checks.
+    pub fn findings(&self) -> usize {
+        self.findings
+    }
+
+    /// Reports a finding which isn't about any file in particular.
+    pub fn error(&mut self, msg: String) {
+        let check = self.check;
+        self.emit(check, None, None, &msg);
+    }
+
+    /// Reports a finding about `file`, or about one of its lines.
+    pub fn error_at(&mut self, file: &Path, line: Option<usize>, msg: String) {
+        let check = self.check;
+        self.lint_at(check, file, line, msg);
+    }
+
+    /// Like `error_at`, but for the finer-grained kind of finding `id`.
+    pub fn lint_at(&mut self, id: &'static str, file: &Path, line: Option<usize>, msg: String) {
+        if !self.suppressed(id, file, line) {
+            self.emit(id, Some(file), line, &msg);
+        }
+    }
+
+    fn emit(&mut self, id: &str, file: Option<&Path>, line: Option<usize>, msg: &str) {
+        self.failed = true;
+        self.findings += 1;
+        if self.silent {
+            return
+        }
+        let location = match (file, line) {
+            (Some(file), Some(line)) => format!("{}:{}: ", file.display(), line),
+            (Some(file), None) => format!("{}: ", file.display()),
+            (None, _) => String::new(),
+        };
+        let out = if self.json {
+            format!("{{\"check\":{},\"id\":{},\"file\":{},\"line\":{},\"message\":{}}}",
+                    json_str(self.check),
+                    json_str(id),
+                    file.map(|f| json_str(&f.display().to_string()))
+                        .unwrap_or("null".to_string()),
+                    line.map(|l| l.to_string()).unwrap_or("null".to_string()),
+                    json_str(msg))
+        } else {
+            format!("tidy error: {}{}", location, msg)
+        };
+        writeln!(io::stderr(), "{}", out).expect("could not write to stderr");
+    }
+
+    fn suppressed(&mut self, id: &str, file: &Path, line: Option<usize>) -> bool {
+        let stale = match self.contents {
+            Some((ref path, _)) => path != file,
+            None => true,
+        };
+        if stale {
+            let mut bytes = Vec::new();
+            if let Ok(mut f) = File::open(file) {
+                t!(f.read_to_end(&mut bytes), file);
+            }
+            let contents = String::from_utf8_lossy(&bytes).into_owned();
+            self.contents = Some((file.to_path_buf(), contents));
+        }
+        let contents = &self.contents.as_ref().unwrap().1;
+
+        let ids = [self.check, id];
+        if ids.iter().any(|id| contents.contains(&format!("ignore-tidy-{}", id))) {
+            return true
+        }
+        let line = match line {
+            Some(line) => line,
+            None => return false,
+        };
+        let lines = contents.lines().collect::<Vec<_>>();
+        let ignored = |line: &str| line_ignores(line).iter().any(|i| ids.contains(&&i[..]));
+        if lines.get(line - 1).map_or(false, |l| ignored(l)) {
+            return true
+        }
+        line > 1 && {
+            let prev = lines[line - 2].trim_left();
+            (prev.starts_with("//") || prev.starts_with("#")) && ignored(prev)
+        }
+    }
+}
+
+/// Returns the ids of the checks suppressed on `line` by `tidy-ignore` comments.
+fn line_ignores(line: &str) -> Vec<String> {
+    let marker = "tidy-ignore(";
+    let mut ids = Vec::new();
+    let mut rest = line;
+    while let Some(i) = rest.find(marker) {
+        rest = &rest[i + marker.len()..];
+        let end = match rest.find(')') {
+            Some(end) => end,
+            None => break,
+        };
+        ids.extend(rest[..end].split(',').map(|id| id.trim().to_string()));
+        rest = &rest[end..];
+    }
+    ids
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -20,12 +20,16 @@
 //! * A valid license header is at the top
 //!
 //! A number of these checks can be opted-out of with various directives like
-//! `// ignore-tidy-linelength`.
+//! `// ignore-tidy-linelength`, naming the ids `linelength`, `tab`,
+//! `end-whitespace`, `cr`, `todo`, `xxx` and `license` of the findings. These
+//! also work with `tidy-ignore(...)` for a single line.
 
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 
+use Report;
+
 const COLS: usize = 100;
 const LICENSE: &'static str = "\
 Copyright <year> The Rust Project Developers. See the COPYRIGHT
@@ -92,7 +96,7 @@ fn long_line_is_ok(line: &str) -> bool {
     false
 }
 
-pub fn check(path: &Path, bad: &mut bool) {
+pub fn check(path: &Path, bad: &mut Report) {
     let mut contents = String::new();
     super::walk(path, &mut super::filter_dirs, &mut |file| {
         let filename = file.file_name().unwrap().to_string_lossy();
@@ -109,41 +113,36 @@ pub fn check(path: &Path, bad: &mut bool) {
         t!(t!(File::open(file), file).read_to_string(&mut contents));
 
         if contents.is_empty() {
-            tidy_error!(bad, "{}: empty file", file.display());
+            bad.error_at(file, None, "empty file".to_string());
         }
 
-        let skip_cr = contents.contains("ignore-tidy-cr");
-        let skip_tab = contents.contains("ignore-tidy-tab");
-        let skip_length = contents.contains("ignore-tidy-linelength");
-        let skip_end_whitespace = contents.contains("ignore-tidy-end-whitespace");
         for (i, line) in contents.split("\n").enumerate() {
-            let mut err = |msg: &str| {
-                tidy_error!(bad, "{}:{}: {}", file.display(), i + 1, msg);
+            let mut err = |id: &'static str, msg: &str| {
+                bad.lint_at(id, file, Some(i + 1), msg.to_string());
             };
-            if !skip_length && line.chars().count() > COLS
-                && !long_line_is_ok(line) {
-                    err(&format!("line longer than {} chars", COLS));
+            if line.chars().count() > COLS && !long_line_is_ok(line) {
+                err("linelength", &format!("line longer than {} chars", COLS));
             }
-            if line.contains("\t") && !skip_tab {
-                err("tab character");
+            if line.contains("\t") {
+                err("tab", "tab character");
             }
-            if !skip_end_whitespace && (line.ends_with(" ") || line.ends_with("\t")) {
-                err("trailing whitespace");
+            if line.ends_with(" ") || line.ends_with("\t") {
+                err("end-whitespace", "trailing whitespace");
             }
-            if line.contains("\r") && !skip_cr {
-                err("CR character");
+            if line.contains("\r") {
+                err("cr", "CR character");
             }
             if filename != "style.rs" {
                 if line.contains("TODO") {
-                    err("TODO is deprecated; use FIXME")
+                    err("todo", "TODO is deprecated; use FIXME")
                 }
                 if line.contains("//") && line.contains(" XXX") {
-                    err("XXX is deprecated; use FIXME")
+                    err("xxx", "XXX is deprecated; use FIXME")
                 }
             }
         }
         if !licenseck(file, &contents) {
-            tidy_error!(bad, "{}: incorrect license", file.display());
+            bad.lint_at("license", file, None, "incorrect license".to_string());
         }
     })
 }
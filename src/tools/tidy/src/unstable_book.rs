@@ -12,6 +12,7 @@ use std::collections::HashSet;
 use std::fs;
 use std::path;
 use features::{collect_lang_features, collect_lib_features, Status};
+use Report;
 
 const PATH_STR: &'static str = "doc/unstable-book/src";
 
@@ -53,7 +54,7 @@ fn collect_unstable_lang_feature_names(base_src_path: &path::Path) -> HashSet<St
 
 /// Retrieve names of all lib-related unstable features
 fn collect_unstable_lib_feature_names(base_src_path: &path::Path) -> HashSet<String> {
-    let mut bad = true;
+    let mut bad = Report::silent();
     let lang_features = collect_lang_features(base_src_path);
     collect_lib_features(base_src_path, &mut bad, &lang_features)
         .into_iter()
@@ -62,6 +63,18 @@ fn collect_unstable_lib_feature_names(base_src_path: &path::Path) -> HashSet<Str
         .collect()
 }
 
+/// Retrieve names of all features which are stable, as lang or library features
+fn collect_stable_feature_names(base_src_path: &path::Path) -> HashSet<String> {
+    let mut bad = Report::silent();
+    let lang_features = collect_lang_features(base_src_path);
+    let lib_features = collect_lib_features(base_src_path, &mut bad, &lang_features);
+    lang_features.into_iter()
+        .chain(lib_features)
+        .filter(|&(_, ref f)| f.level == Status::Stable)
+        .map(|(name, _)| name)
+        .collect()
+}
+
 fn collect_unstable_book_section_file_names(dir: &path::Path) -> HashSet<String> {
     fs::read_dir(dir)
         .expect("could not read directory")
@@ -91,7 +104,11 @@ fn collect_unstable_book_lib_features_section_file_names(base_src_path: &path::P
     collect_unstable_book_section_file_names(&unstable_book_lib_features_path(base_src_path))
 }
 
-pub fn check(path: &path::Path, bad: &mut bool) {
+pub fn check(path: &path::Path, bad: &mut Report) {
+
+    // Sections for features which have since been stabilized are reported by
+    // the features check
+    let stable_feature_names = collect_stable_feature_names(path);
 
     // Library features
 
@@ -109,8 +126,8 @@ pub fn check(path: &path::Path, bad: &mut bool) {
     }
 
     // Check for Unstable Book sections that don't have a corresponding unstable feature
-    for feature_name in &unstable_book_lib_features_section_file_names -
-                        &unstable_lib_feature_names {
+    for feature_name in &(&unstable_book_lib_features_section_file_names -
+                          &unstable_lib_feature_names) - &stable_feature_names {
         tidy_error!(bad,
                     "The Unstable Book has a 'library feature' section '{}' which doesn't \
                      correspond to an unstable library feature",
@@ -132,8 +149,8 @@ pub fn check(path: &path::Path, bad: &mut bool) {
     }
 
     // Check for Unstable Book sections that don't have a corresponding unstable feature
-    for feature_name in &unstable_book_lang_features_section_file_names -
-                        &unstable_lang_feature_names {
+    for feature_name in &(&unstable_book_lang_features_section_file_names -
+                          &unstable_lang_feature_names) - &stable_feature_names {
         tidy_error!(bad,
                     "The Unstable Book has a 'language feature' section '{}' which doesn't \
                      correspond to an unstable language feature",